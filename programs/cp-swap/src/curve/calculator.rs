@@ -1,6 +1,12 @@
 //! Swap calculations
 
-use crate::curve::{constant_product::ConstantProductCurve, fees::Fees};
+use crate::curve::{
+    constant_product::ConstantProductCurve,
+    fees::{FeeRates, Fees},
+    stable::StableCurve,
+    weighted::{WeightedCurve, WEIGHT_DENOMINATOR},
+};
+use crate::utils::U256;
 use anchor_lang::prelude::*;
 use {crate::error::ErrorCode, std::fmt::Debug};
 
@@ -43,6 +49,179 @@ impl TradeDirection {
             TradeDirection::OneForZero => TradeDirection::ZeroForOne,
         }
     }
+
+    /// Orders the amounts of the source and destination token of a trade in
+    /// this direction as (token_0, token_1)
+    pub fn token_amounts<T>(&self, source: T, destination: T) -> (T, T) {
+        match self {
+            TradeDirection::ZeroForOne => (source, destination),
+            TradeDirection::OneForZero => (destination, source),
+        }
+    }
+}
+
+/// The stored discriminator of a curve, see `PoolState::curve_type`
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CurveType {
+    /// Uniswap-style x * y = k
+    ConstantProduct,
+    /// Curve.fi-style StableSwap with an amplification coefficient
+    Stable,
+    /// Balancer-style x^w_0 * y^w_1 = k with per pool weights
+    Weighted,
+}
+
+impl TryFrom<u8> for CurveType {
+    type Error = anchor_lang::error::Error;
+
+    fn try_from(curve_type: u8) -> Result<Self> {
+        match curve_type {
+            0 => Ok(CurveType::ConstantProduct),
+            1 => Ok(CurveType::Stable),
+            2 => Ok(CurveType::Weighted),
+            _ => err!(ErrorCode::InvalidCurve),
+        }
+    }
+}
+
+/// An invariant a pool can trade against. Implementations only do the curve
+/// math, fees are taken on top by `CurveCalculator`.
+pub trait Curve: Debug {
+    /// Calculate how much destination token will be provided given an amount
+    /// of source token, rounded down. None if the trade can't be done.
+    fn swap_base_input_without_fees(
+        &self,
+        source_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128>;
+
+    /// Calculate how much source token is needed to receive an amount of
+    /// destination token, rounded up. None if the trade can't be done.
+    fn swap_base_output_without_fees(
+        &self,
+        destination_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128>;
+
+    /// Get the amount of trading tokens for the given amount of pool tokens,
+    /// provided the total trading tokens and supply of pool tokens.
+    fn lp_tokens_to_trading_tokens(
+        &self,
+        lp_token_amount: u128,
+        lp_token_supply: u128,
+        swap_token_0_amount: u128,
+        swap_token_1_amount: u128,
+        round_direction: RoundDirection,
+    ) -> Option<TradingTokenResult>;
+
+    /// The value a swap must never decrease
+    fn invariant(&self, swap_token_0_amount: u128, swap_token_1_amount: u128) -> Option<u128>;
+}
+
+/// The invariant a pool trades against, selected by `PoolState::curve_type`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SwapCurve {
+    ConstantProduct,
+    Stable(StableCurve),
+    Weighted(WeightedCurve),
+}
+
+impl SwapCurve {
+    pub fn new(curve_type: u8, amp: u64, token_0_weight: u64, token_1_weight: u64) -> Result<Self> {
+        match CurveType::try_from(curve_type)? {
+            CurveType::ConstantProduct => Ok(SwapCurve::ConstantProduct),
+            CurveType::Stable => Ok(SwapCurve::Stable(StableCurve::new(amp)?)),
+            CurveType::Weighted => Ok(SwapCurve::Weighted(WeightedCurve::new(
+                token_0_weight,
+                token_1_weight,
+            )?)),
+        }
+    }
+
+    /// The discriminator stored for this curve
+    pub fn curve_type(&self) -> CurveType {
+        match self {
+            SwapCurve::ConstantProduct => CurveType::ConstantProduct,
+            SwapCurve::Stable(_) => CurveType::Stable,
+            SwapCurve::Weighted(_) => CurveType::Weighted,
+        }
+    }
+
+    /// The token weights, even unless the curve is weighted
+    pub fn weights(&self) -> (u64, u64) {
+        match self {
+            SwapCurve::Weighted(curve) => (curve.token_0_weight, curve.token_1_weight),
+            _ => (WEIGHT_DENOMINATOR / 2, WEIGHT_DENOMINATOR / 2),
+        }
+    }
+
+    /// The implementation of the selected invariant
+    pub fn as_curve(&self) -> &dyn Curve {
+        match self {
+            SwapCurve::ConstantProduct => &ConstantProductCurve,
+            SwapCurve::Stable(curve) => curve,
+            SwapCurve::Weighted(curve) => curve,
+        }
+    }
+}
+
+impl Curve for SwapCurve {
+    fn swap_base_input_without_fees(
+        &self,
+        source_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        self.as_curve().swap_base_input_without_fees(
+            source_amount,
+            swap_source_amount,
+            swap_destination_amount,
+            trade_direction,
+        )
+    }
+
+    fn swap_base_output_without_fees(
+        &self,
+        destination_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        self.as_curve().swap_base_output_without_fees(
+            destination_amount,
+            swap_source_amount,
+            swap_destination_amount,
+            trade_direction,
+        )
+    }
+
+    fn lp_tokens_to_trading_tokens(
+        &self,
+        lp_token_amount: u128,
+        lp_token_supply: u128,
+        swap_token_0_amount: u128,
+        swap_token_1_amount: u128,
+        round_direction: RoundDirection,
+    ) -> Option<TradingTokenResult> {
+        self.as_curve().lp_tokens_to_trading_tokens(
+            lp_token_amount,
+            lp_token_supply,
+            swap_token_0_amount,
+            swap_token_1_amount,
+            round_direction,
+        )
+    }
+
+    fn invariant(&self, swap_token_0_amount: u128, swap_token_1_amount: u128) -> Option<u128> {
+        self.as_curve()
+            .invariant(swap_token_0_amount, swap_token_1_amount)
+    }
 }
 
 /// Encodes results of depositing both sides at once
@@ -71,6 +250,8 @@ pub struct SwapResult {
     pub protocol_fee: u128,
     /// Amount of source tokens going to protocol team
     pub fund_fee: u128,
+    /// Amount of source tokens going to the pool creator
+    pub creator_fee: u128,
 }
 
 /// Concrete struct to wrap around the trait object which performs calculation.
@@ -91,25 +272,27 @@ impl CurveCalculator {
     /// Subtract fees and calculate how much destination token will be provided
     /// given an amount of source token.
     pub fn swap_base_input(
+        curve: &dyn Curve,
+        trade_direction: TradeDirection,
         source_amount: u128,
         swap_source_amount: u128,
         swap_destination_amount: u128,
-        trade_fee_rate: u64,
-        protocol_fee_rate: u64,
-        fund_fee_rate: u64,
+        fee_rates: &FeeRates,
     ) -> Option<SwapResult> {
         // debit the fee to calculate the amount swapped
-        let trade_fee = Fees::trading_fee(source_amount, trade_fee_rate)?;
-        let protocol_fee = Fees::protocol_fee(trade_fee, protocol_fee_rate)?;
-        let fund_fee = Fees::fund_fee(trade_fee, fund_fee_rate)?;
+        let trade_fee = Fees::trading_fee(source_amount, fee_rates.trade_fee_rate)?;
+        let protocol_fee = Fees::protocol_fee(trade_fee, fee_rates.protocol_fee_rate)?;
+        let fund_fee = Fees::fund_fee(trade_fee, fee_rates.fund_fee_rate)?;
+        let creator_fee = Fees::creator_fee(trade_fee, fee_rates.creator_fee_rate)?;
 
         let source_amount_less_fees = source_amount.checked_sub(trade_fee)?;
 
-        let destination_amount_swapped = ConstantProductCurve::swap_base_input_without_fees(
+        let destination_amount_swapped = curve.swap_base_input_without_fees(
             source_amount_less_fees,
             swap_source_amount,
             swap_destination_amount,
-        );
+            trade_direction,
+        )?;
 
         Some(SwapResult {
             new_swap_source_amount: swap_source_amount.checked_add(source_amount)?,
@@ -120,28 +303,32 @@ impl CurveCalculator {
             trade_fee,
             protocol_fee,
             fund_fee,
+            creator_fee,
         })
     }
 
     pub fn swap_base_output(
+        curve: &dyn Curve,
+        trade_direction: TradeDirection,
         destinsation_amount: u128,
         swap_source_amount: u128,
         swap_destination_amount: u128,
-        trade_fee_rate: u64,
-        protocol_fee_rate: u64,
-        fund_fee_rate: u64,
+        fee_rates: &FeeRates,
     ) -> Option<SwapResult> {
-        let source_amount_swapped = ConstantProductCurve::swap_base_output_without_fees(
+        let source_amount_swapped = curve.swap_base_output_without_fees(
             destinsation_amount,
             swap_source_amount,
             swap_destination_amount,
-        );
+            trade_direction,
+        )?;
 
         let source_amount =
-            Fees::calculate_pre_fee_amount(source_amount_swapped, trade_fee_rate).unwrap();
-        let trade_fee = Fees::trading_fee(source_amount, trade_fee_rate)?;
-        let protocol_fee = Fees::protocol_fee(trade_fee, protocol_fee_rate)?;
-        let fund_fee = Fees::fund_fee(trade_fee, fund_fee_rate)?;
+            Fees::calculate_pre_fee_amount(source_amount_swapped, fee_rates.trade_fee_rate)
+                .unwrap();
+        let trade_fee = Fees::trading_fee(source_amount, fee_rates.trade_fee_rate)?;
+        let protocol_fee = Fees::protocol_fee(trade_fee, fee_rates.protocol_fee_rate)?;
+        let fund_fee = Fees::fund_fee(trade_fee, fee_rates.fund_fee_rate)?;
+        let creator_fee = Fees::creator_fee(trade_fee, fee_rates.creator_fee_rate)?;
 
         Some(SwapResult {
             new_swap_source_amount: swap_source_amount.checked_add(source_amount)?,
@@ -152,19 +339,85 @@ impl CurveCalculator {
             trade_fee,
             protocol_fee,
             fund_fee,
+            creator_fee,
         })
     }
 
+    /// Get the largest amount of pool tokens the given trading tokens can be
+    /// deposited for, rounded down in favor of the pool.
+    pub fn trading_tokens_to_lp_tokens(
+        token_0_amount: u128,
+        token_1_amount: u128,
+        lp_token_supply: u128,
+        swap_token_0_amount: u128,
+        swap_token_1_amount: u128,
+    ) -> Option<u128> {
+        let lp_token_0_amount = token_0_amount
+            .checked_mul(lp_token_supply)?
+            .checked_div(swap_token_0_amount)?;
+        let lp_token_1_amount = token_1_amount
+            .checked_mul(lp_token_supply)?
+            .checked_div(swap_token_1_amount)?;
+        Some(lp_token_0_amount.min(lp_token_1_amount))
+    }
+
+    /// Find how much of a single sided deposit of source_amount to swap, so
+    /// that the rest of it and the swap output match the pool ratio after the
+    /// swap. Returns the largest such amount, the remainder on the destination
+    /// side is at most rounding dust.
+    pub fn single_side_swap_amount(
+        curve: &dyn Curve,
+        trade_direction: TradeDirection,
+        source_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        fee_rates: &FeeRates,
+    ) -> Option<u128> {
+        // the leftover source shrinks and the swap output grows with the
+        // swapped amount, binary search where they cross
+        let (mut low, mut high) = (0u128, source_amount);
+        while low < high {
+            let mid = low + (high - low).div_ceil(2);
+            // a curve refusing the swap means it is too large
+            let Some(result) = Self::swap_base_input(
+                curve,
+                trade_direction,
+                mid,
+                swap_source_amount,
+                swap_destination_amount,
+                fee_rates,
+            ) else {
+                high = mid - 1;
+                continue;
+            };
+            let new_swap_source_amount = result
+                .new_swap_source_amount
+                .checked_sub(result.protocol_fee)?
+                .checked_sub(result.fund_fee)?
+                .checked_sub(result.creator_fee)?;
+            if U256::from(source_amount - mid) * U256::from(result.new_swap_destination_amount)
+                >= U256::from(result.destination_amount_swapped)
+                    * U256::from(new_swap_source_amount)
+            {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+        Some(low)
+    }
+
     /// Get the amount of trading tokens for the given amount of pool tokens,
     /// provided the total trading tokens and supply of pool tokens.
     pub fn lp_tokens_to_trading_tokens(
+        curve: &dyn Curve,
         lp_token_amount: u128,
         lp_token_supply: u128,
         swap_token_0_amount: u128,
         swap_token_1_amount: u128,
         round_direction: RoundDirection,
     ) -> Option<TradingTokenResult> {
-        ConstantProductCurve::lp_tokens_to_trading_tokens(
+        curve.lp_tokens_to_trading_tokens(
             lp_token_amount,
             lp_token_supply,
             swap_token_0_amount,
@@ -178,7 +431,10 @@ impl CurveCalculator {
 #[cfg(test)]
 pub mod test {
     use {
-        super::*, proptest::prelude::*, spl_math::precise_number::PreciseNumber,
+        super::*,
+        crate::curve::{MAX_AMP, MIN_AMP, MIN_WEIGHT, WEIGHT_DENOMINATOR},
+        proptest::prelude::*,
+        spl_math::precise_number::PreciseNumber,
         spl_math::uint::U256,
     };
 
@@ -212,36 +468,38 @@ pub mod test {
     /// This test guarantees that the relative change in value will be at most
     /// 1 normalized token, and that the value will never decrease from a trade.
     pub fn check_curve_value_from_swap(
+        curve: &dyn Curve,
         source_token_amount: u128,
         swap_source_amount: u128,
         swap_destination_amount: u128,
         trade_direction: TradeDirection,
     ) {
-        let destination_amount_swapped = ConstantProductCurve::swap_base_input_without_fees(
+        let destination_amount_swapped = match curve.swap_base_input_without_fees(
             source_token_amount,
             swap_source_amount,
             swap_destination_amount,
-        );
-
-        let (swap_token_0_amount, swap_token_1_amount) = match trade_direction {
-            TradeDirection::ZeroForOne => (swap_source_amount, swap_destination_amount),
-            TradeDirection::OneForZero => (swap_destination_amount, swap_source_amount),
+            trade_direction,
+        ) {
+            Some(amount) => amount,
+            // a curve may refuse the trade, which never loses value
+            None => return,
         };
-        let previous_value = swap_token_0_amount
-            .checked_mul(swap_token_1_amount)
+
+        let (swap_token_0_amount, swap_token_1_amount) =
+            trade_direction.token_amounts(swap_source_amount, swap_destination_amount);
+        let previous_value = curve
+            .invariant(swap_token_0_amount, swap_token_1_amount)
             .unwrap();
 
         let new_swap_source_amount = swap_source_amount.checked_add(source_token_amount).unwrap();
         let new_swap_destination_amount = swap_destination_amount
             .checked_sub(destination_amount_swapped)
             .unwrap();
-        let (swap_token_0_amount, swap_token_1_amount) = match trade_direction {
-            TradeDirection::ZeroForOne => (new_swap_source_amount, new_swap_destination_amount),
-            TradeDirection::OneForZero => (new_swap_destination_amount, new_swap_source_amount),
-        };
+        let (swap_token_0_amount, swap_token_1_amount) =
+            trade_direction.token_amounts(new_swap_source_amount, new_swap_destination_amount);
 
-        let new_value = swap_token_0_amount
-            .checked_mul(swap_token_1_amount)
+        let new_value = curve
+            .invariant(swap_token_0_amount, swap_token_1_amount)
             .unwrap();
         assert!(new_value >= previous_value);
     }
@@ -253,19 +511,21 @@ pub mod test {
     /// truncation at some point, meaning a potential for value to be lost if
     /// too much is given to the depositor.
     pub fn check_pool_value_from_deposit(
+        curve: &dyn Curve,
         lp_token_amount: u128,
         lp_token_supply: u128,
         swap_token_0_amount: u128,
         swap_token_1_amount: u128,
     ) {
-        let deposit_result = CurveCalculator::lp_tokens_to_trading_tokens(
-            lp_token_amount,
-            lp_token_supply,
-            swap_token_0_amount,
-            swap_token_1_amount,
-            RoundDirection::Ceiling,
-        )
-        .unwrap();
+        let deposit_result = curve
+            .lp_tokens_to_trading_tokens(
+                lp_token_amount,
+                lp_token_supply,
+                swap_token_0_amount,
+                swap_token_1_amount,
+                RoundDirection::Ceiling,
+            )
+            .unwrap();
         let new_swap_token_0_amount = swap_token_0_amount + deposit_result.token_0_amount;
         let new_swap_token_1_amount = swap_token_1_amount + deposit_result.token_1_amount;
         let new_lp_token_supply = lp_token_supply + lp_token_amount;
@@ -300,19 +560,21 @@ pub mod test {
     /// truncation at some point, meaning a potential for value to be lost if
     /// too much is given to the depositor.
     pub fn check_pool_value_from_withdraw(
+        curve: &dyn Curve,
         lp_token_amount: u128,
         lp_token_supply: u128,
         swap_token_0_amount: u128,
         swap_token_1_amount: u128,
     ) {
-        let withdraw_result = CurveCalculator::lp_tokens_to_trading_tokens(
-            lp_token_amount,
-            lp_token_supply,
-            swap_token_0_amount,
-            swap_token_1_amount,
-            RoundDirection::Floor,
-        )
-        .unwrap();
+        let withdraw_result = curve
+            .lp_tokens_to_trading_tokens(
+                lp_token_amount,
+                lp_token_supply,
+                swap_token_0_amount,
+                swap_token_1_amount,
+                RoundDirection::Floor,
+            )
+            .unwrap();
         let new_swap_token_0_amount = swap_token_0_amount - withdraw_result.token_0_amount;
         let new_swap_token_1_amount = swap_token_1_amount - withdraw_result.token_1_amount;
         let new_pool_token_supply = lp_token_supply - lp_token_amount;
@@ -343,4 +605,269 @@ pub mod test {
            (total, intermediate)
        }
     }
+
+    prop_compose! {
+        /// Any curve a pool can be created with
+        pub fn swap_curve()(
+            curve_type in 0..=CurveType::Weighted as u8,
+            amp in MIN_AMP..=MAX_AMP,
+            token_0_weight in MIN_WEIGHT..=WEIGHT_DENOMINATOR - MIN_WEIGHT,
+        ) -> SwapCurve {
+            SwapCurve::new(curve_type, amp, token_0_weight, WEIGHT_DENOMINATOR - token_0_weight)
+                .unwrap()
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn any_curve_value_does_not_decrease_from_swap(
+            curve in swap_curve(),
+            source_token_amount in 1..u64::MAX,
+            swap_source_amount in 1..u64::MAX,
+            swap_destination_amount in 1..u64::MAX,
+        ) {
+            for trade_direction in [TradeDirection::ZeroForOne, TradeDirection::OneForZero] {
+                check_curve_value_from_swap(
+                    &curve,
+                    source_token_amount as u128,
+                    swap_source_amount as u128,
+                    swap_destination_amount as u128,
+                    trade_direction,
+                );
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn any_curve_value_does_not_decrease_from_deposit(
+            curve in swap_curve(),
+            lp_token_amount in 1..u64::MAX,
+            lp_token_supply in 1..u64::MAX,
+            swap_token_0_amount in 1..u64::MAX,
+            swap_token_1_amount in 1..u64::MAX,
+        ) {
+            // Make sure we will get at least one trading token out for each
+            // side, otherwise the calculation fails
+            prop_assume!(lp_token_amount as u128 * swap_token_0_amount as u128 / lp_token_supply as u128 >= 1);
+            prop_assume!(lp_token_amount as u128 * swap_token_1_amount as u128 / lp_token_supply as u128 >= 1);
+            check_pool_value_from_deposit(
+                &curve,
+                lp_token_amount as u128,
+                lp_token_supply as u128,
+                swap_token_0_amount as u128,
+                swap_token_1_amount as u128,
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn trading_tokens_to_lp_tokens_fits_in_amounts(
+            curve in swap_curve(),
+            token_0_amount in 1..u64::MAX,
+            token_1_amount in 1..u64::MAX,
+            lp_token_supply in 1..u64::MAX,
+            swap_token_0_amount in 1..u64::MAX,
+            swap_token_1_amount in 1..u64::MAX,
+        ) {
+            let lp_token_amount = CurveCalculator::trading_tokens_to_lp_tokens(
+                token_0_amount as u128,
+                token_1_amount as u128,
+                lp_token_supply as u128,
+                swap_token_0_amount as u128,
+                swap_token_1_amount as u128,
+            )
+            .unwrap();
+            let results = CurveCalculator::lp_tokens_to_trading_tokens(
+                &curve,
+                lp_token_amount,
+                lp_token_supply as u128,
+                swap_token_0_amount as u128,
+                swap_token_1_amount as u128,
+                RoundDirection::Ceiling,
+            )
+            .unwrap();
+            prop_assert!(results.token_0_amount <= token_0_amount as u128);
+            prop_assert!(results.token_1_amount <= token_1_amount as u128);
+        }
+    }
+
+    const FEE_RATES: FeeRates = FeeRates {
+        trade_fee_rate: 2_500,
+        protocol_fee_rate: 120_000,
+        fund_fee_rate: 40_000,
+        creator_fee_rate: 10_000,
+    };
+
+    #[test]
+    fn single_side_swap_amount_constant_product() {
+        // without fees the optimal swap is R * (sqrt(1 + a / R) - 1)
+        let swap_amount = CurveCalculator::single_side_swap_amount(
+            &SwapCurve::ConstantProduct,
+            TradeDirection::ZeroForOne,
+            100_000,
+            1_000_000,
+            2_000_000,
+            &FeeRates::default(),
+        )
+        .unwrap();
+        assert_eq!(swap_amount, 48_808);
+
+        // fees make the swap less efficient, so more has to be swapped
+        let swap_amount_with_fees = CurveCalculator::single_side_swap_amount(
+            &SwapCurve::ConstantProduct,
+            TradeDirection::ZeroForOne,
+            100_000,
+            1_000_000,
+            2_000_000,
+            &FEE_RATES,
+        )
+        .unwrap();
+        assert!(swap_amount_with_fees > swap_amount);
+    }
+
+    /// Constant product curve refusing swaps above `max_source_amount`
+    #[derive(Debug)]
+    struct CappedCurve {
+        max_source_amount: u128,
+    }
+
+    impl Curve for CappedCurve {
+        fn swap_base_input_without_fees(
+            &self,
+            source_amount: u128,
+            swap_source_amount: u128,
+            swap_destination_amount: u128,
+            trade_direction: TradeDirection,
+        ) -> Option<u128> {
+            if source_amount > self.max_source_amount {
+                return None;
+            }
+            ConstantProductCurve.swap_base_input_without_fees(
+                source_amount,
+                swap_source_amount,
+                swap_destination_amount,
+                trade_direction,
+            )
+        }
+
+        fn swap_base_output_without_fees(
+            &self,
+            destination_amount: u128,
+            swap_source_amount: u128,
+            swap_destination_amount: u128,
+            trade_direction: TradeDirection,
+        ) -> Option<u128> {
+            ConstantProductCurve.swap_base_output_without_fees(
+                destination_amount,
+                swap_source_amount,
+                swap_destination_amount,
+                trade_direction,
+            )
+        }
+
+        fn lp_tokens_to_trading_tokens(
+            &self,
+            lp_token_amount: u128,
+            lp_token_supply: u128,
+            swap_token_0_amount: u128,
+            swap_token_1_amount: u128,
+            round_direction: RoundDirection,
+        ) -> Option<TradingTokenResult> {
+            ConstantProductCurve.lp_tokens_to_trading_tokens(
+                lp_token_amount,
+                lp_token_supply,
+                swap_token_0_amount,
+                swap_token_1_amount,
+                round_direction,
+            )
+        }
+
+        fn invariant(&self, swap_token_0_amount: u128, swap_token_1_amount: u128) -> Option<u128> {
+            ConstantProductCurve.invariant(swap_token_0_amount, swap_token_1_amount)
+        }
+    }
+
+    #[test]
+    fn single_side_swap_amount_skips_refused_swaps() {
+        // the first midpoint is refused, the search goes on below it
+        let swap_amount = CurveCalculator::single_side_swap_amount(
+            &CappedCurve {
+                max_source_amount: 30_000,
+            },
+            TradeDirection::ZeroForOne,
+            100_000,
+            1_000_000,
+            2_000_000,
+            &FeeRates::default(),
+        )
+        .unwrap();
+        assert_eq!(swap_amount, 30_000);
+    }
+
+    proptest! {
+        #[test]
+        fn any_curve_single_side_swap_matches_pool_ratio(
+            curve in swap_curve(),
+            source_amount in 1..u32::MAX as u64,
+            swap_source_amount in 1_000..u32::MAX as u64,
+            swap_destination_amount in 1_000..u32::MAX as u64,
+        ) {
+            let swap = |amount: u128| {
+                let result = CurveCalculator::swap_base_input(
+                    &curve,
+                    TradeDirection::ZeroForOne,
+                    amount,
+                    swap_source_amount as u128,
+                    swap_destination_amount as u128,
+                    &FEE_RATES,
+                )
+                .unwrap();
+                // leftover source and swap output, scaled to the pool after the swap
+                (
+                    (source_amount as u128 - amount)
+                        * result.new_swap_destination_amount,
+                    result.destination_amount_swapped
+                        * (result.new_swap_source_amount
+                            - result.protocol_fee
+                            - result.fund_fee
+                            - result.creator_fee),
+                )
+            };
+            let swap_amount = CurveCalculator::single_side_swap_amount(
+                &curve,
+                TradeDirection::ZeroForOne,
+                source_amount as u128,
+                swap_source_amount as u128,
+                swap_destination_amount as u128,
+                &FEE_RATES,
+            )
+            .unwrap();
+            let (source_value, destination_value) = swap(swap_amount);
+            prop_assert!(source_value >= destination_value);
+            if swap_amount < source_amount as u128 {
+                let (source_value, destination_value) = swap(swap_amount + 1);
+                prop_assert!(source_value < destination_value);
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn any_curve_value_does_not_decrease_from_withdraw(
+            curve in swap_curve(),
+            (lp_token_supply, lp_token_amount) in total_and_intermediate(u64::MAX),
+            swap_token_0_amount in 1..u64::MAX,
+            swap_token_1_amount in 1..u64::MAX,
+        ) {
+            check_pool_value_from_withdraw(
+                &curve,
+                lp_token_amount as u128,
+                lp_token_supply as u128,
+                swap_token_0_amount as u128,
+                swap_token_1_amount as u128,
+            );
+        }
+    }
 }
@@ -1,8 +1,8 @@
 //! The Uniswap invariantConstantProductCurve::
 
 use crate::{
-    curve::calculator::{RoundDirection, TradingTokenResult},
-    utils::CheckedCeilDiv,
+    curve::calculator::{Curve, RoundDirection, TradeDirection, TradingTokenResult},
+    utils::{CheckedCeilDiv, U256},
 };
 
 /// ConstantProductCurve struct implementing CurveCalculator
@@ -90,6 +90,85 @@ impl ConstantProductCurve {
             token_1_amount,
         })
     }
+
+    /// Largest source amount that keeps the token_0 price, token_1 per
+    /// token_0 as a Q32, at or beyond `price_limit_x32` after the swap.
+    ///
+    /// The bound is taken on the full source amount, the trade fee only grows
+    /// the invariant and so moves the price less than the bound assumes.
+    pub fn max_source_amount_for_price_limit(
+        price_limit_x32: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        // (x + delta_x) * (y - delta_y) = x * y = k
+        // selling token_0 moves its price to k / x'^2, selling token_1 to y'^2 / k
+        let invariant =
+            U256::from(swap_source_amount).checked_mul(U256::from(swap_destination_amount))?;
+        let target_source_amount_squared = match trade_direction {
+            TradeDirection::ZeroForOne => {
+                (invariant << 32).checked_div(U256::from(price_limit_x32))?
+            }
+            TradeDirection::OneForZero => invariant.checked_mul(U256::from(price_limit_x32))? >> 32,
+        };
+        let source_amount = target_source_amount_squared
+            .integer_sqrt()
+            .saturating_sub(U256::from(swap_source_amount));
+        Some(source_amount.min(U256::from(u128::MAX)).as_u128())
+    }
+}
+
+impl Curve for ConstantProductCurve {
+    fn swap_base_input_without_fees(
+        &self,
+        source_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        _trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        Some(ConstantProductCurve::swap_base_input_without_fees(
+            source_amount,
+            swap_source_amount,
+            swap_destination_amount,
+        ))
+    }
+
+    fn swap_base_output_without_fees(
+        &self,
+        destination_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        _trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        Some(ConstantProductCurve::swap_base_output_without_fees(
+            destination_amount,
+            swap_source_amount,
+            swap_destination_amount,
+        ))
+    }
+
+    fn lp_tokens_to_trading_tokens(
+        &self,
+        lp_token_amount: u128,
+        lp_token_supply: u128,
+        swap_token_0_amount: u128,
+        swap_token_1_amount: u128,
+        round_direction: RoundDirection,
+    ) -> Option<TradingTokenResult> {
+        ConstantProductCurve::lp_tokens_to_trading_tokens(
+            lp_token_amount,
+            lp_token_supply,
+            swap_token_0_amount,
+            swap_token_1_amount,
+            round_direction,
+        )
+    }
+
+    /// x * y
+    fn invariant(&self, swap_token_0_amount: u128, swap_token_1_amount: u128) -> Option<u128> {
+        swap_token_0_amount.checked_mul(swap_token_1_amount)
+    }
 }
 
 #[cfg(test)]
@@ -101,7 +180,7 @@ mod tests {
                 check_curve_value_from_swap, check_pool_value_from_deposit,
                 check_pool_value_from_withdraw, total_and_intermediate,
             },
-            RoundDirection, TradeDirection,
+            RoundDirection, SwapCurve, TradeDirection,
         },
         proptest::prelude::*,
     };
@@ -226,6 +305,7 @@ mod tests {
             swap_destination_amount in 1..u64::MAX,
         ) {
             check_curve_value_from_swap(
+                &SwapCurve::ConstantProduct,
                 source_token_amount as u128,
                 swap_source_amount as u128,
                 swap_destination_amount as u128,
@@ -234,6 +314,75 @@ mod tests {
         }
     }
 
+    #[test]
+    fn max_source_amount_for_price_limit() {
+        // 1_000 token_0 against 4_000 token_1 trades at 4, selling token_0
+        // down to a price of 1 takes the pool to 2_000 of each
+        let amount = ConstantProductCurve::max_source_amount_for_price_limit(
+            1 << 32,
+            1_000,
+            4_000,
+            TradeDirection::ZeroForOne,
+        );
+        assert_eq!(amount, Some(1_000));
+        // and buying token_0 back up to 16 takes token_1 to 8_000
+        let amount = ConstantProductCurve::max_source_amount_for_price_limit(
+            16 << 32,
+            4_000,
+            1_000,
+            TradeDirection::OneForZero,
+        );
+        assert_eq!(amount, Some(4_000));
+        // a limit the price already passed leaves nothing to fill
+        let amount = ConstantProductCurve::max_source_amount_for_price_limit(
+            8 << 32,
+            1_000,
+            4_000,
+            TradeDirection::ZeroForOne,
+        );
+        assert_eq!(amount, Some(0));
+    }
+
+    proptest! {
+        #[test]
+        fn price_limit_holds_after_swap(
+            swap_token_0_amount in 1..u32::MAX as u128,
+            swap_token_1_amount in 1..u32::MAX as u128,
+            price_limit_x32 in 1..u64::MAX as u128,
+        ) {
+            let amount = ConstantProductCurve::max_source_amount_for_price_limit(
+                price_limit_x32,
+                swap_token_0_amount,
+                swap_token_1_amount,
+                TradeDirection::ZeroForOne,
+            ).unwrap();
+            let amount_out = ConstantProductCurve::swap_base_input_without_fees(
+                amount,
+                swap_token_0_amount,
+                swap_token_1_amount,
+            );
+            let price_x32 = ((swap_token_1_amount - amount_out) << 32)
+                / (swap_token_0_amount + amount);
+            prop_assert!(amount == 0 || price_x32 >= price_limit_x32);
+
+            let amount = ConstantProductCurve::max_source_amount_for_price_limit(
+                price_limit_x32,
+                swap_token_1_amount,
+                swap_token_0_amount,
+                TradeDirection::OneForZero,
+            ).unwrap();
+            let amount_out = ConstantProductCurve::swap_base_input_without_fees(
+                amount,
+                swap_token_1_amount,
+                swap_token_0_amount,
+            );
+            prop_assume!(amount_out < swap_token_0_amount);
+            let price_x32 = ((swap_token_1_amount + amount) << 32)
+                / (swap_token_0_amount - amount_out);
+            prop_assert!(amount == 0 || price_x32 <= price_limit_x32);
+        }
+    }
+
     proptest! {
         #[test]
         fn curve_value_does_not_decrease_from_deposit(
@@ -251,6 +400,7 @@ mod tests {
             prop_assume!(pool_token_amount * swap_token_a_amount / pool_token_supply >= 1);
             prop_assume!(pool_token_amount * swap_token_b_amount / pool_token_supply >= 1);
             check_pool_value_from_deposit(
+                &SwapCurve::ConstantProduct,
                 pool_token_amount,
                 pool_token_supply,
                 swap_token_a_amount,
@@ -275,6 +425,7 @@ mod tests {
             prop_assume!(pool_token_amount * swap_token_a_amount / pool_token_supply >= 1);
             prop_assume!(pool_token_amount * swap_token_b_amount / pool_token_supply >= 1);
             check_pool_value_from_withdraw(
+                &SwapCurve::ConstantProduct,
                 pool_token_amount,
                 pool_token_supply,
                 swap_token_a_amount,
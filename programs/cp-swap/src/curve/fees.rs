@@ -1,9 +1,27 @@
 //! All fee information, to be used for validation currently
 
+use crate::utils::{exp2_x64, ONE_X64};
+
 pub const FEE_RATE_DENOMINATOR_VALUE: u64 = 1_000_000;
 
+/// The launch fee falls linearly to the trade fee
+pub const LAUNCH_FEE_DECAY_LINEAR: u8 = 0;
+/// The launch fee halves LAUNCH_FEE_HALVINGS times over the launch period
+pub const LAUNCH_FEE_DECAY_EXPONENTIAL: u8 = 1;
+pub const LAUNCH_FEE_HALVINGS: u64 = 10;
+
 pub struct Fees {}
 
+/// Fee rates charged on a swap, out of FEE_RATE_DENOMINATOR_VALUE. All but the
+/// trade fee rate are shares of the trade fee.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FeeRates {
+    pub trade_fee_rate: u64,
+    pub protocol_fee_rate: u64,
+    pub fund_fee_rate: u64,
+    pub creator_fee_rate: u64,
+}
+
 fn ceil_div(token_amount: u128, fee_numerator: u128, fee_denominator: u128) -> Option<u128> {
     token_amount
         .checked_mul(u128::from(fee_numerator))
@@ -50,6 +68,24 @@ impl Fees {
         )
     }
 
+    /// Calculate the pool creator fee in trading tokens
+    pub fn creator_fee(amount: u128, creator_fee_rate: u64) -> Option<u128> {
+        floor_div(
+            amount,
+            u128::from(creator_fee_rate),
+            u128::from(FEE_RATE_DENOMINATOR_VALUE),
+        )
+    }
+
+    /// Calculate the referrer share of the protocol fee in trading tokens
+    pub fn referral_fee(protocol_fee: u128, referral_fee_rate: u64) -> Option<u128> {
+        floor_div(
+            protocol_fee,
+            u128::from(referral_fee_rate),
+            u128::from(FEE_RATE_DENOMINATOR_VALUE),
+        )
+    }
+
     pub fn calculate_pre_fee_amount(post_fee_amount: u128, trade_fee_rate: u64) -> Option<u128> {
         if trade_fee_rate == 0 {
             Some(post_fee_amount)
@@ -64,4 +100,140 @@ impl Fees {
                 .checked_div(denominator)
         }
     }
+
+    /// Calculate the trade fee rate of a pool in dynamic fee mode.
+    ///
+    /// The base rate is raised by the relative distance between the spot price
+    /// and the reference (time weighted) price, so a 1% move adds 1% of fee,
+    /// capped at max_fee_rate. The added fee decays linearly to zero over
+    /// decay_period seconds since the reference price was observed.
+    pub fn dynamic_fee_rate(
+        base_fee_rate: u64,
+        max_fee_rate: u64,
+        decay_period: u64,
+        spot_price_x32: u128,
+        reference_price_x32: u128,
+        elapsed: u64,
+    ) -> u64 {
+        if max_fee_rate <= base_fee_rate || reference_price_x32 == 0 || elapsed >= decay_period {
+            return base_fee_rate;
+        }
+        let deviation_rate = spot_price_x32
+            .abs_diff(reference_price_x32)
+            .saturating_mul(u128::from(FEE_RATE_DENOMINATOR_VALUE))
+            / reference_price_x32;
+        let variable_fee_rate = deviation_rate.saturating_mul(u128::from(decay_period - elapsed))
+            / u128::from(decay_period);
+        u128::from(base_fee_rate)
+            .saturating_add(variable_fee_rate)
+            .min(u128::from(max_fee_rate)) as u64
+    }
+
+    /// Calculate the trade fee rate of a pool during its launch period.
+    ///
+    /// Starts at launch_fee_rate at open_time and decays to the base rate
+    /// over duration seconds, see `LAUNCH_FEE_DECAY_LINEAR` and
+    /// `LAUNCH_FEE_DECAY_EXPONENTIAL`.
+    pub fn launch_fee_rate(
+        base_fee_rate: u64,
+        launch_fee_rate: u64,
+        duration: u64,
+        decay: u8,
+        elapsed: u64,
+    ) -> u64 {
+        if launch_fee_rate <= base_fee_rate || elapsed >= duration {
+            return base_fee_rate;
+        }
+        let extra_fee_rate = u128::from(launch_fee_rate - base_fee_rate);
+        let extra_fee_rate = if decay == LAUNCH_FEE_DECAY_EXPONENTIAL {
+            // 2^(-halvings * elapsed / duration)
+            let exponent_x64 =
+                (u128::from(LAUNCH_FEE_HALVINGS * elapsed) << 64) / u128::from(duration);
+            extra_fee_rate * ONE_X64 / exp2_x64(exponent_x64).unwrap()
+        } else {
+            extra_fee_rate * u128::from(duration - elapsed) / u128::from(duration)
+        };
+        base_fee_rate + extra_fee_rate as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn referral_fee_splits_protocol_fee() {
+        let trade_fee = Fees::trading_fee(1_000_000_000, 2_500).unwrap();
+        let protocol_fee = Fees::protocol_fee(trade_fee, 120_000).unwrap();
+        assert_eq!((trade_fee, protocol_fee), (2_500_000, 300_000));
+        // the referrer takes its share, the protocol keeps the rest
+        let referral_fee = Fees::referral_fee(protocol_fee, 200_000).unwrap();
+        assert_eq!(referral_fee, 60_000);
+        assert_eq!(protocol_fee - referral_fee, 240_000);
+        // rounded down in favor of the protocol
+        assert_eq!(Fees::referral_fee(9, 500_000), Some(4));
+        assert_eq!(Fees::referral_fee(protocol_fee, 0), Some(0));
+        assert_eq!(
+            Fees::referral_fee(protocol_fee, FEE_RATE_DENOMINATOR_VALUE),
+            Some(protocol_fee)
+        );
+    }
+
+    #[test]
+    fn dynamic_fee_rate_follows_price_deviation() {
+        // no move, no extra fee
+        assert_eq!(
+            Fees::dynamic_fee_rate(2_500, 50_000, 300, 1 << 32, 1 << 32, 0),
+            2_500
+        );
+        // a 1% move adds 1% of fee
+        assert_eq!(
+            Fees::dynamic_fee_rate(2_500, 50_000, 300, 101 << 32, 100 << 32, 0),
+            12_500
+        );
+        // half way through the decay period half of it is left
+        assert_eq!(
+            Fees::dynamic_fee_rate(2_500, 50_000, 300, 99 << 32, 100 << 32, 150),
+            7_500
+        );
+        // capped at the max rate
+        assert_eq!(
+            Fees::dynamic_fee_rate(2_500, 50_000, 300, 200 << 32, 100 << 32, 0),
+            50_000
+        );
+        // fully decayed, or disabled
+        assert_eq!(
+            Fees::dynamic_fee_rate(2_500, 50_000, 300, 200 << 32, 100 << 32, 300),
+            2_500
+        );
+        assert_eq!(
+            Fees::dynamic_fee_rate(2_500, 0, 300, 200 << 32, 100 << 32, 0),
+            2_500
+        );
+    }
+
+    #[test]
+    fn launch_fee_rate_decays_to_base_rate() {
+        let linear =
+            |elapsed| Fees::launch_fee_rate(2_500, 502_500, 100, LAUNCH_FEE_DECAY_LINEAR, elapsed);
+        assert_eq!(linear(0), 502_500);
+        assert_eq!(linear(50), 252_500);
+        assert_eq!(linear(99), 7_500);
+        assert_eq!(linear(100), 2_500);
+
+        let exponential = |elapsed| {
+            Fees::launch_fee_rate(2_500, 514_500, 100, LAUNCH_FEE_DECAY_EXPONENTIAL, elapsed)
+        };
+        assert_eq!(exponential(0), 514_500);
+        // one halving every tenth of the period
+        assert_eq!(exponential(10), 258_500);
+        assert_eq!(exponential(50), 18_500);
+        assert_eq!(exponential(100), 2_500);
+
+        // no launch fee configured
+        assert_eq!(
+            Fees::launch_fee_rate(2_500, 0, 100, LAUNCH_FEE_DECAY_LINEAR, 0),
+            2_500
+        );
+    }
 }
@@ -3,7 +3,11 @@
 pub mod calculator;
 pub mod constant_product;
 pub mod fees;
+pub mod stable;
+pub mod weighted;
 
 pub use calculator::*;
 pub use constant_product::*;
 pub use fees::*;
+pub use stable::*;
+pub use weighted::*;
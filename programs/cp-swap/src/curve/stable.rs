@@ -0,0 +1,444 @@
+//! The Curve.fi StableSwap invariant
+
+use crate::{
+    curve::{
+        calculator::{Curve, RoundDirection, TradeDirection, TradingTokenResult},
+        constant_product::ConstantProductCurve,
+    },
+    error::ErrorCode,
+    utils::U256,
+};
+use anchor_lang::prelude::*;
+
+/// Minimum amplification coefficient
+pub const MIN_AMP: u64 = 1;
+
+/// Maximum amplification coefficient
+pub const MAX_AMP: u64 = 1_000_000;
+
+/// Number of coins in a pool
+const N_COINS: u8 = 2;
+
+/// Number of coins to the power of the number of coins
+const N_COINS_POW: u8 = 4;
+
+/// Maximum number of Newton iterations when approximating D or y
+const ITERATIONS: u8 = 32;
+
+/// StableCurve struct implementing the StableSwap invariant
+///
+/// A * n^n * sum(x_i) + D = A * D * n^n + D^(n+1) / (n^n * prod(x_i))
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct StableCurve {
+    /// Amplification coefficient (A)
+    pub amp: u64,
+}
+
+fn checked_u8_power(a: &U256, b: u8) -> Option<U256> {
+    let mut result = *a;
+    for _ in 1..b {
+        result = result.checked_mul(*a)?;
+    }
+    Some(result)
+}
+
+fn checked_u8_mul(a: &U256, b: u8) -> Option<U256> {
+    a.checked_mul(U256::from(b))
+}
+
+/// Returns A * n
+fn compute_leverage(amp: u64) -> Option<u64> {
+    amp.checked_mul(N_COINS as u64)
+}
+
+/// One step of Newton's method for D:
+/// d = (leverage * sum_x + d_p * n) * d / ((leverage - 1) * d + (n + 1) * d_p)
+fn calculate_step(initial_d: &U256, leverage: u64, sum_x: u128, d_product: &U256) -> Option<U256> {
+    let leverage_mul = U256::from(leverage).checked_mul(sum_x.into())?;
+    let d_p_mul = checked_u8_mul(d_product, N_COINS)?;
+    let l_val = leverage_mul.checked_add(d_p_mul)?.checked_mul(*initial_d)?;
+
+    let leverage_sub = initial_d.checked_mul(leverage.checked_sub(1)?.into())?;
+    let n_coins_sum = checked_u8_mul(d_product, N_COINS.checked_add(1)?)?;
+    let r_val = leverage_sub.checked_add(n_coins_sum)?;
+
+    l_val.checked_div(r_val)
+}
+
+/// Compute the StableSwap invariant D for the given reserves
+pub fn compute_d(leverage: u64, amount_a: u128, amount_b: u128) -> Option<u128> {
+    let amount_a_times_coins =
+        checked_u8_mul(&U256::from(amount_a), N_COINS)?.checked_add(U256::one())?;
+    let amount_b_times_coins =
+        checked_u8_mul(&U256::from(amount_b), N_COINS)?.checked_add(U256::one())?;
+    let sum_x = amount_a.checked_add(amount_b)?;
+    if sum_x == 0 {
+        return Some(0);
+    }
+    let mut d = U256::from(sum_x);
+    for _ in 0..ITERATIONS {
+        // d_product = D^(n+1) / (n^n * prod(x_i))
+        let d_product = d
+            .checked_mul(d)?
+            .checked_div(amount_a_times_coins)?
+            .checked_mul(d)?
+            .checked_div(amount_b_times_coins)?;
+        let d_previous = d;
+        d = calculate_step(&d, leverage, sum_x, &d_product)?;
+        // Equality with the precision of 1
+        if d == d_previous {
+            break;
+        }
+    }
+    u128::try_from(d).ok()
+}
+
+/// Compute the new amount of the other token given one reserve and D, rounded up.
+///
+/// Solves y^2 + b*y = c for y, where
+/// b = x + D / leverage, c = D^(n+1) / (n^n * x * leverage)
+pub fn compute_new_destination_amount(
+    leverage: u64,
+    new_source_amount: u128,
+    d_val: u128,
+) -> Option<u128> {
+    let leverage = U256::from(leverage);
+    let new_source_amount = U256::from(new_source_amount);
+    let d_val = U256::from(d_val);
+
+    let c = checked_u8_power(&d_val, N_COINS.checked_add(1)?)?
+        .checked_div(checked_u8_mul(&new_source_amount, N_COINS_POW)?.checked_mul(leverage)?)?;
+    let b = new_source_amount.checked_add(d_val.checked_div(leverage)?)?;
+
+    let mut y = d_val;
+    for _ in 0..ITERATIONS {
+        let numerator = checked_u8_power(&y, 2)?.checked_add(c)?;
+        let denominator = checked_u8_mul(&y, 2)?.checked_add(b)?.checked_sub(d_val)?;
+        if denominator.is_zero() {
+            return None;
+        }
+        // Round up, the pool keeps at least the exact amount of y
+        let mut y_new = numerator.checked_div(denominator)?;
+        if !numerator.checked_rem(denominator)?.is_zero() {
+            y_new = y_new.checked_add(U256::one())?;
+        }
+        if y_new == y {
+            break;
+        }
+        y = y_new;
+    }
+    u128::try_from(y).ok()
+}
+
+impl StableCurve {
+    pub fn new(amp: u64) -> Result<Self> {
+        if !(MIN_AMP..=MAX_AMP).contains(&amp) {
+            return err!(ErrorCode::InvalidCurve);
+        }
+        Ok(Self { amp })
+    }
+}
+
+impl Curve for StableCurve {
+    /// StableSwap calculation of the destination amount for a given source amount,
+    /// rounded down.
+    fn swap_base_input_without_fees(
+        &self,
+        source_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        _trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        if source_amount == 0 {
+            return Some(0);
+        }
+        let leverage = compute_leverage(self.amp)?;
+        let d = compute_d(leverage, swap_source_amount, swap_destination_amount)?;
+        let new_swap_source_amount = swap_source_amount.checked_add(source_amount)?;
+        let new_swap_destination_amount =
+            compute_new_destination_amount(leverage, new_swap_source_amount, d)?;
+        swap_destination_amount.checked_sub(new_swap_destination_amount)
+    }
+
+    /// StableSwap calculation of the source amount needed to receive a given
+    /// destination amount, rounded up.
+    fn swap_base_output_without_fees(
+        &self,
+        destination_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        _trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        if destination_amount == 0 {
+            return Some(0);
+        }
+        let leverage = compute_leverage(self.amp)?;
+        let d = compute_d(leverage, swap_source_amount, swap_destination_amount)?;
+        let new_swap_destination_amount =
+            swap_destination_amount.checked_sub(destination_amount)?;
+        if new_swap_destination_amount == 0 {
+            return None;
+        }
+        let new_swap_source_amount =
+            compute_new_destination_amount(leverage, new_swap_destination_amount, d)?;
+        new_swap_source_amount.checked_sub(swap_source_amount)
+    }
+
+    /// Get the amount of trading tokens for the given amount of pool tokens.
+    ///
+    /// Deposits and withdrawals are balanced, so this is the same ratio
+    /// calculation as the constant product curve.
+    fn lp_tokens_to_trading_tokens(
+        &self,
+        lp_token_amount: u128,
+        lp_token_supply: u128,
+        swap_token_0_amount: u128,
+        swap_token_1_amount: u128,
+        round_direction: RoundDirection,
+    ) -> Option<TradingTokenResult> {
+        ConstantProductCurve::lp_tokens_to_trading_tokens(
+            lp_token_amount,
+            lp_token_supply,
+            swap_token_0_amount,
+            swap_token_1_amount,
+            round_direction,
+        )
+    }
+
+    /// The invariant D of the pool, in units of token
+    fn invariant(&self, swap_token_0_amount: u128, swap_token_1_amount: u128) -> Option<u128> {
+        compute_d(
+            compute_leverage(self.amp)?,
+            swap_token_0_amount,
+            swap_token_1_amount,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::curve::calculator::{
+            test::{
+                check_curve_value_from_swap, check_pool_value_from_deposit,
+                check_pool_value_from_withdraw, total_and_intermediate,
+            },
+            Curve, SwapCurve, TradeDirection,
+        },
+        proptest::prelude::*,
+    };
+
+    #[test]
+    fn swap_zero() {
+        let curve = StableCurve { amp: 100 };
+        let result =
+            curve.swap_base_input_without_fees(0, 1_000_000, 1_000_000, TradeDirection::ZeroForOne);
+        assert_eq!(result, Some(0));
+        let result = curve.swap_base_output_without_fees(
+            0,
+            1_000_000,
+            1_000_000,
+            TradeDirection::ZeroForOne,
+        );
+        assert_eq!(result, Some(0));
+    }
+
+    #[test]
+    fn swap_balanced_pool_is_close_to_one_to_one() {
+        let curve = StableCurve { amp: 100 };
+        let amount_out = curve
+            .swap_base_input_without_fees(
+                1_000,
+                1_000_000_000,
+                1_000_000_000,
+                TradeDirection::ZeroForOne,
+            )
+            .unwrap();
+        assert!(amount_out <= 1_000);
+        assert!(amount_out >= 999);
+        // the constant product curve gives a worse rate for the same trade
+        let constant_product_out = ConstantProductCurve::swap_base_input_without_fees(
+            100_000_000,
+            1_000_000_000,
+            1_000_000_000,
+        );
+        let stable_out = curve
+            .swap_base_input_without_fees(
+                100_000_000,
+                1_000_000_000,
+                1_000_000_000,
+                TradeDirection::ZeroForOne,
+            )
+            .unwrap();
+        assert!(stable_out > constant_product_out);
+        assert!(stable_out < 100_000_000);
+    }
+
+    #[test]
+    fn swap_base_output_rounds_up() {
+        let curve = StableCurve { amp: 85 };
+        let tests: &[(u128, u128, u128)] = &[
+            (1, 1_000_000, 1_000_000),
+            (10, 5_000_000, 20_000_000),
+            (1_000_000, 10_000_000_000, 10_000_000_000),
+            (999_999, 3_000_000, 1_000_000),
+        ];
+        for (destination_amount, swap_source_amount, swap_destination_amount) in tests.iter() {
+            let source_amount = curve
+                .swap_base_output_without_fees(
+                    *destination_amount,
+                    *swap_source_amount,
+                    *swap_destination_amount,
+                    TradeDirection::ZeroForOne,
+                )
+                .unwrap();
+            let destination_amount_swapped = curve
+                .swap_base_input_without_fees(
+                    source_amount,
+                    *swap_source_amount,
+                    *swap_destination_amount,
+                    TradeDirection::ZeroForOne,
+                )
+                .unwrap();
+            assert!(destination_amount_swapped >= *destination_amount);
+        }
+    }
+
+    #[test]
+    fn swap_base_output_drains_pool() {
+        let curve = StableCurve { amp: 100 };
+        let result =
+            curve.swap_base_output_without_fees(1_000, 1_000, 1_000, TradeDirection::ZeroForOne);
+        assert!(result.is_none());
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_swap(
+            source_token_amount in 1..u64::MAX,
+            swap_source_amount in 1..u64::MAX,
+            swap_destination_amount in 1..u64::MAX,
+            amp in MIN_AMP..MAX_AMP,
+        ) {
+            let curve = SwapCurve::Stable(StableCurve { amp });
+            for trade_direction in [TradeDirection::ZeroForOne, TradeDirection::OneForZero] {
+                check_curve_value_from_swap(
+                    &curve,
+                    source_token_amount as u128,
+                    swap_source_amount as u128,
+                    swap_destination_amount as u128,
+                    trade_direction,
+                );
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn round_trip_swap_does_not_profit(
+            source_token_amount in 1..u64::MAX,
+            swap_source_amount in 1..u64::MAX,
+            swap_destination_amount in 1..u64::MAX,
+            amp in MIN_AMP..MAX_AMP,
+        ) {
+            let curve = StableCurve { amp };
+            let (source_token_amount, swap_source_amount, swap_destination_amount) = (
+                source_token_amount as u128,
+                swap_source_amount as u128,
+                swap_destination_amount as u128,
+            );
+            for trade_direction in [TradeDirection::ZeroForOne, TradeDirection::OneForZero] {
+                let Some(destination_amount) = curve.swap_base_input_without_fees(
+                    source_token_amount,
+                    swap_source_amount,
+                    swap_destination_amount,
+                    trade_direction,
+                ) else {
+                    continue;
+                };
+                let new_swap_source_amount = swap_source_amount + source_token_amount;
+                let new_swap_destination_amount = swap_destination_amount - destination_amount;
+                // swap everything received back the other way
+                let Some(source_amount_back) = curve.swap_base_input_without_fees(
+                    destination_amount,
+                    new_swap_destination_amount,
+                    new_swap_source_amount,
+                    trade_direction.opposite(),
+                ) else {
+                    continue;
+                };
+                prop_assert!(source_amount_back <= source_token_amount);
+
+                let (swap_token_0_amount, swap_token_1_amount) =
+                    trade_direction.token_amounts(swap_source_amount, swap_destination_amount);
+                let previous_value = curve
+                    .invariant(swap_token_0_amount, swap_token_1_amount)
+                    .unwrap();
+                let (swap_token_0_amount, swap_token_1_amount) = trade_direction.token_amounts(
+                    new_swap_source_amount - source_amount_back,
+                    swap_destination_amount,
+                );
+                let new_value = curve
+                    .invariant(swap_token_0_amount, swap_token_1_amount)
+                    .unwrap();
+                prop_assert!(new_value >= previous_value);
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_deposit(
+            pool_token_amount in 1..u64::MAX,
+            pool_token_supply in 1..u64::MAX,
+            swap_token_a_amount in 1..u64::MAX,
+            swap_token_b_amount in 1..u64::MAX,
+            amp in MIN_AMP..MAX_AMP,
+        ) {
+            let curve = SwapCurve::Stable(StableCurve { amp });
+            let pool_token_amount = pool_token_amount as u128;
+            let pool_token_supply = pool_token_supply as u128;
+            let swap_token_a_amount = swap_token_a_amount as u128;
+            let swap_token_b_amount = swap_token_b_amount as u128;
+            // Make sure we will get at least one trading token out for each
+            // side, otherwise the calculation fails
+            prop_assume!(pool_token_amount * swap_token_a_amount / pool_token_supply >= 1);
+            prop_assume!(pool_token_amount * swap_token_b_amount / pool_token_supply >= 1);
+            check_pool_value_from_deposit(
+                &curve,
+                pool_token_amount,
+                pool_token_supply,
+                swap_token_a_amount,
+                swap_token_b_amount,
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_withdraw(
+            (pool_token_supply, pool_token_amount) in total_and_intermediate(u64::MAX),
+            swap_token_a_amount in 1..u64::MAX,
+            swap_token_b_amount in 1..u64::MAX,
+            amp in MIN_AMP..MAX_AMP,
+        ) {
+            let curve = SwapCurve::Stable(StableCurve { amp });
+            let pool_token_amount = pool_token_amount as u128;
+            let pool_token_supply = pool_token_supply as u128;
+            let swap_token_a_amount = swap_token_a_amount as u128;
+            let swap_token_b_amount = swap_token_b_amount as u128;
+            // Make sure we will get at least one trading token out for each
+            // side, otherwise the calculation fails
+            prop_assume!(pool_token_amount * swap_token_a_amount / pool_token_supply >= 1);
+            prop_assume!(pool_token_amount * swap_token_b_amount / pool_token_supply >= 1);
+            check_pool_value_from_withdraw(
+                &curve,
+                pool_token_amount,
+                pool_token_supply,
+                swap_token_a_amount,
+                swap_token_b_amount,
+            );
+        }
+    }
+}
@@ -0,0 +1,376 @@
+//! The Balancer weighted product invariant
+
+use crate::{
+    curve::{
+        calculator::{Curve, RoundDirection, TradeDirection, TradingTokenResult},
+        constant_product::ConstantProductCurve,
+    },
+    error::ErrorCode,
+    utils::{exp2_x64, log2_x64, ONE_X64, U256},
+};
+use anchor_lang::prelude::*;
+
+/// Denominator of the token weights, the weights of a pool sum up to it
+pub const WEIGHT_DENOMINATOR: u64 = 1_000_000;
+
+/// Minimum weight of a single token, 2%
+pub const MIN_WEIGHT: u64 = 20_000;
+
+/// The fixed point power is rounded in the pool's favor by a relative 2^-40,
+/// well above the approximation error of `log2_x64` and `exp2_x64`
+const POW_ERROR_SHIFT: u32 = 40;
+
+/// WeightedCurve struct implementing the weighted product invariant
+///
+/// x^w_0 * y^w_1 = constant
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct WeightedCurve {
+    /// Weight of token_0, denominated in `WEIGHT_DENOMINATOR`
+    pub token_0_weight: u64,
+    /// Weight of token_1, denominated in `WEIGHT_DENOMINATOR`
+    pub token_1_weight: u64,
+}
+
+/// (numerator / denominator)^(exponent_numerator / exponent_denominator) in
+/// Q64.64, for numerator >= denominator
+fn pow_x64(
+    numerator: u128,
+    denominator: u128,
+    exponent_numerator: u64,
+    exponent_denominator: u64,
+) -> Option<u128> {
+    let log = log2_x64(numerator, denominator)?;
+    let exponent = U256::from(log)
+        .checked_mul(U256::from(exponent_numerator))?
+        .checked_div(U256::from(exponent_denominator))?;
+    exp2_x64(u128::try_from(exponent).ok()?)
+}
+
+impl WeightedCurve {
+    pub fn new(token_0_weight: u64, token_1_weight: u64) -> Result<Self> {
+        if token_0_weight < MIN_WEIGHT
+            || token_1_weight < MIN_WEIGHT
+            || token_0_weight.checked_add(token_1_weight) != Some(WEIGHT_DENOMINATOR)
+        {
+            return err!(ErrorCode::InvalidCurve);
+        }
+        Ok(Self {
+            token_0_weight,
+            token_1_weight,
+        })
+    }
+
+    /// The weights of the source and destination token
+    fn weights(&self, trade_direction: TradeDirection) -> (u64, u64) {
+        match trade_direction {
+            TradeDirection::ZeroForOne => (self.token_0_weight, self.token_1_weight),
+            TradeDirection::OneForZero => (self.token_1_weight, self.token_0_weight),
+        }
+    }
+}
+
+impl Curve for WeightedCurve {
+    /// Weighted product swap ensures x^w_x * y^w_y = constant
+    ///
+    /// delta_y = y * (1 - (x / (x + delta_x))^(w_x / w_y)), rounded down.
+    fn swap_base_input_without_fees(
+        &self,
+        source_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        if source_amount == 0 {
+            return Some(0);
+        }
+        if swap_source_amount == 0 {
+            return None;
+        }
+        let (source_weight, destination_weight) = self.weights(trade_direction);
+        // q = ((x + delta_x) / x)^(w_x / w_y), which saturates when it no longer fits
+        let q = pow_x64(
+            swap_source_amount.checked_add(source_amount)?,
+            swap_source_amount,
+            source_weight,
+            destination_weight,
+        )
+        .unwrap_or(u128::MAX);
+        let q = q.checked_sub(q >> POW_ERROR_SHIFT)?.checked_sub(1)?;
+        if q <= ONE_X64 {
+            return Some(0);
+        }
+        // delta_y = y * (q - 1) / q
+        let destination_amount_swapped = U256::from(swap_destination_amount)
+            .checked_mul(U256::from(q - ONE_X64))?
+            .checked_div(U256::from(q))?;
+        u128::try_from(destination_amount_swapped).ok()
+    }
+
+    /// delta_x = x * ((y / (y - delta_y))^(w_y / w_x) - 1), rounded up.
+    fn swap_base_output_without_fees(
+        &self,
+        destination_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        if destination_amount == 0 {
+            return Some(0);
+        }
+        if destination_amount >= swap_destination_amount {
+            return None;
+        }
+        let (source_weight, destination_weight) = self.weights(trade_direction);
+        let q = pow_x64(
+            swap_destination_amount,
+            swap_destination_amount - destination_amount,
+            destination_weight,
+            source_weight,
+        )?;
+        let q = q.checked_add(q >> POW_ERROR_SHIFT)?.checked_add(1)?;
+        let numerator = U256::from(swap_source_amount).checked_mul(U256::from(q - ONE_X64))?;
+        let mut source_amount_swapped = numerator >> 64;
+        if !(numerator & U256::from(u64::MAX)).is_zero() {
+            source_amount_swapped = source_amount_swapped.checked_add(U256::one())?;
+        }
+        u128::try_from(source_amount_swapped).ok()
+    }
+
+    /// Get the amount of trading tokens for the given amount of pool tokens.
+    ///
+    /// Deposits and withdrawals take both tokens in the ratio of the reserves,
+    /// which scales x^w_0 * y^w_1 without moving the weighted price, so it is
+    /// the same calculation as the constant product curve.
+    fn lp_tokens_to_trading_tokens(
+        &self,
+        lp_token_amount: u128,
+        lp_token_supply: u128,
+        swap_token_0_amount: u128,
+        swap_token_1_amount: u128,
+        round_direction: RoundDirection,
+    ) -> Option<TradingTokenResult> {
+        ConstantProductCurve::lp_tokens_to_trading_tokens(
+            lp_token_amount,
+            lp_token_supply,
+            swap_token_0_amount,
+            swap_token_1_amount,
+            round_direction,
+        )
+    }
+
+    /// The invariant in log space, w_0 * log2(x) + w_1 * log2(y) in Q64.64
+    fn invariant(&self, swap_token_0_amount: u128, swap_token_1_amount: u128) -> Option<u128> {
+        log2_x64(swap_token_0_amount, 1)?
+            .checked_mul(u128::from(self.token_0_weight))?
+            .checked_add(
+                log2_x64(swap_token_1_amount, 1)?.checked_mul(u128::from(self.token_1_weight))?,
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::curve::calculator::{
+            test::{
+                check_curve_value_from_swap, check_pool_value_from_deposit,
+                check_pool_value_from_withdraw, total_and_intermediate,
+            },
+            SwapCurve,
+        },
+        proptest::prelude::*,
+    };
+
+    #[test]
+    fn new_checks_weights() {
+        assert!(WeightedCurve::new(800_000, 200_000).is_ok());
+        assert!(WeightedCurve::new(MIN_WEIGHT, WEIGHT_DENOMINATOR - MIN_WEIGHT).is_ok());
+        assert!(WeightedCurve::new(MIN_WEIGHT - 1, WEIGHT_DENOMINATOR - MIN_WEIGHT + 1).is_err());
+        assert!(WeightedCurve::new(600_000, 600_000).is_err());
+    }
+
+    #[test]
+    fn swap_matches_float() {
+        let curve = WeightedCurve::new(800_000, 200_000).unwrap();
+        let tests: &[(u128, u128, u128, TradeDirection)] = &[
+            (1_000, 1_000_000, 1_000_000, TradeDirection::ZeroForOne),
+            (1_000, 1_000_000, 1_000_000, TradeDirection::OneForZero),
+            (500_000, 4_000_000, 1_000_000, TradeDirection::ZeroForOne),
+            (500_000, 1_000_000, 4_000_000, TradeDirection::OneForZero),
+        ];
+        for (source_amount, swap_source_amount, swap_destination_amount, trade_direction) in
+            tests.iter()
+        {
+            let (source_weight, destination_weight) = curve.weights(*trade_direction);
+            let expected = *swap_destination_amount as f64
+                * (1.0
+                    - (*swap_source_amount as f64 / (*swap_source_amount + *source_amount) as f64)
+                        .powf(source_weight as f64 / destination_weight as f64));
+            let actual = curve
+                .swap_base_input_without_fees(
+                    *source_amount,
+                    *swap_source_amount,
+                    *swap_destination_amount,
+                    *trade_direction,
+                )
+                .unwrap();
+            assert!(actual as f64 <= expected);
+            assert!(actual as f64 >= expected - 2.0);
+        }
+    }
+
+    #[test]
+    fn swap_base_output_rounds_up() {
+        let curve = WeightedCurve::new(600_000, 400_000).unwrap();
+        let tests: &[(u128, u128, u128, TradeDirection)] = &[
+            (1, 1_000_000, 1_000_000, TradeDirection::ZeroForOne),
+            (10, 5_000_000, 20_000_000, TradeDirection::OneForZero),
+            (
+                1_000_000,
+                10_000_000_000,
+                10_000_000_000,
+                TradeDirection::ZeroForOne,
+            ),
+            (999_999, 3_000_000, 1_000_000, TradeDirection::OneForZero),
+        ];
+        for (destination_amount, swap_source_amount, swap_destination_amount, trade_direction) in
+            tests.iter()
+        {
+            let source_amount = curve
+                .swap_base_output_without_fees(
+                    *destination_amount,
+                    *swap_source_amount,
+                    *swap_destination_amount,
+                    *trade_direction,
+                )
+                .unwrap();
+            let (swap_token_0_amount, swap_token_1_amount) =
+                trade_direction.token_amounts(*swap_source_amount, *swap_destination_amount);
+            let (new_swap_token_0_amount, new_swap_token_1_amount) = trade_direction.token_amounts(
+                swap_source_amount + source_amount,
+                swap_destination_amount - destination_amount,
+            );
+            assert!(
+                curve
+                    .invariant(new_swap_token_0_amount, new_swap_token_1_amount)
+                    .unwrap()
+                    >= curve
+                        .invariant(swap_token_0_amount, swap_token_1_amount)
+                        .unwrap()
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn equal_weights_never_beat_constant_product(
+            source_amount in 1..u64::MAX,
+            swap_source_amount in 1..u64::MAX,
+            swap_destination_amount in 1..u64::MAX,
+        ) {
+            let curve = WeightedCurve::new(WEIGHT_DENOMINATOR / 2, WEIGHT_DENOMINATOR / 2).unwrap();
+            let weighted = curve
+                .swap_base_input_without_fees(
+                    source_amount as u128,
+                    swap_source_amount as u128,
+                    swap_destination_amount as u128,
+                    TradeDirection::ZeroForOne,
+                )
+                .unwrap();
+            let constant_product = ConstantProductCurve::swap_base_input_without_fees(
+                source_amount as u128,
+                swap_source_amount as u128,
+                swap_destination_amount as u128,
+            );
+            prop_assert!(weighted <= constant_product);
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_swap(
+            source_token_amount in 1..u64::MAX,
+            swap_source_amount in 1..u64::MAX,
+            swap_destination_amount in 1..u64::MAX,
+            token_0_weight in MIN_WEIGHT..(WEIGHT_DENOMINATOR - MIN_WEIGHT),
+        ) {
+            let curve = SwapCurve::Weighted(
+                WeightedCurve::new(token_0_weight, WEIGHT_DENOMINATOR - token_0_weight).unwrap(),
+            );
+            check_curve_value_from_swap(
+                &curve,
+                source_token_amount as u128,
+                swap_source_amount as u128,
+                swap_destination_amount as u128,
+                TradeDirection::ZeroForOne
+            );
+            check_curve_value_from_swap(
+                &curve,
+                source_token_amount as u128,
+                swap_source_amount as u128,
+                swap_destination_amount as u128,
+                TradeDirection::OneForZero
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_deposit(
+            pool_token_amount in 1..u64::MAX,
+            pool_token_supply in 1..u64::MAX,
+            swap_token_a_amount in 1..u64::MAX,
+            swap_token_b_amount in 1..u64::MAX,
+            token_0_weight in MIN_WEIGHT..(WEIGHT_DENOMINATOR - MIN_WEIGHT),
+        ) {
+            let curve = SwapCurve::Weighted(
+                WeightedCurve::new(token_0_weight, WEIGHT_DENOMINATOR - token_0_weight).unwrap(),
+            );
+            let pool_token_amount = pool_token_amount as u128;
+            let pool_token_supply = pool_token_supply as u128;
+            let swap_token_a_amount = swap_token_a_amount as u128;
+            let swap_token_b_amount = swap_token_b_amount as u128;
+            // Make sure we will get at least one trading token out for each
+            // side, otherwise the calculation fails
+            prop_assume!(pool_token_amount * swap_token_a_amount / pool_token_supply >= 1);
+            prop_assume!(pool_token_amount * swap_token_b_amount / pool_token_supply >= 1);
+            check_pool_value_from_deposit(
+                &curve,
+                pool_token_amount,
+                pool_token_supply,
+                swap_token_a_amount,
+                swap_token_b_amount,
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_withdraw(
+            (pool_token_supply, pool_token_amount) in total_and_intermediate(u64::MAX),
+            swap_token_a_amount in 1..u64::MAX,
+            swap_token_b_amount in 1..u64::MAX,
+            token_0_weight in MIN_WEIGHT..(WEIGHT_DENOMINATOR - MIN_WEIGHT),
+        ) {
+            let curve = SwapCurve::Weighted(
+                WeightedCurve::new(token_0_weight, WEIGHT_DENOMINATOR - token_0_weight).unwrap(),
+            );
+            let pool_token_amount = pool_token_amount as u128;
+            let pool_token_supply = pool_token_supply as u128;
+            let swap_token_a_amount = swap_token_a_amount as u128;
+            let swap_token_b_amount = swap_token_b_amount as u128;
+            // Make sure we will get at least one trading token out for each
+            // side, otherwise the calculation fails
+            prop_assume!(pool_token_amount * swap_token_a_amount / pool_token_supply >= 1);
+            prop_assume!(pool_token_amount * swap_token_b_amount / pool_token_supply >= 1);
+            check_pool_value_from_withdraw(
+                &curve,
+                pool_token_amount,
+                pool_token_supply,
+                swap_token_a_amount,
+                swap_token_b_amount,
+            );
+        }
+    }
+}
@@ -41,4 +41,26 @@ pub enum ErrorCode {
     InvalidLpMintAddress,
     #[msg("Invalid account data")]
     InvalidAccountData,
+    /// Unknown curve type or out of range curve parameters
+    #[msg("Invalid curve type or parameters")]
+    InvalidCurve,
+    /// The swap moves the price too far away from the oracle
+    #[msg("Price deviates too far from the latest observation")]
+    PriceDeviationExceeded,
+    #[msg("Swaps are halted after an abnormal price move")]
+    PoolInCooldown,
+    #[msg("Invalid swap route accounts")]
+    InvalidRoute,
+    #[msg("Referrer token account does not match the input mint")]
+    InvalidReferrer,
+    #[msg("Transaction deadline has passed")]
+    DeadlineExceeded,
+    #[msg("Pool price is already beyond the price limit")]
+    PriceLimitReached,
+    #[msg("Wallet is not on the early access allowlist")]
+    InvalidMerkleProof,
+    #[msg("Early access buy cap exceeded")]
+    EarlyAccessCapExceeded,
+    #[msg("Invalid number of pool tokens or token accounts")]
+    InvalidTokenCount,
 }
@@ -85,6 +85,25 @@ pub fn collect_fund_fee(
     let auth_bump: u8;
     {
         let pool_state = &mut ctx.accounts.pool_state;
+        pool_state.update_weights(oracle::block_timestamp());
+        // Tokens sent to the vaults from outside the pool are left out
+        let (vault_0_amount, vault_1_amount) = pool_state.vault_amounts(
+            ctx.accounts.token_0_vault.amount,
+            ctx.accounts.token_1_vault.amount,
+        );
+        let (token_0_price_x32, token_1_price_x32) =
+            pool_state.token_price_x32(vault_0_amount, vault_1_amount);
+        let (total_token_0_amount, total_token_1_amount) =
+            pool_state.vault_amount_without_fee(vault_0_amount, vault_1_amount);
+        // update the previous price to the observation, when it is passed
+        oracle::update_optional_observation(
+            ctx.remaining_accounts,
+            pool_state.observation_key,
+            oracle::block_timestamp(),
+            token_0_price_x32,
+            token_1_price_x32,
+            oracle::liquidity(total_token_0_amount, total_token_1_amount),
+        )?;
         amount_0 = amount_0_requested.min(pool_state.fund_fees_token_0);
         amount_1 = amount_1_requested.min(pool_state.fund_fees_token_1);
 
@@ -128,5 +147,15 @@ pub fn collect_fund_fee(
         ctx.accounts.system_program.to_account_info(),
     )?;
 
+    let pool_state = &mut ctx.accounts.pool_state;
+    pool_state.update_reserve(
+        &ctx.accounts.token_0_vault.to_account_info(),
+        ctx.accounts.token_0_vault.amount,
+    )?;
+    pool_state.update_reserve(
+        &ctx.accounts.token_1_vault.to_account_info(),
+        ctx.accounts.token_1_vault.amount,
+    )?;
+
     Ok(())
 }
@@ -0,0 +1,138 @@
+use crate::error::ErrorCode;
+use crate::instructions::MultiPoolTokenAccounts;
+use crate::states::*;
+use crate::utils::token::*;
+use anchor_lang::prelude::*;
+use light_anchor_spl::token_interface::TokenInterface;
+
+#[derive(Accounts)]
+pub struct CollectMultiPoolProtocolFee<'info> {
+    /// Only admin or owner can collect fee now
+    #[account(constraint = (owner.key() == amm_config.protocol_owner || owner.key() == crate::admin::ID) @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: pool vault authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// Pool state stores accumulated protocol fee amounts
+    #[account(mut)]
+    pub multi_pool_state: Box<Account<'info, MultiPoolState>>,
+
+    /// Amm config account stores owner
+    #[account(address = multi_pool_state.amm_config)]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    /// Light Token program for CPI
+    pub light_token_program: Interface<'info, TokenInterface>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: light_token CPI authority.
+    pub light_token_cpi_authority: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CollectMultiPoolFundFee<'info> {
+    /// Only admin or fund_owner can collect fee now
+    #[account(constraint = (owner.key() == amm_config.fund_owner || owner.key() == crate::admin::ID) @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: pool vault authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// Pool state stores accumulated fund fee amounts
+    #[account(mut)]
+    pub multi_pool_state: Box<Account<'info, MultiPoolState>>,
+
+    /// Amm config account stores fund_owner
+    #[account(address = multi_pool_state.amm_config)]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    /// Light Token program for CPI
+    pub light_token_program: Interface<'info, TokenInterface>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: light_token CPI authority.
+    pub light_token_cpi_authority: AccountInfo<'info>,
+}
+
+/// Collects up to `amounts_requested` of the protocol fees owed in each token of a
+/// multi-token pool. See `MultiPoolTokenAccounts` for the remaining accounts,
+/// the token accounts receive the fees.
+pub fn collect_multi_pool_protocol_fee<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CollectMultiPoolProtocolFee<'info>>,
+    amounts_requested: Vec<u64>,
+) -> Result<()> {
+    let pool_state = &mut ctx.accounts.multi_pool_state;
+    let token_accounts = MultiPoolTokenAccounts::load(pool_state, ctx.remaining_accounts)?;
+    require_eq!(
+        amounts_requested.len(),
+        pool_state.token_count(),
+        ErrorCode::InvalidTokenCount
+    );
+    for (i, vault) in token_accounts.vaults.iter().enumerate() {
+        let amount = amounts_requested[i].min(pool_state.protocol_fees[i]);
+        pool_state.protocol_fees[i] -= amount;
+        transfer_from_pool_vault_to_user(
+            ctx.accounts.authority.to_account_info(),
+            vault.to_account_info(),
+            token_accounts.user_token_accounts[i].clone(),
+            token_accounts.mints[i].clone(),
+            ctx.accounts.light_token_program.to_account_info(),
+            amount,
+            &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+            ctx.accounts.owner.to_account_info(),
+            ctx.accounts.light_token_cpi_authority.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        )?;
+    }
+    pool_state.recent_epoch = Clock::get()?.epoch;
+    Ok(())
+}
+
+/// Collects up to `amounts_requested` of the fund fees owed in each token of a
+/// multi-token pool. See `MultiPoolTokenAccounts` for the remaining accounts,
+/// the token accounts receive the fees.
+pub fn collect_multi_pool_fund_fee<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CollectMultiPoolFundFee<'info>>,
+    amounts_requested: Vec<u64>,
+) -> Result<()> {
+    let pool_state = &mut ctx.accounts.multi_pool_state;
+    let token_accounts = MultiPoolTokenAccounts::load(pool_state, ctx.remaining_accounts)?;
+    require_eq!(
+        amounts_requested.len(),
+        pool_state.token_count(),
+        ErrorCode::InvalidTokenCount
+    );
+    for (i, vault) in token_accounts.vaults.iter().enumerate() {
+        let amount = amounts_requested[i].min(pool_state.fund_fees[i]);
+        pool_state.fund_fees[i] -= amount;
+        transfer_from_pool_vault_to_user(
+            ctx.accounts.authority.to_account_info(),
+            vault.to_account_info(),
+            token_accounts.user_token_accounts[i].clone(),
+            token_accounts.mints[i].clone(),
+            ctx.accounts.light_token_program.to_account_info(),
+            amount,
+            &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+            ctx.accounts.owner.to_account_info(),
+            ctx.accounts.light_token_cpi_authority.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        )?;
+    }
+    pool_state.recent_epoch = Clock::get()?.epoch;
+    Ok(())
+}
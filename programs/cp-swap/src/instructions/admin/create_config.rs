@@ -1,3 +1,4 @@
+use crate::curve::{SwapCurve, WEIGHT_DENOMINATOR};
 use crate::error::ErrorCode;
 use crate::states::*;
 use anchor_lang::prelude::*;
@@ -29,6 +30,14 @@ pub struct CreateAmmConfig<'info> {
     pub system_program: Program<'info, System>,
 }
 
+/// The invariant of pools created with a config
+#[derive(Clone, Copy, Debug)]
+pub struct CurveParams {
+    pub curve_type: u8,
+    /// Amplification coefficient of the stable curve, ignored otherwise
+    pub amp: u64,
+}
+
 pub fn create_amm_config(
     ctx: Context<CreateAmmConfig>,
     index: u16,
@@ -36,6 +45,7 @@ pub fn create_amm_config(
     protocol_fee_rate: u64,
     fund_fee_rate: u64,
     create_pool_fee: u64,
+    curve: CurveParams,
 ) -> Result<()> {
     let amm_config = ctx.accounts.amm_config.deref_mut();
     amm_config.protocol_owner = ctx.accounts.owner.key();
@@ -47,5 +57,14 @@ pub fn create_amm_config(
     amm_config.fund_fee_rate = fund_fee_rate;
     amm_config.create_pool_fee = create_pool_fee;
     amm_config.fund_owner = ctx.accounts.owner.key();
+    amm_config.curve_type = curve.curve_type;
+    amm_config.amp = curve.amp;
+    // Weighted pools pick their weights at initialization, only check the type here
+    SwapCurve::new(
+        curve.curve_type,
+        curve.amp,
+        WEIGHT_DENOMINATOR / 2,
+        WEIGHT_DENOMINATOR / 2,
+    )?;
     Ok(())
 }
@@ -7,8 +7,17 @@ pub use update_config::*;
 pub mod update_pool_status;
 pub use update_pool_status::*;
 
+pub mod update_observation_duration;
+pub use update_observation_duration::*;
+
 pub mod collect_protocol_fee;
 pub use collect_protocol_fee::*;
 
 pub mod collect_fund_fee;
 pub use collect_fund_fee::*;
+
+pub mod update_multi_pool_status;
+pub use update_multi_pool_status::*;
+
+pub mod collect_multi_pool_fee;
+pub use collect_multi_pool_fee::*;
@@ -1,4 +1,6 @@
-use crate::curve::fees::FEE_RATE_DENOMINATOR_VALUE;
+use crate::curve::fees::{
+    FEE_RATE_DENOMINATOR_VALUE, LAUNCH_FEE_DECAY_EXPONENTIAL, LAUNCH_FEE_DECAY_LINEAR,
+};
 use crate::error::ErrorCode;
 use crate::states::*;
 use anchor_lang::prelude::*;
@@ -31,6 +33,15 @@ pub fn update_amm_config(ctx: Context<UpdateAmmConfig>, param: u8, value: u64) -
         }
         Some(5) => amm_config.create_pool_fee = value,
         Some(6) => amm_config.disable_create_pool = if value == 0 { false } else { true },
+        Some(7) => update_dynamic_fee_max_rate(amm_config, value),
+        Some(8) => amm_config.dynamic_fee_decay_period = value,
+        Some(9) => amm_config.max_price_deviation_rate = value,
+        Some(10) => amm_config.price_deviation_cooldown = value,
+        Some(11) => update_referral_fee_rate(amm_config, value),
+        Some(12) => update_creator_fee_rate(amm_config, value),
+        Some(13) => update_launch_fee_rate(amm_config, value),
+        Some(14) => amm_config.launch_fee_duration = value,
+        Some(15) => update_launch_fee_decay(amm_config, value)?,
         _ => return err!(ErrorCode::InvalidInput),
     }
 
@@ -39,7 +50,10 @@ pub fn update_amm_config(ctx: Context<UpdateAmmConfig>, param: u8, value: u64) -
 
 fn update_protocol_fee_rate(amm_config: &mut Account<AmmConfig>, protocol_fee_rate: u64) {
     assert!(protocol_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
-    assert!(protocol_fee_rate + amm_config.fund_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
+    assert!(
+        protocol_fee_rate + amm_config.fund_fee_rate + amm_config.creator_fee_rate
+            <= FEE_RATE_DENOMINATOR_VALUE
+    );
     amm_config.protocol_fee_rate = protocol_fee_rate;
 }
 
@@ -48,9 +62,49 @@ fn update_trade_fee_rate(amm_config: &mut Account<AmmConfig>, trade_fee_rate: u6
     amm_config.trade_fee_rate = trade_fee_rate;
 }
 
+fn update_dynamic_fee_max_rate(amm_config: &mut Account<AmmConfig>, dynamic_fee_max_rate: u64) {
+    assert!(dynamic_fee_max_rate < FEE_RATE_DENOMINATOR_VALUE);
+    amm_config.dynamic_fee_max_rate = dynamic_fee_max_rate;
+}
+
+fn update_launch_fee_rate(amm_config: &mut Account<AmmConfig>, launch_fee_rate: u64) {
+    assert!(launch_fee_rate < FEE_RATE_DENOMINATOR_VALUE);
+    amm_config.launch_fee_rate = launch_fee_rate;
+}
+
+fn update_launch_fee_decay(
+    amm_config: &mut Account<AmmConfig>,
+    launch_fee_decay: u64,
+) -> Result<()> {
+    require!(
+        launch_fee_decay == u64::from(LAUNCH_FEE_DECAY_LINEAR)
+            || launch_fee_decay == u64::from(LAUNCH_FEE_DECAY_EXPONENTIAL),
+        ErrorCode::InvalidInput
+    );
+    amm_config.launch_fee_decay = launch_fee_decay as u8;
+    Ok(())
+}
+
+fn update_referral_fee_rate(amm_config: &mut Account<AmmConfig>, referral_fee_rate: u64) {
+    assert!(referral_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
+    amm_config.referral_fee_rate = referral_fee_rate;
+}
+
+fn update_creator_fee_rate(amm_config: &mut Account<AmmConfig>, creator_fee_rate: u64) {
+    assert!(creator_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
+    assert!(
+        creator_fee_rate + amm_config.protocol_fee_rate + amm_config.fund_fee_rate
+            <= FEE_RATE_DENOMINATOR_VALUE
+    );
+    amm_config.creator_fee_rate = creator_fee_rate;
+}
+
 fn update_fund_fee_rate(amm_config: &mut Account<AmmConfig>, fund_fee_rate: u64) {
     assert!(fund_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
-    assert!(fund_fee_rate + amm_config.protocol_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
+    assert!(
+        fund_fee_rate + amm_config.protocol_fee_rate + amm_config.creator_fee_rate
+            <= FEE_RATE_DENOMINATOR_VALUE
+    );
     amm_config.fund_fee_rate = fund_fee_rate;
 }
 
@@ -0,0 +1,21 @@
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct UpdateMultiPoolStatus<'info> {
+    #[account(
+        address = crate::admin::ID
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub multi_pool_state: Account<'info, MultiPoolState>,
+}
+
+pub fn update_multi_pool_status(ctx: Context<UpdateMultiPoolStatus>, status: u8) -> Result<()> {
+    require_gte!(255, status);
+    let pool_state = &mut ctx.accounts.multi_pool_state;
+    pool_state.set_status(status);
+    pool_state.recent_epoch = Clock::get()?.epoch;
+    Ok(())
+}
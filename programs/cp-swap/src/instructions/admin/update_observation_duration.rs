@@ -0,0 +1,24 @@
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct UpdateObservationDuration<'info> {
+    #[account(
+        address = crate::admin::ID
+    )]
+    pub authority: Signer<'info>,
+
+    pub pool_state: Account<'info, PoolState>,
+
+    #[account(mut, address = pool_state.observation_key)]
+    pub observation_state: Account<'info, ObservationState>,
+}
+
+pub fn update_observation_duration(
+    ctx: Context<UpdateObservationDuration>,
+    update_duration: u64,
+) -> Result<()> {
+    require_gt!(update_duration, 0);
+    ctx.accounts.observation_state.update_duration = update_duration;
+    Ok(())
+}
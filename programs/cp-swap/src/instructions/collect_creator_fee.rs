@@ -0,0 +1,165 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::utils::token::*;
+use anchor_lang::prelude::*;
+use light_anchor_spl::token::Token;
+use light_anchor_spl::token_interface::Mint;
+use light_anchor_spl::token_interface::Token2022;
+use light_anchor_spl::token_interface::TokenAccount;
+#[derive(Accounts)]
+pub struct CollectCreatorFee<'info> {
+    /// Only the pool creator can collect creator fees
+    #[account(address = pool_state.pool_creator @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: pool vault and lp mint authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// Pool state stores accumulated creator fee amount
+    #[account(mut)]
+    pub pool_state: Account<'info, PoolState>,
+
+    /// The program account for the most recent oracle observation
+    #[account(mut, address = pool_state.observation_key)]
+    pub observation_state: Box<Account<'info, ObservationState>>,
+
+    /// The address that holds pool tokens for token_0
+    #[account(
+        mut,
+        constraint = token_0_vault.key() == pool_state.token_0_vault
+    )]
+    pub token_0_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for token_1
+    #[account(
+        mut,
+        constraint = token_1_vault.key() == pool_state.token_1_vault
+    )]
+    pub token_1_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of token_0 vault
+    #[account(
+        address = token_0_vault.mint
+    )]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of token_1 vault
+    #[account(
+        address = token_1_vault.mint
+    )]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The address that receives the collected token_0 creator fees
+    #[account(mut)]
+    pub recipient_token_0_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that receives the collected token_1 creator fees
+    #[account(mut)]
+    pub recipient_token_1_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The SPL program to perform token transfers
+    pub token_program: Program<'info, Token>,
+
+    /// The SPL program 2022 to perform token transfers
+    pub token_program_2022: Program<'info, Token2022>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: light_token CPI authority.
+    pub light_token_cpi_authority: AccountInfo<'info>,
+}
+
+pub fn collect_creator_fee(
+    ctx: Context<CollectCreatorFee>,
+    amount_0_requested: u64,
+    amount_1_requested: u64,
+) -> Result<()> {
+    let amount_0: u64;
+    let amount_1: u64;
+    let auth_bump: u8;
+    {
+        let pool_state = &mut ctx.accounts.pool_state;
+        pool_state.update_weights(oracle::block_timestamp());
+        // Tokens sent to the vaults from outside the pool are left out
+        let (vault_0_amount, vault_1_amount) = pool_state.vault_amounts(
+            ctx.accounts.token_0_vault.amount,
+            ctx.accounts.token_1_vault.amount,
+        );
+        // update the previous price to the observation
+        let (token_0_price_x32, token_1_price_x32) =
+            pool_state.token_price_x32(vault_0_amount, vault_1_amount);
+        let (total_token_0_amount, total_token_1_amount) =
+            pool_state.vault_amount_without_fee(vault_0_amount, vault_1_amount);
+        ctx.accounts.observation_state.update(
+            oracle::block_timestamp(),
+            token_0_price_x32,
+            token_1_price_x32,
+            oracle::liquidity(total_token_0_amount, total_token_1_amount),
+        );
+        amount_0 = amount_0_requested.min(pool_state.creator_fees_token_0);
+        amount_1 = amount_1_requested.min(pool_state.creator_fees_token_1);
+
+        pool_state.creator_fees_token_0 = pool_state
+            .creator_fees_token_0
+            .checked_sub(amount_0)
+            .unwrap();
+        pool_state.creator_fees_token_1 = pool_state
+            .creator_fees_token_1
+            .checked_sub(amount_1)
+            .unwrap();
+        auth_bump = pool_state.auth_bump;
+        pool_state.recent_epoch = Clock::get()?.epoch;
+    }
+
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_0_vault.to_account_info(),
+        ctx.accounts.recipient_token_0_account.to_account_info(),
+        ctx.accounts.vault_0_mint.to_account_info(),
+        if ctx.accounts.vault_0_mint.to_account_info().owner == ctx.accounts.token_program.key {
+            ctx.accounts.token_program.to_account_info()
+        } else {
+            ctx.accounts.token_program_2022.to_account_info()
+        },
+        amount_0,
+        &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]],
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.light_token_cpi_authority.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    )?;
+
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_1_vault.to_account_info(),
+        ctx.accounts.recipient_token_1_account.to_account_info(),
+        ctx.accounts.vault_1_mint.to_account_info(),
+        if ctx.accounts.vault_1_mint.to_account_info().owner == ctx.accounts.token_program.key {
+            ctx.accounts.token_program.to_account_info()
+        } else {
+            ctx.accounts.token_program_2022.to_account_info()
+        },
+        amount_1,
+        &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]],
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.light_token_cpi_authority.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    )?;
+
+    let pool_state = &mut ctx.accounts.pool_state;
+    pool_state.update_reserve(
+        &ctx.accounts.token_0_vault.to_account_info(),
+        ctx.accounts.token_0_vault.amount,
+    )?;
+    pool_state.update_reserve(
+        &ctx.accounts.token_1_vault.to_account_info(),
+        ctx.accounts.token_1_vault.amount,
+    )?;
+
+    Ok(())
+}
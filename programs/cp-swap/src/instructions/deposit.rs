@@ -1,13 +1,14 @@
 use crate::curve::CurveCalculator;
 use crate::curve::RoundDirection;
+use crate::curve::SwapCurve;
 use crate::error::ErrorCode;
 use crate::states::*;
 use crate::utils::token::*;
 use anchor_lang::prelude::*;
 use light_anchor_spl::token::Token;
 use light_anchor_spl::token_interface::Token2022;
+use light_anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 use light_token::instruction::MintToCpi;
-use light_anchor_spl::token_interface::{TokenAccount, Mint,TokenInterface};
 
 #[derive(Accounts)]
 pub struct Deposit<'info> {
@@ -107,11 +108,23 @@ pub fn deposit(
     if !pool_state.get_status_by_bit(PoolStatusBitIndex::Deposit) {
         return err!(ErrorCode::NotApproved);
     }
-    let (total_token_0_amount, total_token_1_amount) = pool_state.vault_amount_without_fee(
+    // Only the creator provides liquidity until an LBP graduates
+    if pool_state.is_lbp() && ctx.accounts.owner.key() != pool_state.pool_creator {
+        return err!(ErrorCode::NotApproved);
+    }
+    pool_state.update_weights(oracle::block_timestamp());
+    // Tokens sent to the vaults from outside the pool are left out
+    let (vault_0_amount, vault_1_amount) = pool_state.vault_amounts(
         ctx.accounts.token_0_vault.amount,
         ctx.accounts.token_1_vault.amount,
     );
+    let (total_token_0_amount, total_token_1_amount) =
+        pool_state.vault_amount_without_fee(vault_0_amount, vault_1_amount);
+    let (token_0_price_x32, token_1_price_x32) =
+        pool_state.token_price_x32(vault_0_amount, vault_1_amount);
+    // Every curve converts lp tokens in proportion to the reserves
     let results = CurveCalculator::lp_tokens_to_trading_tokens(
+        &SwapCurve::ConstantProduct,
         u128::from(lp_token_amount),
         u128::from(pool_state.lp_supply),
         u128::from(total_token_0_amount),
@@ -215,7 +228,76 @@ pub fn deposit(
         max_top_up: None,
     }
     .invoke_signed(&[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]])?;
+    // update the previous price to the observation, when it is passed
+    oracle::update_optional_observation(
+        ctx.remaining_accounts,
+        pool_state.observation_key,
+        oracle::block_timestamp(),
+        token_0_price_x32,
+        token_1_price_x32,
+        oracle::liquidity(total_token_0_amount, total_token_1_amount),
+    )?;
+    pool_state.update_reserve(
+        &ctx.accounts.token_0_vault.to_account_info(),
+        ctx.accounts.token_0_vault.amount,
+    )?;
+    pool_state.update_reserve(
+        &ctx.accounts.token_1_vault.to_account_info(),
+        ctx.accounts.token_1_vault.amount,
+    )?;
     pool_state.recent_epoch = Clock::get()?.epoch;
 
     Ok(())
 }
+
+/// Deposit up to the given token amounts, minting the largest lp amount they
+/// cover at the current reserves
+pub fn deposit_by_token_amounts(
+    ctx: Context<Deposit>,
+    maximum_token_0_amount: u64,
+    maximum_token_1_amount: u64,
+    minimum_lp_token_amount: u64,
+) -> Result<()> {
+    let pool_state = &ctx.accounts.pool_state;
+    // Tokens sent to the vaults from outside the pool are left out
+    let (vault_0_amount, vault_1_amount) = pool_state.vault_amounts(
+        ctx.accounts.token_0_vault.amount,
+        ctx.accounts.token_1_vault.amount,
+    );
+    let (total_token_0_amount, total_token_1_amount) =
+        pool_state.vault_amount_without_fee(vault_0_amount, vault_1_amount);
+    // Only what arrives in the vaults after transfer fees counts
+    let token_0_amount = maximum_token_0_amount
+        .checked_sub(get_transfer_fee(
+            &ctx.accounts.vault_0_mint.to_account_info(),
+            maximum_token_0_amount,
+        )?)
+        .unwrap();
+    let token_1_amount = maximum_token_1_amount
+        .checked_sub(get_transfer_fee(
+            &ctx.accounts.vault_1_mint.to_account_info(),
+            maximum_token_1_amount,
+        )?)
+        .unwrap();
+    let lp_token_amount = CurveCalculator::trading_tokens_to_lp_tokens(
+        u128::from(token_0_amount),
+        u128::from(token_1_amount),
+        u128::from(pool_state.lp_supply),
+        u128::from(total_token_0_amount),
+        u128::from(total_token_1_amount),
+    )
+    .ok_or(ErrorCode::ZeroTradingTokens)?;
+    let lp_token_amount = u64::try_from(lp_token_amount).map_err(|_| ErrorCode::InvalidInput)?;
+    require_gt!(lp_token_amount, 0, ErrorCode::ZeroTradingTokens);
+    require_gte!(
+        lp_token_amount,
+        minimum_lp_token_amount,
+        ErrorCode::ExceededSlippage
+    );
+    deposit(
+        ctx,
+        lp_token_amount,
+        maximum_token_0_amount,
+        maximum_token_1_amount,
+    )
+}
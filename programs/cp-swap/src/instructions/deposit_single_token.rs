@@ -0,0 +1,384 @@
+use crate::curve::calculator::CurveCalculator;
+use crate::curve::{Curve, RoundDirection, TradeDirection};
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::utils::token::*;
+use anchor_lang::prelude::*;
+use light_anchor_spl::token::Token;
+use light_anchor_spl::token_interface::Token2022;
+use light_anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use light_token::instruction::MintToCpi;
+
+#[derive(Accounts)]
+pub struct DepositSingleToken<'info> {
+    /// Pays to mint the position
+    pub owner: Signer<'info>,
+
+    /// CHECK: pool vault and lp mint authority
+    #[account(
+        mut,
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// The factory state to read protocol fees
+    #[account(address = pool_state.amm_config)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    #[account(mut)]
+    pub pool_state: Account<'info, PoolState>,
+
+    /// The program account for the most recent oracle observation
+    #[account(mut, address = pool_state.observation_key)]
+    pub observation_state: Box<Account<'info, ObservationState>>,
+
+    /// Owner lp token account
+    #[account(mut,  token::authority = owner)]
+    pub owner_lp_token: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The payer's token account for the deposited token
+    #[account(
+        mut,
+        token::mint = input_vault.mint,
+        token::authority = owner
+    )]
+    pub input_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The vault that receives the deposited token
+    #[account(mut)]
+    pub input_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The vault of the other pool token
+    pub output_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// token Program
+    pub token_program: Program<'info, Token>,
+
+    /// Token program 2022
+    pub token_program_2022: Program<'info, Token2022>,
+
+    /// CHECK: Light Token program for CPI.
+    pub light_token_program: Interface<'info, TokenInterface>,
+
+    /// The mint of the deposited token
+    #[account(
+        address = input_vault.mint
+    )]
+    pub input_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Lp mint
+    #[account(
+        mut,
+        address = pool_state.lp_mint @ ErrorCode::IncorrectLpMint
+    )]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: light-token CPI authority.
+    pub light_token_cpi_authority: AccountInfo<'info>,
+}
+
+pub fn deposit_single_token(
+    ctx: Context<DepositSingleToken>,
+    amount_in: u64,
+    minimum_lp_token_amount: u64,
+) -> Result<()> {
+    let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
+    let pool_id = ctx.accounts.pool_state.key();
+    let pool_state = &mut ctx.accounts.pool_state;
+    if !pool_state.get_status_by_bit(PoolStatusBitIndex::Deposit)
+        || !pool_state.get_status_by_bit(PoolStatusBitIndex::Swap)
+        || block_timestamp < pool_state.open_time
+    {
+        return err!(ErrorCode::NotApproved);
+    }
+    if block_timestamp < pool_state.cooldown_end_time {
+        return err!(ErrorCode::PoolInCooldown);
+    }
+    // Only the creator provides liquidity until an LBP graduates
+    if pool_state.is_lbp() && ctx.accounts.owner.key() != pool_state.pool_creator {
+        return err!(ErrorCode::NotApproved);
+    }
+    pool_state.update_weights(block_timestamp);
+    // Tokens sent to the vaults from outside the pool are left out
+    let input_vault_amount = pool_state.vault_amount(
+        &ctx.accounts.input_vault.key(),
+        ctx.accounts.input_vault.amount,
+    );
+    let output_vault_amount = pool_state.vault_amount(
+        &ctx.accounts.output_vault.key(),
+        ctx.accounts.output_vault.amount,
+    );
+
+    let transfer_fee =
+        get_transfer_fee(&ctx.accounts.input_token_mint.to_account_info(), amount_in)?;
+    // Take transfer fees into account for actual amount transferred in
+    let actual_amount_in = amount_in.saturating_sub(transfer_fee);
+    require_gt!(actual_amount_in, 0);
+
+    // Calculate the trade amounts and the price before the deposit
+    let (trade_direction, total_input_token_amount, total_output_token_amount) =
+        if ctx.accounts.input_vault.key() == pool_state.token_0_vault
+            && ctx.accounts.output_vault.key() == pool_state.token_1_vault
+        {
+            let (total_input_token_amount, total_output_token_amount) =
+                pool_state.vault_amount_without_fee(input_vault_amount, output_vault_amount);
+            (
+                TradeDirection::ZeroForOne,
+                total_input_token_amount,
+                total_output_token_amount,
+            )
+        } else if ctx.accounts.input_vault.key() == pool_state.token_1_vault
+            && ctx.accounts.output_vault.key() == pool_state.token_0_vault
+        {
+            let (total_output_token_amount, total_input_token_amount) =
+                pool_state.vault_amount_without_fee(output_vault_amount, input_vault_amount);
+            (
+                TradeDirection::OneForZero,
+                total_input_token_amount,
+                total_output_token_amount,
+            )
+        } else {
+            return err!(ErrorCode::InvalidVault);
+        };
+    let (vault_0_amount, vault_1_amount) =
+        trade_direction.token_amounts(input_vault_amount, output_vault_amount);
+    let (token_0_price_x32, token_1_price_x32) =
+        pool_state.token_price_x32(vault_0_amount, vault_1_amount);
+    let (total_token_0_amount, total_token_1_amount) =
+        trade_direction.token_amounts(total_input_token_amount, total_output_token_amount);
+
+    // Swap part of the input into the other token, the swap stays inside the
+    // pool as both sides are deposited right after
+    let curve = pool_state.swap_curve(&ctx.accounts.amm_config)?;
+    let trade_fee_rate = ctx.accounts.amm_config.effective_trade_fee_rate(
+        &ctx.accounts.observation_state,
+        token_0_price_x32,
+        pool_state.open_time,
+        block_timestamp,
+    );
+    let swap_amount = CurveCalculator::single_side_swap_amount(
+        &curve,
+        trade_direction,
+        u128::from(actual_amount_in),
+        u128::from(total_input_token_amount),
+        u128::from(total_output_token_amount),
+        &ctx.accounts.amm_config.fee_rates(trade_fee_rate),
+    )
+    .ok_or(ErrorCode::ZeroTradingTokens)?;
+    let result = CurveCalculator::swap_base_input(
+        &curve,
+        trade_direction,
+        swap_amount,
+        u128::from(total_input_token_amount),
+        u128::from(total_output_token_amount),
+        &ctx.accounts.amm_config.fee_rates(trade_fee_rate),
+    )
+    .ok_or(ErrorCode::ZeroTradingTokens)?;
+
+    let constant_before = curve
+        .invariant(
+            u128::from(total_token_0_amount),
+            u128::from(total_token_1_amount),
+        )
+        .unwrap();
+    let (new_swap_token_0_amount, new_swap_token_1_amount) = trade_direction.token_amounts(
+        result
+            .new_swap_source_amount
+            .checked_sub(result.trade_fee)
+            .unwrap(),
+        result.new_swap_destination_amount,
+    );
+    let constant_after = curve
+        .invariant(new_swap_token_0_amount, new_swap_token_1_amount)
+        .unwrap();
+    require_gte!(constant_after, constant_before);
+
+    // Mint LP for the rest of the input and the swap output, against the
+    // reserves after the swap
+    let new_swap_source_amount = result
+        .new_swap_source_amount
+        .checked_sub(result.protocol_fee)
+        .unwrap()
+        .checked_sub(result.fund_fee)
+        .unwrap()
+        .checked_sub(result.creator_fee)
+        .unwrap();
+    let new_swap_destination_amount = result.new_swap_destination_amount;
+    let lp_token_amount = (u128::from(actual_amount_in) - swap_amount)
+        .checked_mul(u128::from(pool_state.lp_supply))
+        .unwrap()
+        .checked_div(new_swap_source_amount)
+        .unwrap()
+        .min(
+            result
+                .destination_amount_swapped
+                .checked_mul(u128::from(pool_state.lp_supply))
+                .unwrap()
+                .checked_div(new_swap_destination_amount)
+                .unwrap(),
+        );
+    let lp_token_amount = u64::try_from(lp_token_amount).unwrap();
+    require_gt!(lp_token_amount, 0, ErrorCode::ZeroTradingTokens);
+    require_gte!(
+        lp_token_amount,
+        minimum_lp_token_amount,
+        ErrorCode::ExceededSlippage
+    );
+    let (new_swap_token_0_amount, new_swap_token_1_amount) =
+        trade_direction.token_amounts(new_swap_source_amount, new_swap_destination_amount);
+    let results = CurveCalculator::lp_tokens_to_trading_tokens(
+        &curve,
+        u128::from(lp_token_amount),
+        u128::from(pool_state.lp_supply),
+        new_swap_token_0_amount,
+        new_swap_token_1_amount,
+        RoundDirection::Ceiling,
+    )
+    .ok_or(ErrorCode::ZeroTradingTokens)?;
+
+    let protocol_fee = u64::try_from(result.protocol_fee).unwrap();
+    let fund_fee = u64::try_from(result.fund_fee).unwrap();
+    let creator_fee = u64::try_from(result.creator_fee).unwrap();
+    match trade_direction {
+        TradeDirection::ZeroForOne => {
+            pool_state.protocol_fees_token_0 = pool_state
+                .protocol_fees_token_0
+                .checked_add(protocol_fee)
+                .unwrap();
+            pool_state.fund_fees_token_0 =
+                pool_state.fund_fees_token_0.checked_add(fund_fee).unwrap();
+            pool_state.creator_fees_token_0 = pool_state
+                .creator_fees_token_0
+                .checked_add(creator_fee)
+                .unwrap();
+        }
+        TradeDirection::OneForZero => {
+            pool_state.protocol_fees_token_1 = pool_state
+                .protocol_fees_token_1
+                .checked_add(protocol_fee)
+                .unwrap();
+            pool_state.fund_fees_token_1 =
+                pool_state.fund_fees_token_1.checked_add(fund_fee).unwrap();
+            pool_state.creator_fees_token_1 = pool_state
+                .creator_fees_token_1
+                .checked_add(creator_fee)
+                .unwrap();
+        }
+    };
+    let (new_vault_0_amount, new_vault_1_amount) = trade_direction.token_amounts(
+        input_vault_amount.checked_add(actual_amount_in).unwrap(),
+        output_vault_amount,
+    );
+    pool_state.check_price_deviation(
+        &ctx.accounts.amm_config,
+        &ctx.accounts.observation_state,
+        new_vault_0_amount,
+        new_vault_1_amount,
+        block_timestamp,
+    )?;
+
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "swap_amount:{}, destination_amount_swapped:{}, lp_token_amount:{}, token_0_amount:{}, token_1_amount:{}",
+        swap_amount,
+        result.destination_amount_swapped,
+        lp_token_amount,
+        results.token_0_amount,
+        results.token_1_amount
+    );
+
+    // The transfer fee is split between the swapped and the deposited part
+    let swap_transfer_fee = u64::try_from(
+        u128::from(transfer_fee)
+            .checked_mul(swap_amount)
+            .unwrap()
+            .checked_div(u128::from(actual_amount_in))
+            .unwrap(),
+    )
+    .unwrap();
+    emit!(SwapEvent {
+        pool_id,
+        input_vault_before: total_input_token_amount,
+        output_vault_before: total_output_token_amount,
+        input_amount: u64::try_from(result.source_amount_swapped).unwrap(),
+        output_amount: u64::try_from(result.destination_amount_swapped).unwrap(),
+        input_transfer_fee: swap_transfer_fee,
+        output_transfer_fee: 0,
+        base_input: true,
+        trade_fee_rate,
+        referral_fee: 0,
+        unfilled_amount: 0,
+    });
+    let (token_0_transfer_fee, token_1_transfer_fee) =
+        trade_direction.token_amounts(transfer_fee - swap_transfer_fee, 0);
+    emit!(LpChangeEvent {
+        pool_id,
+        lp_amount_before: pool_state.lp_supply,
+        token_0_vault_before: u64::try_from(new_swap_token_0_amount).unwrap(),
+        token_1_vault_before: u64::try_from(new_swap_token_1_amount).unwrap(),
+        token_0_amount: u64::try_from(results.token_0_amount).unwrap(),
+        token_1_amount: u64::try_from(results.token_1_amount).unwrap(),
+        token_0_transfer_fee,
+        token_1_transfer_fee,
+        change_type: 0
+    });
+
+    // Rounding dust of the deposit stays in the pool
+    transfer_from_user_to_pool_vault(
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.input_token_account.to_account_info(),
+        ctx.accounts.input_vault.to_account_info(),
+        ctx.accounts.input_token_mint.to_account_info(),
+        if ctx.accounts.input_token_mint.to_account_info().owner == ctx.accounts.token_program.key {
+            ctx.accounts.token_program.to_account_info()
+        } else {
+            ctx.accounts.token_program_2022.to_account_info()
+        },
+        amount_in,
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.light_token_cpi_authority.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    )?;
+
+    pool_state.lp_supply = pool_state.lp_supply.checked_add(lp_token_amount).unwrap();
+
+    MintToCpi {
+        mint: ctx.accounts.lp_mint.to_account_info(),
+        destination: ctx.accounts.owner_lp_token.to_account_info(),
+        amount: lp_token_amount,
+        authority: ctx.accounts.authority.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+        max_top_up: None,
+    }
+    .invoke_signed(&[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]])?;
+
+    ctx.accounts.observation_state.accumulate_swap(
+        trade_direction,
+        u64::try_from(result.source_amount_swapped).unwrap(),
+        u64::try_from(result.destination_amount_swapped).unwrap(),
+        u64::try_from(result.trade_fee).unwrap(),
+    );
+    // update the previous price to the observation
+    ctx.accounts.observation_state.update(
+        oracle::block_timestamp(),
+        token_0_price_x32,
+        token_1_price_x32,
+        oracle::liquidity(total_token_0_amount, total_token_1_amount),
+    );
+    pool_state.update_reserve(
+        &ctx.accounts.input_vault.to_account_info(),
+        ctx.accounts.input_vault.amount,
+    )?;
+    pool_state.update_reserve(
+        &ctx.accounts.output_vault.to_account_info(),
+        ctx.accounts.output_vault.amount,
+    )?;
+    pool_state.recent_epoch = Clock::get()?.epoch;
+
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use crate::error::ErrorCode;
+use crate::instructions::swap_base_input::*;
+use crate::states::*;
+use crate::utils::{merkle_leaf, verify_merkle_proof};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetEarlyAccess<'info> {
+    /// Only the pool creator can set the allowlist
+    #[account(address = pool_state.pool_creator @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub pool_state: Account<'info, PoolState>,
+}
+
+pub fn set_early_access(
+    ctx: Context<SetEarlyAccess>,
+    early_access_root: [u8; 32],
+    early_access_time: u64,
+) -> Result<()> {
+    let block_timestamp = Clock::get()?.unix_timestamp as u64;
+    let pool_state = &mut ctx.accounts.pool_state;
+    // The allowlist can't be changed once anyone may trade
+    require_gt!(
+        pool_state.open_time,
+        block_timestamp,
+        ErrorCode::NotApproved
+    );
+    require_gt!(
+        pool_state.open_time,
+        early_access_time,
+        ErrorCode::InvalidInput
+    );
+    pool_state.early_access_root = early_access_root;
+    pool_state.early_access_time = early_access_time;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SwapEarlyAccess<'info> {
+    pub swap: Swap<'info>,
+
+    /// Pays for the record of the wallet on its first early access swap
+    #[account(mut)]
+    pub rent_payer: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        seeds = [
+            EARLY_ACCESS_SEED.as_bytes(),
+            swap.pool_state.key().as_ref(),
+            swap.payer.key().as_ref(),
+        ],
+        bump,
+        payer = rent_payer,
+        space = EarlyAccessRecord::LEN
+    )]
+    pub early_access_record: Account<'info, EarlyAccessRecord>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn swap_base_input_early_access<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SwapEarlyAccess<'info>>,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    max_amount_in: u64,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    let block_timestamp = Clock::get()?.unix_timestamp as u64;
+    let pool_state = &ctx.accounts.swap.pool_state;
+    if pool_state.early_access_root == [0u8; 32]
+        || block_timestamp < pool_state.early_access_time
+        || block_timestamp >= pool_state.open_time
+    {
+        return err!(ErrorCode::NotApproved);
+    }
+    let leaf = merkle_leaf(ctx.accounts.swap.payer.key().as_ref(), max_amount_in);
+    require!(
+        verify_merkle_proof(&proof, pool_state.early_access_root, leaf),
+        ErrorCode::InvalidMerkleProof
+    );
+    let early_access_time = pool_state.early_access_time;
+
+    let early_access_record = &mut ctx.accounts.early_access_record;
+    early_access_record.bump = ctx.bumps.early_access_record;
+    early_access_record.pool_id = ctx.accounts.swap.pool_state.key();
+    early_access_record.owner = ctx.accounts.swap.payer.key();
+    early_access_record.amount_in = early_access_record
+        .amount_in
+        .checked_add(amount_in)
+        .unwrap();
+    require_gte!(
+        max_amount_in,
+        early_access_record.amount_in,
+        ErrorCode::EarlyAccessCapExceeded
+    );
+
+    let SwapEarlyAccessBumps { swap: bumps, .. } = ctx.bumps;
+    swap_base_input_from(
+        Context::<Swap>::new(
+            ctx.program_id,
+            &mut ctx.accounts.swap,
+            ctx.remaining_accounts,
+            bumps,
+        ),
+        amount_in,
+        minimum_amount_out,
+        early_access_time,
+    )
+}
@@ -0,0 +1,365 @@
+use crate::curve::calculator::Curve;
+use crate::curve::{Fees, TradeDirection};
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::utils::token::*;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    self,
+    instruction::{AccountMeta, Instruction},
+    program::invoke,
+};
+use light_anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Instruction discriminator of the borrower callback, the anchor sighash of
+/// `flash_swap_callback`
+pub const FLASH_SWAP_CALLBACK_DISCRIMINATOR: [u8; 8] = [225, 54, 80, 1, 45, 208, 202, 124];
+
+/// Arguments the borrower program is called back with, after the discriminator
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct FlashSwapCallbackArgs {
+    /// The user that started the flash swap
+    pub sender: Pubkey,
+    pub amount_0_out: u64,
+    pub amount_1_out: u64,
+    /// Data passed through from the flash swap instruction
+    pub data: Vec<u8>,
+}
+
+#[derive(Accounts)]
+pub struct FlashSwap<'info> {
+    /// The user performing the flash swap
+    pub payer: Signer<'info>,
+
+    /// CHECK: pool vault and lp mint authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// The factory state to read protocol fees
+    #[account(address = pool_state.amm_config)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// The program account of the pool the tokens are borrowed from
+    #[account(mut)]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    /// The program account for the most recent oracle observation
+    #[account(mut, address = pool_state.observation_key)]
+    pub observation_state: Box<Account<'info, ObservationState>>,
+
+    /// The address that holds pool tokens for token_0
+    #[account(
+        mut,
+        constraint = token_0_vault.key() == pool_state.token_0_vault
+    )]
+    pub token_0_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for token_1
+    #[account(
+        mut,
+        constraint = token_1_vault.key() == pool_state.token_1_vault
+    )]
+    pub token_1_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The token account that receives the borrowed token_0
+    #[account(
+        mut,
+        token::mint = token_0_vault.mint,
+    )]
+    pub token_0_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The token account that receives the borrowed token_1
+    #[account(
+        mut,
+        token::mint = token_1_vault.mint,
+    )]
+    pub token_1_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of token_0 vault
+    #[account(
+        address = token_0_vault.mint
+    )]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of token_1 vault
+    #[account(
+        address = token_1_vault.mint
+    )]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: the program called back to repay the pool, it receives the
+    /// remaining accounts
+    #[account(executable)]
+    pub borrower_program: UncheckedAccount<'info>,
+
+    /// Light Token program for CPI
+    pub light_token_program: Interface<'info, TokenInterface>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: light_token CPI authority.
+    pub light_token_cpi_authority: AccountInfo<'info>,
+}
+
+pub fn flash_swap<'info>(
+    ctx: Context<'_, '_, 'info, 'info, FlashSwap<'info>>,
+    amount_0_out: u64,
+    amount_1_out: u64,
+    data: Vec<u8>,
+) -> Result<()> {
+    require!(
+        amount_0_out > 0 || amount_1_out > 0,
+        ErrorCode::ZeroTradingTokens
+    );
+    let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
+    let pool_id = ctx.accounts.pool_state.key();
+    let pool_state = &mut ctx.accounts.pool_state;
+    if !pool_state.get_status_by_bit(PoolStatusBitIndex::Swap)
+        || block_timestamp < pool_state.open_time
+    {
+        return err!(ErrorCode::NotApproved);
+    }
+    if block_timestamp < pool_state.cooldown_end_time {
+        return err!(ErrorCode::PoolInCooldown);
+    }
+    pool_state.update_weights(block_timestamp);
+
+    let (vault_0_balance_before, vault_1_balance_before) = (
+        ctx.accounts.token_0_vault.amount,
+        ctx.accounts.token_1_vault.amount,
+    );
+    // Tokens sent to the vaults from outside the pool are left out
+    let (vault_0_amount_before, vault_1_amount_before) =
+        pool_state.vault_amounts(vault_0_balance_before, vault_1_balance_before);
+    let (total_token_0_amount, total_token_1_amount) =
+        pool_state.vault_amount_without_fee(vault_0_amount_before, vault_1_amount_before);
+    require_gt!(total_token_0_amount, amount_0_out);
+    require_gt!(total_token_1_amount, amount_1_out);
+    let (token_0_price_x32, token_1_price_x32) =
+        pool_state.token_price_x32(vault_0_amount_before, vault_1_amount_before);
+    let curve = pool_state.swap_curve(&ctx.accounts.amm_config)?;
+    let trade_fee_rate = ctx.accounts.amm_config.effective_trade_fee_rate(
+        &ctx.accounts.observation_state,
+        token_0_price_x32,
+        pool_state.open_time,
+        block_timestamp,
+    );
+    let constant_before = curve
+        .invariant(
+            u128::from(total_token_0_amount),
+            u128::from(total_token_1_amount),
+        )
+        .unwrap();
+
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_0_vault.to_account_info(),
+        ctx.accounts.token_0_account.to_account_info(),
+        ctx.accounts.vault_0_mint.to_account_info(),
+        ctx.accounts.light_token_program.to_account_info(),
+        amount_0_out,
+        &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.light_token_cpi_authority.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    )?;
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_1_vault.to_account_info(),
+        ctx.accounts.token_1_account.to_account_info(),
+        ctx.accounts.vault_1_mint.to_account_info(),
+        ctx.accounts.light_token_program.to_account_info(),
+        amount_1_out,
+        &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.light_token_cpi_authority.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    )?;
+
+    // The borrower repays into the vaults. It can't re-enter this program, the
+    // runtime only allows direct self recursion.
+    let mut callback_data = FLASH_SWAP_CALLBACK_DISCRIMINATOR.to_vec();
+    FlashSwapCallbackArgs {
+        sender: ctx.accounts.payer.key(),
+        amount_0_out,
+        amount_1_out,
+        data,
+    }
+    .serialize(&mut callback_data)?;
+    invoke(
+        &Instruction {
+            program_id: ctx.accounts.borrower_program.key(),
+            accounts: ctx
+                .remaining_accounts
+                .iter()
+                .map(|account| AccountMeta {
+                    pubkey: account.key(),
+                    is_signer: account.is_signer,
+                    is_writable: account.is_writable,
+                })
+                .collect(),
+            data: callback_data,
+        },
+        ctx.remaining_accounts,
+    )?;
+
+    ctx.accounts.token_0_vault.reload()?;
+    ctx.accounts.token_1_vault.reload()?;
+    // What came back on top of what is left after the borrow
+    let amount_0_in = ctx
+        .accounts
+        .token_0_vault
+        .amount
+        .saturating_sub(vault_0_balance_before.checked_sub(amount_0_out).unwrap());
+    let amount_1_in = ctx
+        .accounts
+        .token_1_vault
+        .amount
+        .saturating_sub(vault_1_balance_before.checked_sub(amount_1_out).unwrap());
+    let vault_0_amount_after = vault_0_amount_before - amount_0_out + amount_0_in;
+    let vault_1_amount_after = vault_1_amount_before - amount_1_out + amount_1_in;
+    require!(
+        amount_0_in > 0 || amount_1_in > 0,
+        ErrorCode::ZeroTradingTokens
+    );
+
+    let trade_fee_0 =
+        u64::try_from(Fees::trading_fee(u128::from(amount_0_in), trade_fee_rate).unwrap()).unwrap();
+    let trade_fee_1 =
+        u64::try_from(Fees::trading_fee(u128::from(amount_1_in), trade_fee_rate).unwrap()).unwrap();
+    let protocol_fee_rate = ctx.accounts.amm_config.protocol_fee_rate;
+    let fund_fee_rate = ctx.accounts.amm_config.fund_fee_rate;
+    let protocol_fee_0 =
+        u64::try_from(Fees::protocol_fee(u128::from(trade_fee_0), protocol_fee_rate).unwrap())
+            .unwrap();
+    let protocol_fee_1 =
+        u64::try_from(Fees::protocol_fee(u128::from(trade_fee_1), protocol_fee_rate).unwrap())
+            .unwrap();
+    let fund_fee_0 =
+        u64::try_from(Fees::fund_fee(u128::from(trade_fee_0), fund_fee_rate).unwrap()).unwrap();
+    let fund_fee_1 =
+        u64::try_from(Fees::fund_fee(u128::from(trade_fee_1), fund_fee_rate).unwrap()).unwrap();
+    let creator_fee_rate = ctx.accounts.amm_config.creator_fee_rate;
+    let creator_fee_0 =
+        u64::try_from(Fees::creator_fee(u128::from(trade_fee_0), creator_fee_rate).unwrap())
+            .unwrap();
+    let creator_fee_1 =
+        u64::try_from(Fees::creator_fee(u128::from(trade_fee_1), creator_fee_rate).unwrap())
+            .unwrap();
+
+    // Same check as a swap, the trade fee doesn't count towards the invariant
+    let (new_total_token_0_amount, new_total_token_1_amount) =
+        pool_state.vault_amount_without_fee(vault_0_amount_after, vault_1_amount_after);
+    let constant_after = curve
+        .invariant(
+            u128::from(new_total_token_0_amount.checked_sub(trade_fee_0).unwrap()),
+            u128::from(new_total_token_1_amount.checked_sub(trade_fee_1).unwrap()),
+        )
+        .unwrap();
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "amount_0_in:{}, amount_1_in:{}, trade_fee_0:{}, trade_fee_1:{}, constant_before:{},constant_after:{}",
+        amount_0_in,
+        amount_1_in,
+        trade_fee_0,
+        trade_fee_1,
+        constant_before,
+        constant_after
+    );
+    require_gte!(constant_after, constant_before);
+
+    pool_state.protocol_fees_token_0 = pool_state
+        .protocol_fees_token_0
+        .checked_add(protocol_fee_0)
+        .unwrap();
+    pool_state.protocol_fees_token_1 = pool_state
+        .protocol_fees_token_1
+        .checked_add(protocol_fee_1)
+        .unwrap();
+    pool_state.fund_fees_token_0 = pool_state
+        .fund_fees_token_0
+        .checked_add(fund_fee_0)
+        .unwrap();
+    pool_state.fund_fees_token_1 = pool_state
+        .fund_fees_token_1
+        .checked_add(fund_fee_1)
+        .unwrap();
+    pool_state.creator_fees_token_0 = pool_state
+        .creator_fees_token_0
+        .checked_add(creator_fee_0)
+        .unwrap();
+    pool_state.creator_fees_token_1 = pool_state
+        .creator_fees_token_1
+        .checked_add(creator_fee_1)
+        .unwrap();
+    pool_state.check_price_deviation(
+        &ctx.accounts.amm_config,
+        &ctx.accounts.observation_state,
+        vault_0_amount_after,
+        vault_1_amount_after,
+        block_timestamp,
+    )?;
+
+    emit!(FlashSwapEvent {
+        pool_id,
+        token_0_vault_before: total_token_0_amount,
+        token_1_vault_before: total_token_1_amount,
+        amount_0_out,
+        amount_1_out,
+        amount_0_in,
+        amount_1_in,
+        trade_fee_rate,
+    });
+
+    // Each token paid in counts as a swap into the other token
+    if amount_0_in > 0 {
+        ctx.accounts.observation_state.accumulate_swap(
+            TradeDirection::ZeroForOne,
+            amount_0_in,
+            amount_1_out,
+            trade_fee_0,
+        );
+    }
+    if amount_1_in > 0 {
+        ctx.accounts.observation_state.accumulate_swap(
+            TradeDirection::OneForZero,
+            amount_1_in,
+            amount_0_out,
+            trade_fee_1,
+        );
+    }
+    // update the previous price to the observation
+    ctx.accounts.observation_state.update(
+        oracle::block_timestamp(),
+        token_0_price_x32,
+        token_1_price_x32,
+        oracle::liquidity(total_token_0_amount, total_token_1_amount),
+    );
+    pool_state.update_reserve(
+        &ctx.accounts.token_0_vault.to_account_info(),
+        vault_0_balance_before,
+    )?;
+    pool_state.update_reserve(
+        &ctx.accounts.token_1_vault.to_account_info(),
+        vault_1_balance_before,
+    )?;
+    pool_state.recent_epoch = Clock::get()?.epoch;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn callback_discriminator_is_anchor_sighash() {
+        let hash = solana_program::hash::hash(b"global:flash_swap_callback");
+        assert_eq!(FLASH_SWAP_CALLBACK_DISCRIMINATOR, hash.to_bytes()[..8]);
+    }
+}
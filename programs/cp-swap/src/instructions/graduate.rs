@@ -0,0 +1,20 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct Graduate<'info> {
+    /// The liquidity bootstrapping pool, anyone can graduate it once the sale ended
+    #[account(mut)]
+    pub pool_state: Account<'info, PoolState>,
+}
+
+pub fn graduate(ctx: Context<Graduate>) -> Result<()> {
+    let block_timestamp = Clock::get()?.unix_timestamp as u64;
+    let pool_state = &mut ctx.accounts.pool_state;
+    if !pool_state.is_lbp() || block_timestamp < pool_state.lbp_end_time {
+        return err!(ErrorCode::NotApproved);
+    }
+    pool_state.graduate(block_timestamp);
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(observation_cardinality: u16)]
+pub struct IncreaseObservationCardinality<'info> {
+    /// Anyone can pay for a longer oracle history
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The oracle observation account to grow
+    #[account(
+        mut,
+        realloc = 8 + ObservationState::space(usize::from(observation_cardinality)),
+        realloc::payer = payer,
+        realloc::zero = false,
+    )]
+    pub observation_state: Box<Account<'info, ObservationState>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Grows the observation ring buffer of a pool to `observation_cardinality`
+/// observations, at most `OBSERVATION_NUM`
+pub fn increase_observation_cardinality(
+    ctx: Context<IncreaseObservationCardinality>,
+    observation_cardinality: u16,
+) -> Result<()> {
+    ctx.accounts
+        .observation_state
+        .increase_cardinality(usize::from(observation_cardinality))
+}
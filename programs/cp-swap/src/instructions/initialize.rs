@@ -1,4 +1,4 @@
-use crate::curve::CurveCalculator;
+use crate::curve::{CurveCalculator, CurveType, SwapCurve, WeightedCurve, WEIGHT_DENOMINATOR};
 use crate::error::ErrorCode;
 use crate::states::*;
 use crate::utils::*;
@@ -30,6 +30,22 @@ pub struct InitializeParams {
     pub init_amount_0: u64,
     pub init_amount_1: u64,
     pub open_time: u64,
+    /// Weight of token_0 in `WEIGHT_DENOMINATOR`, token_1 gets the rest.
+    /// Only used when the config selects the weighted curve.
+    pub token_0_weight: u64,
+    /// Minimum seconds between two oracle observations, 0 for
+    /// `OBSERVATION_UPDATE_DURATION_DEFAULT`
+    pub observation_update_duration: u64,
+    /// Non zero makes the pool a liquidity bootstrapping pool, its token_0
+    /// weight moves from `token_0_weight` to `lbp_end_token_0_weight` between
+    /// `lbp_start_time` and `lbp_end_time`. Only for weighted configs.
+    pub lbp_end_time: u64,
+    pub lbp_start_time: u64,
+    /// The pool graduates to constant product if it ends at half of
+    /// `WEIGHT_DENOMINATOR`, to a weighted pool at its final weights otherwise
+    pub lbp_end_token_0_weight: u64,
+    /// How the pool reads its reserves, see `RESERVE_MODE_LIVE`
+    pub reserve_mode: u8,
     pub create_accounts_proof: CreateAccountsProof,
     pub lp_mint_signer_bump: u8,
     pub creator_lp_token_bump: u8,
@@ -137,7 +153,7 @@ pub struct Initialize<'info> {
         seeds = [OBSERVATION_SEED.as_bytes(), pool_state.key().as_ref()],
         bump,
         payer = creator,
-        space = 8 + ObservationState::INIT_SPACE
+        space = 8 + ObservationState::space(OBSERVATION_CARDINALITY_DEFAULT)
     )]
     #[light_account(init)]
     pub observation_state: Box<Account<'info, ObservationState>>,
@@ -183,53 +199,89 @@ pub fn initialize<'info>(
         return err!(ErrorCode::NotApproved);
     }
 
+    let (token_0_weight, token_1_weight) =
+        if CurveType::try_from(ctx.accounts.amm_config.curve_type)? == CurveType::Weighted {
+            let token_1_weight = WEIGHT_DENOMINATOR
+                .checked_sub(params.token_0_weight)
+                .ok_or(ErrorCode::InvalidCurve)?;
+            let curve = WeightedCurve::new(params.token_0_weight, token_1_weight)?;
+            (curve.token_0_weight, curve.token_1_weight)
+        } else {
+            (WEIGHT_DENOMINATOR / 2, WEIGHT_DENOMINATOR / 2)
+        };
+    let curve = SwapCurve::new(
+        ctx.accounts.amm_config.curve_type,
+        ctx.accounts.amm_config.amp,
+        token_0_weight,
+        token_1_weight,
+    )?;
+
+    // The stable curve trades raw amounts at par, mints of different decimals
+    // would be priced 10^n apart
+    if curve.curve_type() == CurveType::Stable {
+        require_eq!(
+            ctx.accounts.token_0_mint.decimals,
+            ctx.accounts.token_1_mint.decimals,
+            ErrorCode::InvalidCurve
+        );
+    }
+
     let block_timestamp = clock::Clock::get()?.unix_timestamp as u64;
+    if params.lbp_end_time != 0 {
+        require!(
+            CurveType::try_from(ctx.accounts.amm_config.curve_type)? == CurveType::Weighted
+                && params.lbp_start_time < params.lbp_end_time,
+            ErrorCode::InvalidCurve
+        );
+        WeightedCurve::new(
+            params.lbp_end_token_0_weight,
+            WEIGHT_DENOMINATOR
+                .checked_sub(params.lbp_end_token_0_weight)
+                .ok_or(ErrorCode::InvalidCurve)?,
+        )?;
+    }
+    require_gte!(RESERVE_MODE_SYNC, params.reserve_mode, ErrorCode::InvalidInput);
     // open_time=0 means immediately open (no bump)
     if open_time != 0 && open_time <= block_timestamp {
         open_time = block_timestamp + 1;
     }
 
     let pool_state_key = ctx.accounts.pool_state.key();
+    let rent_free_accounts = RentFreeAccounts {
+        light_token_compressible_config: ctx.accounts.light_token_compressible_config.to_account_info(),
+        light_token_rent_sponsor: ctx.accounts.light_token_rent_sponsor.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+    };
 
     // Create token_0 vault
-    CreateTokenAccountCpi {
-        payer: ctx.accounts.creator.to_account_info(),
-        account: ctx.accounts.token_0_vault.to_account_info(),
-        mint: ctx.accounts.token_0_mint.to_account_info(),
-        owner: ctx.accounts.authority.key(),
-    }
-    .rent_free(
-        ctx.accounts.light_token_compressible_config.to_account_info(),
-        ctx.accounts.light_token_rent_sponsor.to_account_info(),
-        ctx.accounts.system_program.to_account_info(),
-        &crate::ID,
-    )
-    .invoke_signed(&[
-        POOL_VAULT_SEED.as_bytes(),
-        pool_state_key.as_ref(),
-        ctx.accounts.token_0_mint.key().as_ref(),
-        &[ctx.bumps.token_0_vault],
-    ])?;
+    create_pool_vault(
+        ctx.accounts.creator.to_account_info(),
+        ctx.accounts.token_0_vault.to_account_info(),
+        ctx.accounts.token_0_mint.to_account_info(),
+        ctx.accounts.authority.key(),
+        &rent_free_accounts,
+        &[
+            POOL_VAULT_SEED.as_bytes(),
+            pool_state_key.as_ref(),
+            ctx.accounts.token_0_mint.key().as_ref(),
+            &[ctx.bumps.token_0_vault],
+        ],
+    )?;
 
     // Create token_1 vault
-    CreateTokenAccountCpi {
-        payer: ctx.accounts.creator.to_account_info(),
-        account: ctx.accounts.token_1_vault.to_account_info(),
-        mint: ctx.accounts.token_1_mint.to_account_info(),
-        owner: ctx.accounts.authority.key(),
-    }
-    .rent_free(
-        ctx.accounts.light_token_compressible_config.to_account_info(),
-        ctx.accounts.light_token_rent_sponsor.to_account_info(),
-        ctx.accounts.system_program.to_account_info(),
-        &crate::ID,
-    )
-    .invoke_signed(&[
-        POOL_VAULT_SEED.as_bytes(),
-        pool_state_key.as_ref(),
-        ctx.accounts.token_1_mint.key().as_ref(),
-        &[ctx.bumps.token_1_vault],
-    ])?;
+    create_pool_vault(
+        ctx.accounts.creator.to_account_info(),
+        ctx.accounts.token_1_vault.to_account_info(),
+        ctx.accounts.token_1_mint.to_account_info(),
+        ctx.accounts.authority.key(),
+        &rent_free_accounts,
+        &[
+            POOL_VAULT_SEED.as_bytes(),
+            pool_state_key.as_ref(),
+            ctx.accounts.token_1_mint.key().as_ref(),
+            &[ctx.bumps.token_1_vault],
+        ],
+    )?;
 
     // Transfer tokens from creator to vaults
     transfer_from_user_to_pool_vault(
@@ -292,11 +344,7 @@ pub fn initialize<'info>(
         )?;
     }
 
-    let liquidity = U128::from(token_0_vault_balance)
-        .checked_mul(token_1_vault_balance.into())
-        .unwrap()
-        .integer_sqrt()
-        .as_u64();
+    let liquidity = initial_liquidity(token_0_vault_balance, token_1_vault_balance);
     let lock_lp_amount = 100;
 
     let user_lp_amount = liquidity
@@ -306,7 +354,14 @@ pub fn initialize<'info>(
     let pool_state = &mut ctx.accounts.pool_state;
     let observation_state = &mut ctx.accounts.observation_state;
     let observation_state_key = observation_state.key();
-    observation_state.pool_id = pool_state_key;
+    observation_state.initialize(
+        pool_state_key,
+        if params.observation_update_duration == 0 {
+            OBSERVATION_UPDATE_DURATION_DEFAULT
+        } else {
+            params.observation_update_duration
+        },
+    );
 
     pool_state.initialize(
         ctx.bumps.authority,
@@ -320,7 +375,17 @@ pub fn initialize<'info>(
         &ctx.accounts.token_1_mint,
         &ctx.accounts.lp_mint,
         observation_state_key,
+        &curve,
     );
+    pool_state.reserve_mode = params.reserve_mode;
+    pool_state.token_0_reserve = token_0_vault_balance;
+    pool_state.token_1_reserve = token_1_vault_balance;
+    if params.lbp_end_time != 0 {
+        pool_state.lbp_start_time = params.lbp_start_time;
+        pool_state.lbp_end_time = params.lbp_end_time;
+        pool_state.lbp_start_weight_0 = token_0_weight;
+        pool_state.lbp_end_weight_0 = params.lbp_end_token_0_weight;
+    }
 
     // Create creator LP token ATA
     CreateTokenAtaCpi {
@@ -351,3 +416,44 @@ pub fn initialize<'info>(
 
     Ok(())
 }
+
+/// Accounts sponsoring the rent of rent free token accounts
+pub(crate) struct RentFreeAccounts<'info> {
+    pub light_token_compressible_config: AccountInfo<'info>,
+    pub light_token_rent_sponsor: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+}
+
+/// Creates a rent-free pool vault owned by the pool authority
+pub(crate) fn create_pool_vault<'info>(
+    payer: AccountInfo<'info>,
+    vault: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    authority: Pubkey,
+    rent_free_accounts: &RentFreeAccounts<'info>,
+    vault_seeds: &[&[u8]],
+) -> Result<()> {
+    CreateTokenAccountCpi {
+        payer,
+        account: vault,
+        mint,
+        owner: authority,
+    }
+    .rent_free(
+        rent_free_accounts.light_token_compressible_config.clone(),
+        rent_free_accounts.light_token_rent_sponsor.clone(),
+        rent_free_accounts.system_program.clone(),
+        &crate::ID,
+    )
+    .invoke_signed(vault_seeds)?;
+    Ok(())
+}
+
+/// LP supply of a new pool, the geometric mean of its reserves
+pub(crate) fn initial_liquidity(token_0_vault_balance: u64, token_1_vault_balance: u64) -> u64 {
+    U128::from(token_0_vault_balance)
+        .checked_mul(token_1_vault_balance.into())
+        .unwrap()
+        .integer_sqrt()
+        .as_u64()
+}
@@ -0,0 +1,184 @@
+use crate::error::ErrorCode;
+use crate::instructions::initialize::{create_pool_vault, RentFreeAccounts};
+use crate::states::*;
+use crate::utils::*;
+use anchor_lang::prelude::*;
+use light_anchor_spl::token_interface::{Mint, TokenAccount};
+use light_sdk::interface::CreateAccountsProof;
+use light_token::anchor::LightAccounts;
+use light_token::instruction::{COMPRESSIBLE_CONFIG_V1, RENT_SPONSOR as LIGHT_TOKEN_RENT_SPONSOR};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CreateLaunchPoolParams {
+    /// Quote amount the curve prices with on top of the quote paid in
+    pub virtual_quote_reserve: u64,
+    /// Market cap in quote tokens at which the launch migrates
+    pub migration_market_cap: u64,
+    pub create_accounts_proof: CreateAccountsProof,
+}
+
+#[derive(Accounts, LightAccounts)]
+#[instruction(params: CreateLaunchPoolParams)]
+pub struct CreateLaunchPool<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// CHECK: launch pool vault authority
+    #[account(
+        seeds = [crate::AUTH_SEED.as_bytes()],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        seeds = [
+            LAUNCH_POOL_SEED.as_bytes(),
+            amm_config.key().as_ref(),
+            token_mint.key().as_ref(),
+        ],
+        bump,
+        payer = creator,
+        space = 8 + LaunchPool::INIT_SPACE
+    )]
+    #[light_account(init)]
+    pub launch_pool: Box<Account<'info, LaunchPool>>,
+
+    /// The token to launch, its whole supply goes on sale
+    #[account(constraint = token_mint.key() != quote_mint.key())]
+    pub token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The token buyers pay with
+    pub quote_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = creator,
+    )]
+    pub creator_token: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [
+            LAUNCH_POOL_VAULT_SEED.as_bytes(),
+            launch_pool.key().as_ref(),
+            token_mint.key().as_ref()
+        ],
+        bump,
+    )]
+    #[light_account(token, authority = [crate::AUTH_SEED.as_bytes()])]
+    pub token_vault: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            LAUNCH_POOL_VAULT_SEED.as_bytes(),
+            launch_pool.key().as_ref(),
+            quote_mint.key().as_ref()
+        ],
+        bump,
+    )]
+    #[light_account(token, authority = [crate::AUTH_SEED.as_bytes()])]
+    pub quote_vault: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    pub compression_config: AccountInfo<'info>,
+
+    #[account(address = COMPRESSIBLE_CONFIG_V1)]
+    pub light_token_compressible_config: AccountInfo<'info>,
+
+    #[account(mut, address = LIGHT_TOKEN_RENT_SPONSOR)]
+    pub light_token_rent_sponsor: AccountInfo<'info>,
+
+    pub light_token_program: AccountInfo<'info>,
+
+    /// CHECK: light-token CPI authority.
+    pub light_token_cpi_authority: AccountInfo<'info>,
+}
+
+pub fn create_launch_pool<'info>(
+    ctx: Context<'_, '_, '_, 'info, CreateLaunchPool<'info>>,
+    params: CreateLaunchPoolParams,
+) -> Result<()> {
+    if !(is_supported_mint(&ctx.accounts.token_mint)?
+        && is_supported_mint(&ctx.accounts.quote_mint)?)
+    {
+        return err!(ErrorCode::NotSupportMint);
+    }
+    if ctx.accounts.amm_config.disable_create_pool {
+        return err!(ErrorCode::NotApproved);
+    }
+    // Tokens held outside the curve could be sold against the virtual reserve
+    require!(
+        ctx.accounts.token_mint.mint_authority.is_none()
+            && ctx.accounts.creator_token.amount == ctx.accounts.token_mint.supply,
+        ErrorCode::InvalidInput
+    );
+    require_gt!(params.virtual_quote_reserve, 0);
+    require_gt!(params.migration_market_cap, 0);
+
+    let launch_pool_key = ctx.accounts.launch_pool.key();
+    let rent_free_accounts = RentFreeAccounts {
+        light_token_compressible_config: ctx
+            .accounts
+            .light_token_compressible_config
+            .to_account_info(),
+        light_token_rent_sponsor: ctx.accounts.light_token_rent_sponsor.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+    };
+    create_pool_vault(
+        ctx.accounts.creator.to_account_info(),
+        ctx.accounts.token_vault.to_account_info(),
+        ctx.accounts.token_mint.to_account_info(),
+        ctx.accounts.authority.key(),
+        &rent_free_accounts,
+        &[
+            LAUNCH_POOL_VAULT_SEED.as_bytes(),
+            launch_pool_key.as_ref(),
+            ctx.accounts.token_mint.key().as_ref(),
+            &[ctx.bumps.token_vault],
+        ],
+    )?;
+    create_pool_vault(
+        ctx.accounts.creator.to_account_info(),
+        ctx.accounts.quote_vault.to_account_info(),
+        ctx.accounts.quote_mint.to_account_info(),
+        ctx.accounts.authority.key(),
+        &rent_free_accounts,
+        &[
+            LAUNCH_POOL_VAULT_SEED.as_bytes(),
+            launch_pool_key.as_ref(),
+            ctx.accounts.quote_mint.key().as_ref(),
+            &[ctx.bumps.quote_vault],
+        ],
+    )?;
+
+    transfer_from_user_to_pool_vault(
+        ctx.accounts.creator.to_account_info(),
+        ctx.accounts.creator_token.to_account_info(),
+        ctx.accounts.token_vault.to_account_info(),
+        ctx.accounts.token_mint.to_account_info(),
+        ctx.accounts.light_token_program.to_account_info(),
+        ctx.accounts.creator_token.amount,
+        ctx.accounts.creator.to_account_info(),
+        ctx.accounts.light_token_cpi_authority.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    )?;
+
+    let launch_pool = &mut ctx.accounts.launch_pool;
+    launch_pool.creator = ctx.accounts.creator.key();
+    launch_pool.amm_config = ctx.accounts.amm_config.key();
+    launch_pool.token_mint = ctx.accounts.token_mint.key();
+    launch_pool.quote_mint = ctx.accounts.quote_mint.key();
+    launch_pool.token_vault = ctx.accounts.token_vault.key();
+    launch_pool.quote_vault = ctx.accounts.quote_vault.key();
+    launch_pool.virtual_quote_reserve = params.virtual_quote_reserve;
+    launch_pool.migration_market_cap = params.migration_market_cap;
+    launch_pool.pool_id = Pubkey::default();
+    launch_pool.padding = [0u64; 4];
+    Ok(())
+}
@@ -0,0 +1,286 @@
+use crate::curve::{CurveCalculator, SwapCurve};
+use crate::error::ErrorCode;
+use crate::instructions::initialize::{
+    create_pool_vault, initial_liquidity, RentFreeAccounts, LP_MINT_SIGNER_SEED,
+};
+use crate::states::*;
+use crate::utils::*;
+use anchor_lang::prelude::*;
+use light_anchor_spl::token_interface::{Mint, TokenAccount};
+use light_sdk::interface::CreateAccountsProof;
+use light_token::anchor::LightAccounts;
+use light_token::{
+    instruction::{COMPRESSIBLE_CONFIG_V1, RENT_SPONSOR as LIGHT_TOKEN_RENT_SPONSOR},
+    utils::get_token_account_balance,
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct MigrateLaunchPoolParams {
+    pub create_accounts_proof: CreateAccountsProof,
+    pub lp_mint_signer_bump: u8,
+    pub authority_bump: u8,
+}
+
+#[derive(Accounts, LightAccounts)]
+#[instruction(params: MigrateLaunchPoolParams)]
+pub struct MigrateLaunchPool<'info> {
+    /// Anyone can migrate a launch that reached its market cap
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(address = launch_pool.amm_config)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    #[account(
+        mut,
+        seeds = [crate::AUTH_SEED.as_bytes()],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub launch_pool: Box<Account<'info, LaunchPool>>,
+
+    /// Seeded by the launch rather than by the pair, so a pool opened through
+    /// `initialize` for the same config and mints can't block the migration
+    #[account(
+        init,
+        seeds = [
+            POOL_SEED.as_bytes(),
+            launch_pool.key().as_ref(),
+        ],
+        bump,
+        payer = payer,
+        space = 8 + PoolState::INIT_SPACE
+    )]
+    #[light_account(init)]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    #[account(
+        constraint = token_0_mint.key() < token_1_mint.key(),
+        constraint = token_0_mint.key() == launch_pool.token_mint
+            || token_0_mint.key() == launch_pool.quote_mint,
+    )]
+    pub token_0_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        constraint = token_1_mint.key() == launch_pool.token_mint
+            || token_1_mint.key() == launch_pool.quote_mint,
+    )]
+    pub token_1_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        seeds = [LP_MINT_SIGNER_SEED, pool_state.key().as_ref()],
+        bump,
+    )]
+    pub lp_mint_signer: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    #[light_account(init, mint,
+        mint_signer = lp_mint_signer,
+        authority = authority,
+        decimals = 9,
+        mint_seeds = &[LP_MINT_SIGNER_SEED, self.pool_state.to_account_info().key.as_ref(), &[params.lp_mint_signer_bump]],
+        authority_seeds = &[crate::AUTH_SEED.as_bytes(), &[params.authority_bump]]
+    )]
+    pub lp_mint: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            POOL_VAULT_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            token_0_mint.key().as_ref()
+        ],
+        bump,
+    )]
+    #[light_account(token, authority = [crate::AUTH_SEED.as_bytes()])]
+    pub pool_token_0_vault: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            POOL_VAULT_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            token_1_mint.key().as_ref()
+        ],
+        bump,
+    )]
+    #[light_account(token, authority = [crate::AUTH_SEED.as_bytes()])]
+    pub pool_token_1_vault: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        seeds = [OBSERVATION_SEED.as_bytes(), pool_state.key().as_ref()],
+        bump,
+        payer = payer,
+        space = 8 + ObservationState::space(OBSERVATION_CARDINALITY_DEFAULT)
+    )]
+    #[light_account(init)]
+    pub observation_state: Box<Account<'info, ObservationState>>,
+
+    /// The vault of the launched token
+    #[account(mut, address = launch_pool.token_vault)]
+    pub launch_token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The vault of the quote token
+    #[account(mut, address = launch_pool.quote_vault)]
+    pub launch_quote_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+
+    pub compression_config: AccountInfo<'info>,
+
+    #[account(address = COMPRESSIBLE_CONFIG_V1)]
+    pub light_token_compressible_config: AccountInfo<'info>,
+
+    #[account(mut, address = LIGHT_TOKEN_RENT_SPONSOR)]
+    pub light_token_rent_sponsor: AccountInfo<'info>,
+
+    pub light_token_program: AccountInfo<'info>,
+
+    /// CHECK: light-token CPI authority.
+    pub light_token_cpi_authority: AccountInfo<'info>,
+}
+
+/// Moves the reserves of a launch that reached its market cap into a
+/// standard constant product pool. The pool's LP tokens are never minted, so
+/// the migrated liquidity stays locked for good.
+pub fn migrate_launch_pool<'info>(
+    ctx: Context<'_, '_, '_, 'info, MigrateLaunchPool<'info>>,
+    _params: MigrateLaunchPoolParams,
+) -> Result<()> {
+    let launch_pool = &ctx.accounts.launch_pool;
+    let (token_mint, quote_mint) = if ctx.accounts.token_0_mint.key() == launch_pool.token_mint {
+        (&ctx.accounts.token_0_mint, &ctx.accounts.token_1_mint)
+    } else {
+        (&ctx.accounts.token_1_mint, &ctx.accounts.token_0_mint)
+    };
+    require_keys_eq!(quote_mint.key(), launch_pool.quote_mint);
+    let token_vault_amount = ctx.accounts.launch_token_vault.amount;
+    let quote_vault_amount = ctx.accounts.launch_quote_vault.amount;
+    if launch_pool.is_migrated()
+        || !launch_pool.migration_reached(token_vault_amount, quote_vault_amount, token_mint.supply)
+    {
+        return err!(ErrorCode::NotApproved);
+    }
+
+    // The pool opens at the last price of the curve, the tokens that don't fit
+    // next to the paid in quote stay locked in the launch vault
+    let quote_amount = quote_vault_amount;
+    let token_amount = u64::try_from(
+        u128::from(token_vault_amount) * u128::from(quote_amount)
+            / u128::from(launch_pool.quote_reserve(quote_vault_amount)),
+    )
+    .unwrap();
+    let (amount_0, amount_1) = if token_mint.key() == ctx.accounts.token_0_mint.key() {
+        (token_amount, quote_amount)
+    } else {
+        (quote_amount, token_amount)
+    };
+    let (launch_vault_0, launch_vault_1) = if token_mint.key() == ctx.accounts.token_0_mint.key() {
+        (
+            &ctx.accounts.launch_token_vault,
+            &ctx.accounts.launch_quote_vault,
+        )
+    } else {
+        (
+            &ctx.accounts.launch_quote_vault,
+            &ctx.accounts.launch_token_vault,
+        )
+    };
+
+    let pool_state_key = ctx.accounts.pool_state.key();
+    let rent_free_accounts = RentFreeAccounts {
+        light_token_compressible_config: ctx
+            .accounts
+            .light_token_compressible_config
+            .to_account_info(),
+        light_token_rent_sponsor: ctx.accounts.light_token_rent_sponsor.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+    };
+    create_pool_vault(
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.pool_token_0_vault.to_account_info(),
+        ctx.accounts.token_0_mint.to_account_info(),
+        ctx.accounts.authority.key(),
+        &rent_free_accounts,
+        &[
+            POOL_VAULT_SEED.as_bytes(),
+            pool_state_key.as_ref(),
+            ctx.accounts.token_0_mint.key().as_ref(),
+            &[ctx.bumps.pool_token_0_vault],
+        ],
+    )?;
+    create_pool_vault(
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.pool_token_1_vault.to_account_info(),
+        ctx.accounts.token_1_mint.to_account_info(),
+        ctx.accounts.authority.key(),
+        &rent_free_accounts,
+        &[
+            POOL_VAULT_SEED.as_bytes(),
+            pool_state_key.as_ref(),
+            ctx.accounts.token_1_mint.key().as_ref(),
+            &[ctx.bumps.pool_token_1_vault],
+        ],
+    )?;
+
+    // Launch vaults and pool vaults share the authority
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        launch_vault_0.to_account_info(),
+        ctx.accounts.pool_token_0_vault.to_account_info(),
+        ctx.accounts.token_0_mint.to_account_info(),
+        ctx.accounts.light_token_program.to_account_info(),
+        amount_0,
+        &[&[crate::AUTH_SEED.as_bytes(), &[ctx.bumps.authority]]],
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.light_token_cpi_authority.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    )?;
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        launch_vault_1.to_account_info(),
+        ctx.accounts.pool_token_1_vault.to_account_info(),
+        ctx.accounts.token_1_mint.to_account_info(),
+        ctx.accounts.light_token_program.to_account_info(),
+        amount_1,
+        &[&[crate::AUTH_SEED.as_bytes(), &[ctx.bumps.authority]]],
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.light_token_cpi_authority.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    )?;
+
+    let token_0_vault_balance =
+        get_token_account_balance(&ctx.accounts.pool_token_0_vault.to_account_info())
+            .map_err(|_| ErrorCode::InvalidAccountData)?;
+    let token_1_vault_balance =
+        get_token_account_balance(&ctx.accounts.pool_token_1_vault.to_account_info())
+            .map_err(|_| ErrorCode::InvalidAccountData)?;
+    CurveCalculator::validate_supply(token_0_vault_balance, token_1_vault_balance)?;
+    let liquidity = initial_liquidity(token_0_vault_balance, token_1_vault_balance);
+
+    let observation_state = &mut ctx.accounts.observation_state;
+    let observation_state_key = observation_state.key();
+    observation_state.initialize(pool_state_key, OBSERVATION_UPDATE_DURATION_DEFAULT);
+
+    ctx.accounts.pool_state.initialize(
+        ctx.bumps.authority,
+        liquidity,
+        0,
+        ctx.accounts.launch_pool.creator,
+        ctx.accounts.amm_config.key(),
+        ctx.accounts.pool_token_0_vault.key(),
+        ctx.accounts.pool_token_1_vault.key(),
+        &ctx.accounts.token_0_mint,
+        &ctx.accounts.token_1_mint,
+        &ctx.accounts.lp_mint,
+        observation_state_key,
+        &SwapCurve::ConstantProduct,
+    );
+    ctx.accounts.pool_state.token_0_reserve = token_0_vault_balance;
+    ctx.accounts.pool_state.token_1_reserve = token_1_vault_balance;
+    ctx.accounts.launch_pool.pool_id = pool_state_key;
+    Ok(())
+}
@@ -0,0 +1,8 @@
+pub mod create_launch_pool;
+pub use create_launch_pool::*;
+
+pub mod swap_launch_pool;
+pub use swap_launch_pool::*;
+
+pub mod migrate_launch_pool;
+pub use migrate_launch_pool::*;
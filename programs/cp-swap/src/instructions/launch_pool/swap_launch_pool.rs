@@ -0,0 +1,179 @@
+use crate::curve::{CurveCalculator, FeeRates, SwapCurve, TradeDirection};
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::utils::token::*;
+use anchor_lang::prelude::*;
+use light_anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+pub struct SwapLaunchPool<'info> {
+    /// The user performing the swap
+    pub payer: Signer<'info>,
+
+    /// CHECK: launch pool vault authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// The config of the trade fee
+    #[account(address = launch_pool.amm_config)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    #[account(mut)]
+    pub launch_pool: Box<Account<'info, LaunchPool>>,
+
+    /// The user token account for input token
+    #[account(mut)]
+    pub input_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The user token account for output token
+    #[account(mut)]
+    pub output_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The vault of the launched token
+    #[account(mut, address = launch_pool.token_vault)]
+    pub token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The vault of the quote token
+    #[account(mut, address = launch_pool.quote_vault)]
+    pub quote_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of the launched token
+    #[account(address = launch_pool.token_mint)]
+    pub token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of the quote token
+    #[account(address = launch_pool.quote_mint)]
+    pub quote_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Light Token program for CPI
+    pub light_token_program: Interface<'info, TokenInterface>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: light_token CPI authority.
+    pub light_token_cpi_authority: AccountInfo<'info>,
+}
+
+pub fn swap_launch_pool(
+    ctx: Context<SwapLaunchPool>,
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> Result<()> {
+    let launch_pool = &ctx.accounts.launch_pool;
+    let token_vault_amount = ctx.accounts.token_vault.amount;
+    let quote_vault_amount = ctx.accounts.quote_vault.amount;
+    // Trading stops once the launch reached its market cap, until it migrates
+    if launch_pool.is_migrated()
+        || launch_pool.migration_reached(
+            token_vault_amount,
+            quote_vault_amount,
+            ctx.accounts.token_mint.supply,
+        )
+    {
+        return err!(ErrorCode::NotApproved);
+    }
+
+    // Paying with the quote token buys the launched token, anything else sells it
+    let buy = ctx.accounts.input_token_account.mint == launch_pool.quote_mint;
+    let (input_vault, output_vault, input_mint, output_mint) = if buy {
+        (
+            &ctx.accounts.quote_vault,
+            &ctx.accounts.token_vault,
+            &ctx.accounts.quote_mint,
+            &ctx.accounts.token_mint,
+        )
+    } else {
+        (
+            &ctx.accounts.token_vault,
+            &ctx.accounts.quote_vault,
+            &ctx.accounts.token_mint,
+            &ctx.accounts.quote_mint,
+        )
+    };
+    require_keys_eq!(ctx.accounts.input_token_account.mint, input_mint.key());
+    require_keys_eq!(ctx.accounts.output_token_account.mint, output_mint.key());
+    let quote_reserve = launch_pool.quote_reserve(quote_vault_amount);
+    let (swap_source_amount, swap_destination_amount) = if buy {
+        (quote_reserve, token_vault_amount)
+    } else {
+        (token_vault_amount, quote_reserve)
+    };
+
+    let input_transfer_fee = get_transfer_fee(&input_mint.to_account_info(), amount_in)?;
+    // Take transfer fees into account for actual amount transferred in
+    let actual_amount_in = amount_in.saturating_sub(input_transfer_fee);
+    require_gt!(actual_amount_in, 0);
+
+    // The whole trade fee stays in the curve
+    let result = CurveCalculator::swap_base_input(
+        &SwapCurve::ConstantProduct,
+        TradeDirection::ZeroForOne,
+        u128::from(actual_amount_in),
+        u128::from(swap_source_amount),
+        u128::from(swap_destination_amount),
+        &FeeRates {
+            trade_fee_rate: ctx.accounts.amm_config.trade_fee_rate,
+            ..Default::default()
+        },
+    )
+    .ok_or(ErrorCode::ZeroTradingTokens)?;
+    let amount_out = u64::try_from(result.destination_amount_swapped).unwrap();
+    // Sells can't pay out of the virtual reserve
+    require_gte!(
+        output_vault.amount,
+        amount_out,
+        ErrorCode::ZeroTradingTokens
+    );
+    let output_transfer_fee = get_transfer_fee(&output_mint.to_account_info(), amount_out)?;
+    let amount_received = amount_out.checked_sub(output_transfer_fee).unwrap();
+    require_gt!(amount_received, 0);
+    require_gte!(
+        amount_received,
+        minimum_amount_out,
+        ErrorCode::ExceededSlippage
+    );
+
+    emit!(SwapEvent {
+        pool_id: launch_pool.key(),
+        input_vault_before: swap_source_amount,
+        output_vault_before: swap_destination_amount,
+        input_amount: actual_amount_in,
+        output_amount: amount_out,
+        input_transfer_fee,
+        output_transfer_fee,
+        base_input: true,
+        trade_fee_rate: ctx.accounts.amm_config.trade_fee_rate,
+        referral_fee: 0,
+        unfilled_amount: 0,
+    });
+
+    transfer_from_user_to_pool_vault(
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.input_token_account.to_account_info(),
+        input_vault.to_account_info(),
+        input_mint.to_account_info(),
+        ctx.accounts.light_token_program.to_account_info(),
+        amount_in,
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.light_token_cpi_authority.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    )?;
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        output_vault.to_account_info(),
+        ctx.accounts.output_token_account.to_account_info(),
+        output_mint.to_account_info(),
+        ctx.accounts.light_token_program.to_account_info(),
+        amount_out,
+        &[&[crate::AUTH_SEED.as_bytes(), &[ctx.bumps.authority]]],
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.light_token_cpi_authority.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    )?;
+    Ok(())
+}
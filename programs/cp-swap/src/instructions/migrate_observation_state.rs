@@ -0,0 +1,55 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::{prelude::*, system_program, Discriminator};
+
+#[derive(Accounts)]
+pub struct MigrateObservationState<'info> {
+    /// Anyone can migrate an observation account, paying the extra rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: an observation account in the `ObservationStateV0` layout,
+    /// checked in the handler
+    #[account(mut, owner = crate::ID)]
+    pub observation_state: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Rewrites an observation account of the first layout into the current one.
+/// Pools whose observation account predates the log2 price, liquidity and
+/// volume accumulators can't trade until it is migrated. Only decompressed
+/// accounts can be migrated.
+pub fn migrate_observation_state(ctx: Context<MigrateObservationState>) -> Result<()> {
+    let account_info = ctx.accounts.observation_state.to_account_info();
+    let observation_state_v0 = {
+        let data = account_info.try_borrow_data()?;
+        require!(
+            data.len() == 8 + ObservationStateV0::INIT_SPACE
+                && data[..8] == *ObservationState::DISCRIMINATOR,
+            ErrorCode::InvalidAccountData
+        );
+        ObservationStateV0::deserialize(&mut &data[8..])?
+    };
+    let observation_state = observation_state_v0.migrate();
+
+    let space = 8 + ObservationState::space(OBSERVATION_CARDINALITY_DEFAULT);
+    let rent = Rent::get()?.minimum_balance(space);
+    let top_up = rent.saturating_sub(account_info.lamports());
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: account_info.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    account_info.resize(space)?;
+    let mut data = account_info.try_borrow_mut_data()?;
+    observation_state.try_serialize(&mut &mut data[..])?;
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::{prelude::*, system_program, Discriminator};
+
+#[derive(Accounts)]
+pub struct MigratePoolState<'info> {
+    /// Anyone can migrate a pool, paying the extra rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: a pool in the `PoolStateV0` layout, checked in the handler
+    #[account(mut, owner = crate::ID)]
+    pub pool_state: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Rewrites a pool of the first layout into the current one. Pools created
+/// before curves and weights can't be loaded until they are migrated. Only
+/// decompressed pools can be migrated.
+pub fn migrate_pool_state(ctx: Context<MigratePoolState>) -> Result<()> {
+    let account_info = ctx.accounts.pool_state.to_account_info();
+    let pool_state_v0 = {
+        let data = account_info.try_borrow_data()?;
+        require!(
+            data.len() == 8 + PoolStateV0::INIT_SPACE && data[..8] == *PoolState::DISCRIMINATOR,
+            ErrorCode::InvalidAccountData
+        );
+        PoolStateV0::deserialize(&mut &data[8..])?
+    };
+    let pool_state = pool_state_v0.migrate();
+
+    let space = 8 + PoolState::INIT_SPACE;
+    let rent = Rent::get()?.minimum_balance(space);
+    let top_up = rent.saturating_sub(account_info.lamports());
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: account_info.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    account_info.resize(space)?;
+    let mut data = account_info.try_borrow_mut_data()?;
+    pool_state.try_serialize(&mut &mut data[..])?;
+    Ok(())
+}
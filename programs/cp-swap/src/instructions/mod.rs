@@ -1,15 +1,58 @@
 pub mod deposit;
+pub mod deposit_single_token;
 pub mod initialize;
 pub mod swap_base_input;
 pub mod withdraw;
+pub mod withdraw_single_token;
 
 pub use deposit::*;
+pub use deposit_single_token::*;
 pub use initialize::*;
 pub use swap_base_input::*;
 pub use withdraw::*;
+pub use withdraw_single_token::*;
 
 pub mod admin;
 pub use admin::*;
 
 pub mod swap_base_output;
 pub use swap_base_output::*;
+
+pub mod swap_route;
+pub use swap_route::*;
+
+pub mod flash_swap;
+pub use flash_swap::*;
+
+pub mod collect_creator_fee;
+pub use collect_creator_fee::*;
+
+pub mod swap_with_price_limit;
+pub use swap_with_price_limit::*;
+
+pub mod early_access;
+pub use early_access::*;
+
+pub mod graduate;
+pub use graduate::*;
+
+pub mod launch_pool;
+pub use launch_pool::*;
+
+pub mod multi_pool;
+pub use multi_pool::*;
+
+pub mod skim;
+pub use skim::*;
+
+pub mod sync;
+pub use sync::*;
+
+pub mod increase_observation_cardinality;
+pub use increase_observation_cardinality::*;
+
+pub mod migrate_observation_state;
+pub use migrate_observation_state::*;
+
+pub mod migrate_pool_state;
+pub use migrate_pool_state::*;
@@ -0,0 +1,162 @@
+use super::swap_multi_pool::load_multi_pool_vaults;
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::utils::token::*;
+use anchor_lang::prelude::*;
+use light_anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use light_token::instruction::MintToCpi;
+
+#[derive(Accounts)]
+pub struct DepositMultiPool<'info> {
+    /// Pays to mint the position
+    pub owner: Signer<'info>,
+
+    /// CHECK: pool vault and lp mint authority
+    #[account(
+        mut,
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub multi_pool_state: Box<Account<'info, MultiPoolState>>,
+
+    /// The program account for the most recent oracle observation
+    #[account(mut, address = multi_pool_state.observation_key)]
+    pub multi_observation_state: Box<Account<'info, MultiObservationState>>,
+
+    /// Owner lp token account
+    #[account(mut, token::authority = owner)]
+    pub owner_lp_token: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Lp mint
+    #[account(
+        mut,
+        address = multi_pool_state.lp_mint @ ErrorCode::IncorrectLpMint
+    )]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: Light Token program for CPI.
+    pub light_token_program: Interface<'info, TokenInterface>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: light-token CPI authority.
+    pub light_token_cpi_authority: AccountInfo<'info>,
+}
+
+/// Pool vaults, their mints and the user token accounts of a multi-token
+/// pool, from remaining accounts laid out as all vaults, then all mints,
+/// then all user token accounts, each in pool order
+pub(crate) struct MultiPoolTokenAccounts<'info> {
+    pub vaults: Vec<InterfaceAccount<'info, TokenAccount>>,
+    pub mints: &'info [AccountInfo<'info>],
+    pub user_token_accounts: &'info [AccountInfo<'info>],
+}
+
+impl<'info> MultiPoolTokenAccounts<'info> {
+    pub fn load(
+        pool_state: &MultiPoolState,
+        accounts: &'info [AccountInfo<'info>],
+    ) -> Result<Self> {
+        let token_count = pool_state.token_count();
+        require_eq!(
+            accounts.len(),
+            3 * token_count,
+            ErrorCode::InvalidTokenCount
+        );
+        let vaults = load_multi_pool_vaults(pool_state, accounts)?;
+        let mints = &accounts[token_count..2 * token_count];
+        let user_token_accounts = &accounts[2 * token_count..];
+        for ((vault, mint), user_token_account) in vaults.iter().zip(mints).zip(user_token_accounts)
+        {
+            require_keys_eq!(mint.key(), vault.mint);
+            let user_token_account =
+                InterfaceAccount::<TokenAccount>::try_from(user_token_account)?;
+            require_keys_eq!(user_token_account.mint, vault.mint);
+        }
+        Ok(Self {
+            vaults,
+            mints,
+            user_token_accounts,
+        })
+    }
+
+    pub fn vault_amounts(&self) -> Vec<u64> {
+        self.vaults.iter().map(|vault| vault.amount).collect()
+    }
+}
+
+/// Deposits every token of a multi-token pool in proportion to its reserves
+/// and mints `lp_token_amount`. See `MultiPoolTokenAccounts` for the
+/// remaining accounts.
+pub fn deposit_multi_pool<'info>(
+    ctx: Context<'_, '_, 'info, 'info, DepositMultiPool<'info>>,
+    lp_token_amount: u64,
+    maximum_token_amounts: Vec<u64>,
+) -> Result<()> {
+    require_gt!(lp_token_amount, 0);
+    let pool_state = &ctx.accounts.multi_pool_state;
+    if !pool_state.get_status_by_bit(PoolStatusBitIndex::Deposit) {
+        return err!(ErrorCode::NotApproved);
+    }
+    let token_accounts = MultiPoolTokenAccounts::load(pool_state, ctx.remaining_accounts)?;
+    require_eq!(
+        maximum_token_amounts.len(),
+        pool_state.token_count(),
+        ErrorCode::InvalidTokenCount
+    );
+    // Fees owed to the protocol and fund are no liquidity
+    let vault_amounts = pool_state.vault_amounts_without_fee(&token_accounts.vault_amounts());
+
+    for (i, vault) in token_accounts.vaults.iter().enumerate() {
+        // Round up so the deposit never dilutes the other liquidity providers
+        let token_amount = u64::try_from(
+            (u128::from(lp_token_amount) * u128::from(vault_amounts[i]))
+                .div_ceil(u128::from(pool_state.lp_supply)),
+        )
+        .map_err(|_| ErrorCode::ZeroTradingTokens)?;
+        require_gt!(token_amount, 0, ErrorCode::ZeroTradingTokens);
+        let transfer_fee = get_transfer_inverse_fee(&token_accounts.mints[i], token_amount)?;
+        let transfer_amount = token_amount.checked_add(transfer_fee).unwrap();
+        require_gte!(
+            maximum_token_amounts[i],
+            transfer_amount,
+            ErrorCode::ExceededSlippage
+        );
+        transfer_from_user_to_pool_vault(
+            ctx.accounts.owner.to_account_info(),
+            token_accounts.user_token_accounts[i].clone(),
+            vault.to_account_info(),
+            token_accounts.mints[i].clone(),
+            ctx.accounts.light_token_program.to_account_info(),
+            transfer_amount,
+            ctx.accounts.owner.to_account_info(),
+            ctx.accounts.light_token_cpi_authority.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        )?;
+    }
+
+    MintToCpi {
+        mint: ctx.accounts.lp_mint.to_account_info(),
+        destination: ctx.accounts.owner_lp_token.to_account_info(),
+        amount: lp_token_amount,
+        authority: ctx.accounts.authority.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+        max_top_up: None,
+    }
+    .invoke_signed(&[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]])?;
+
+    // update the previous price to the observation
+    let prices_x32 = pool_state.token_prices_x32(&vault_amounts);
+    ctx.accounts
+        .multi_observation_state
+        .update(oracle::block_timestamp(), &prices_x32);
+    let pool_state = &mut ctx.accounts.multi_pool_state;
+    pool_state.lp_supply = pool_state.lp_supply.checked_add(lp_token_amount).unwrap();
+    pool_state.recent_epoch = Clock::get()?.epoch;
+    Ok(())
+}
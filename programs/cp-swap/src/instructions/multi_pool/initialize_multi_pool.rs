@@ -0,0 +1,305 @@
+use crate::curve::fees::FEE_RATE_DENOMINATOR_VALUE;
+use crate::error::ErrorCode;
+use crate::instructions::initialize::{create_pool_vault, RentFreeAccounts, LP_MINT_SIGNER_SEED};
+use crate::states::*;
+use crate::utils::*;
+use anchor_lang::{
+    prelude::*,
+    solana_program::{clock, program::invoke, system_instruction},
+};
+use light_anchor_spl::{
+    token::spl_token,
+    token::Token,
+    token_interface::{Mint, TokenAccount},
+};
+use light_sdk::interface::CreateAccountsProof;
+use light_token::anchor::LightAccounts;
+use light_token::{
+    instruction::{
+        CreateTokenAtaCpi, MintToCpi, COMPRESSIBLE_CONFIG_V1,
+        RENT_SPONSOR as LIGHT_TOKEN_RENT_SPONSOR,
+    },
+    utils::get_token_account_balance,
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct InitializeMultiPoolParams {
+    /// Initial amount of each token, in the order of the mints
+    pub init_amounts: Vec<u64>,
+    /// Trade fee rate of each token, in the order of the mints
+    pub trade_fee_rates: Vec<u64>,
+    pub open_time: u64,
+    pub create_accounts_proof: CreateAccountsProof,
+    pub lp_mint_signer_bump: u8,
+    pub creator_lp_token_bump: u8,
+    pub authority_bump: u8,
+}
+
+#[derive(Accounts, LightAccounts)]
+#[instruction(params: InitializeMultiPoolParams)]
+pub struct InitializeMultiPool<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    #[account(
+        mut,
+        seeds = [crate::AUTH_SEED.as_bytes()],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// Any fresh key, tells apart pools of the same config
+    pub pool_base: Signer<'info>,
+
+    #[account(
+        init,
+        seeds = [
+            MULTI_POOL_SEED.as_bytes(),
+            amm_config.key().as_ref(),
+            pool_base.key().as_ref(),
+        ],
+        bump,
+        payer = creator,
+        space = 8 + MultiPoolState::INIT_SPACE
+    )]
+    #[light_account(init)]
+    pub multi_pool_state: Box<Account<'info, MultiPoolState>>,
+
+    #[account(
+        seeds = [LP_MINT_SIGNER_SEED, multi_pool_state.key().as_ref()],
+        bump,
+    )]
+    pub lp_mint_signer: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    #[light_account(init, mint,
+        mint_signer = lp_mint_signer,
+        authority = authority,
+        decimals = 9,
+        mint_seeds = &[LP_MINT_SIGNER_SEED, self.multi_pool_state.to_account_info().key.as_ref(), &[params.lp_mint_signer_bump]],
+        authority_seeds = &[crate::AUTH_SEED.as_bytes(), &[params.authority_bump]]
+    )]
+    pub lp_mint: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub creator_lp_token: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        seeds = [OBSERVATION_SEED.as_bytes(), multi_pool_state.key().as_ref()],
+        bump,
+        payer = creator,
+        space = 8 + MultiObservationState::INIT_SPACE
+    )]
+    #[light_account(init)]
+    pub multi_observation_state: Box<Account<'info, MultiObservationState>>,
+
+    #[account(mut, address = crate::create_pool_fee_receiver::ID)]
+    pub create_pool_fee: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    pub compression_config: AccountInfo<'info>,
+
+    #[account(address = COMPRESSIBLE_CONFIG_V1)]
+    pub light_token_compressible_config: AccountInfo<'info>,
+
+    #[account(mut, address = LIGHT_TOKEN_RENT_SPONSOR)]
+    pub light_token_rent_sponsor: AccountInfo<'info>,
+
+    pub light_token_program: AccountInfo<'info>,
+
+    /// CHECK: light-token CPI authority.
+    pub light_token_cpi_authority: AccountInfo<'info>,
+}
+
+/// Creates a pool of three or more tokens. The remaining accounts are
+/// `[mint, vault, creator token account]` for each token, mints in ascending
+/// order. Vaults use the `POOL_VAULT_SEED` seeds of two token pools.
+pub fn initialize_multi_pool<'info>(
+    ctx: Context<'_, '_, 'info, 'info, InitializeMultiPool<'info>>,
+    params: InitializeMultiPoolParams,
+) -> Result<()> {
+    let token_accounts = ctx.remaining_accounts;
+    let token_count = token_accounts.len() / 3;
+    if !token_accounts.len().is_multiple_of(3)
+        || !(MULTI_POOL_MIN_TOKENS..=MULTI_POOL_MAX_TOKENS).contains(&token_count)
+        || params.init_amounts.len() != token_count
+        || params.trade_fee_rates.len() != token_count
+    {
+        return err!(ErrorCode::InvalidTokenCount);
+    }
+    if params
+        .trade_fee_rates
+        .iter()
+        .any(|rate| *rate >= FEE_RATE_DENOMINATOR_VALUE)
+    {
+        return err!(ErrorCode::InvalidInput);
+    }
+    if ctx.accounts.amm_config.disable_create_pool {
+        return err!(ErrorCode::NotApproved);
+    }
+
+    let mut open_time = params.open_time;
+    let block_timestamp = clock::Clock::get()?.unix_timestamp as u64;
+    // open_time=0 means immediately open (no bump)
+    if open_time != 0 && open_time <= block_timestamp {
+        open_time = block_timestamp + 1;
+    }
+
+    let pool_state_key = ctx.accounts.multi_pool_state.key();
+    let rent_free_accounts = RentFreeAccounts {
+        light_token_compressible_config: ctx
+            .accounts
+            .light_token_compressible_config
+            .to_account_info(),
+        light_token_rent_sponsor: ctx.accounts.light_token_rent_sponsor.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+    };
+    let mut vaults = [Pubkey::default(); MULTI_POOL_MAX_TOKENS];
+    let mut mint_decimals = [0u8; MULTI_POOL_MAX_TOKENS];
+    let mut trade_fee_rates = [0u64; MULTI_POOL_MAX_TOKENS];
+    let mut vault_amounts = Vec::with_capacity(token_count);
+    let mut last_mint = Pubkey::default();
+    for (i, accounts) in token_accounts.chunks(3).enumerate() {
+        let (mint_info, vault, creator_token) = (&accounts[0], &accounts[1], &accounts[2]);
+        // Ascending mints rule out duplicate tokens
+        require!(
+            i == 0 || mint_info.key() > last_mint,
+            ErrorCode::InvalidTokenCount
+        );
+        last_mint = mint_info.key();
+        let mint = InterfaceAccount::<Mint>::try_from(mint_info)?;
+        if !is_supported_mint(&mint)? {
+            return err!(ErrorCode::NotSupportMint);
+        }
+
+        let (vault_key, vault_bump) = Pubkey::find_program_address(
+            &[
+                POOL_VAULT_SEED.as_bytes(),
+                pool_state_key.as_ref(),
+                mint_info.key.as_ref(),
+            ],
+            &crate::ID,
+        );
+        require_keys_eq!(vault.key(), vault_key, ErrorCode::InvalidVault);
+        create_pool_vault(
+            ctx.accounts.creator.to_account_info(),
+            vault.clone(),
+            mint_info.clone(),
+            ctx.accounts.authority.key(),
+            &rent_free_accounts,
+            &[
+                POOL_VAULT_SEED.as_bytes(),
+                pool_state_key.as_ref(),
+                mint_info.key.as_ref(),
+                &[vault_bump],
+            ],
+        )?;
+        transfer_from_user_to_pool_vault(
+            ctx.accounts.creator.to_account_info(),
+            creator_token.clone(),
+            vault.clone(),
+            mint_info.clone(),
+            ctx.accounts.light_token_program.to_account_info(),
+            params.init_amounts[i],
+            ctx.accounts.creator.to_account_info(),
+            ctx.accounts.light_token_cpi_authority.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        )?;
+        let vault_amount =
+            get_token_account_balance(vault).map_err(|_| ErrorCode::InvalidAccountData)?;
+        require_gt!(vault_amount, 0, ErrorCode::EmptySupply);
+
+        vaults[i] = vault_key;
+        mint_decimals[i] = mint.decimals;
+        trade_fee_rates[i] = params.trade_fee_rates[i];
+        vault_amounts.push(vault_amount);
+    }
+
+    // Charge the fee to create a pool
+    if ctx.accounts.amm_config.create_pool_fee != 0 {
+        invoke(
+            &system_instruction::transfer(
+                ctx.accounts.creator.key,
+                &ctx.accounts.create_pool_fee.key(),
+                ctx.accounts.amm_config.create_pool_fee,
+            ),
+            &[
+                ctx.accounts.creator.to_account_info(),
+                ctx.accounts.create_pool_fee.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+        invoke(
+            &spl_token::instruction::sync_native(
+                ctx.accounts.token_program.key,
+                &ctx.accounts.create_pool_fee.key(),
+            )?,
+            &[
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.create_pool_fee.to_account_info(),
+            ],
+        )?;
+    }
+
+    let liquidity =
+        multi_pool_initial_liquidity(&vault_amounts).ok_or(ErrorCode::InitLpAmountTooLess)?;
+    let lock_lp_amount = 100;
+    let user_lp_amount = liquidity
+        .checked_sub(lock_lp_amount)
+        .ok_or(ErrorCode::InitLpAmountTooLess)?;
+
+    let observation_state = &mut ctx.accounts.multi_observation_state;
+    observation_state.initialize(pool_state_key);
+    let observation_state_key = observation_state.key();
+
+    let pool_state = &mut ctx.accounts.multi_pool_state;
+    pool_state.amm_config = ctx.accounts.amm_config.key();
+    pool_state.pool_creator = ctx.accounts.creator.key();
+    pool_state.lp_mint = ctx.accounts.lp_mint.key();
+    pool_state.observation_key = observation_state_key;
+    pool_state.auth_bump = ctx.bumps.authority;
+    pool_state.token_count = token_count as u8;
+    pool_state.lp_mint_decimals = 9;
+    pool_state.vaults = vaults;
+    pool_state.mint_decimals = mint_decimals;
+    pool_state.trade_fee_rates = trade_fee_rates;
+    pool_state.lp_supply = liquidity;
+    pool_state.open_time = open_time;
+    pool_state.recent_epoch = Clock::get()?.epoch;
+
+    // Create creator LP token ATA
+    CreateTokenAtaCpi {
+        payer: ctx.accounts.creator.to_account_info(),
+        owner: ctx.accounts.creator.to_account_info(),
+        mint: ctx.accounts.lp_mint.to_account_info(),
+        ata: ctx.accounts.creator_lp_token.to_account_info(),
+        bump: params.creator_lp_token_bump,
+    }
+    .idempotent()
+    .rent_free(
+        ctx.accounts
+            .light_token_compressible_config
+            .to_account_info(),
+        ctx.accounts.light_token_rent_sponsor.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    )
+    .invoke()?;
+
+    // Mint LP tokens to creator
+    MintToCpi {
+        mint: ctx.accounts.lp_mint.to_account_info(),
+        destination: ctx.accounts.creator_lp_token.to_account_info(),
+        amount: user_lp_amount,
+        authority: ctx.accounts.authority.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+        max_top_up: None,
+    }
+    .invoke_signed(&[&[crate::AUTH_SEED.as_bytes(), &[ctx.bumps.authority]]])?;
+
+    Ok(())
+}
@@ -0,0 +1,11 @@
+pub mod initialize_multi_pool;
+pub use initialize_multi_pool::*;
+
+pub mod swap_multi_pool;
+pub use swap_multi_pool::*;
+
+pub mod deposit_multi_pool;
+pub use deposit_multi_pool::*;
+
+pub mod withdraw_multi_pool;
+pub use withdraw_multi_pool::*;
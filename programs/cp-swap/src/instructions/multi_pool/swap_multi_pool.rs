@@ -0,0 +1,193 @@
+use crate::curve::{CurveCalculator, FeeRates, SwapCurve, TradeDirection};
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::utils::token::*;
+use anchor_lang::prelude::*;
+use light_anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+pub struct SwapMultiPool<'info> {
+    /// The user performing the swap
+    pub payer: Signer<'info>,
+
+    /// CHECK: pool vault authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// The factory state to read protocol fees
+    #[account(address = multi_pool_state.amm_config)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    #[account(mut)]
+    pub multi_pool_state: Box<Account<'info, MultiPoolState>>,
+
+    /// The program account for the most recent oracle observation
+    #[account(mut, address = multi_pool_state.observation_key)]
+    pub multi_observation_state: Box<Account<'info, MultiObservationState>>,
+
+    /// The user token account for input token
+    #[account(mut)]
+    pub input_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The user token account for output token
+    #[account(mut)]
+    pub output_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of input token
+    #[account(address = input_token_account.mint)]
+    pub input_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of output token
+    #[account(address = output_token_account.mint)]
+    pub output_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Light Token program for CPI
+    pub light_token_program: Interface<'info, TokenInterface>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: light_token CPI authority.
+    pub light_token_cpi_authority: AccountInfo<'info>,
+}
+
+/// Loads the pool vaults from the remaining accounts, in pool order
+pub(crate) fn load_multi_pool_vaults<'info>(
+    pool_state: &MultiPoolState,
+    accounts: &'info [AccountInfo<'info>],
+) -> Result<Vec<InterfaceAccount<'info, TokenAccount>>> {
+    let token_count = pool_state.token_count();
+    require_gte!(accounts.len(), token_count, ErrorCode::InvalidTokenCount);
+    accounts[..token_count]
+        .iter()
+        .zip(pool_state.vaults.iter())
+        .map(|(account, vault)| {
+            require_keys_eq!(account.key(), *vault, ErrorCode::InvalidVault);
+            InterfaceAccount::<TokenAccount>::try_from(account)
+        })
+        .collect()
+}
+
+/// Swaps an exact input between any two tokens of a multi-token pool. The
+/// remaining accounts are all pool vaults in pool order.
+pub fn swap_multi_pool<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SwapMultiPool<'info>>,
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> Result<()> {
+    let block_timestamp = oracle::block_timestamp();
+    let pool_state = &ctx.accounts.multi_pool_state;
+    if !pool_state.get_status_by_bit(PoolStatusBitIndex::Swap)
+        || block_timestamp < pool_state.open_time
+    {
+        return err!(ErrorCode::NotApproved);
+    }
+    let vaults = load_multi_pool_vaults(pool_state, ctx.remaining_accounts)?;
+    let input_index = vaults
+        .iter()
+        .position(|vault| vault.mint == ctx.accounts.input_token_mint.key())
+        .ok_or(ErrorCode::InvalidVault)?;
+    let output_index = vaults
+        .iter()
+        .position(|vault| vault.mint == ctx.accounts.output_token_mint.key())
+        .ok_or(ErrorCode::InvalidVault)?;
+    require_neq!(input_index, output_index, ErrorCode::InvalidVault);
+    let vault_amounts = pool_state
+        .vault_amounts_without_fee(&vaults.iter().map(|vault| vault.amount).collect::<Vec<_>>());
+    let (input_vault, output_vault) = (&vaults[input_index], &vaults[output_index]);
+    let (input_vault_amount, output_vault_amount) =
+        (vault_amounts[input_index], vault_amounts[output_index]);
+
+    let input_transfer_fee =
+        get_transfer_fee(&ctx.accounts.input_token_mint.to_account_info(), amount_in)?;
+    // Take transfer fees into account for actual amount transferred in
+    let actual_amount_in = amount_in.saturating_sub(input_transfer_fee);
+    require_gt!(actual_amount_in, 0);
+
+    // Only the two reserves change, so the pair swaps along x * y = k. The
+    // trade fee stays in the pool but for the protocol and fund shares.
+    let trade_fee_rate = pool_state.trade_fee_rates[input_index];
+    let result = CurveCalculator::swap_base_input(
+        &SwapCurve::ConstantProduct,
+        TradeDirection::ZeroForOne,
+        u128::from(actual_amount_in),
+        u128::from(input_vault_amount),
+        u128::from(output_vault_amount),
+        &FeeRates {
+            trade_fee_rate,
+            protocol_fee_rate: ctx.accounts.amm_config.protocol_fee_rate,
+            fund_fee_rate: ctx.accounts.amm_config.fund_fee_rate,
+            creator_fee_rate: 0,
+        },
+    )
+    .ok_or(ErrorCode::ZeroTradingTokens)?;
+    let amount_out = u64::try_from(result.destination_amount_swapped).unwrap();
+    let output_transfer_fee = get_transfer_fee(
+        &ctx.accounts.output_token_mint.to_account_info(),
+        amount_out,
+    )?;
+    let amount_received = amount_out.checked_sub(output_transfer_fee).unwrap();
+    require_gt!(amount_received, 0);
+    require_gte!(
+        amount_received,
+        minimum_amount_out,
+        ErrorCode::ExceededSlippage
+    );
+
+    emit!(SwapEvent {
+        pool_id: pool_state.key(),
+        input_vault_before: input_vault_amount,
+        output_vault_before: output_vault_amount,
+        input_amount: actual_amount_in,
+        output_amount: amount_out,
+        input_transfer_fee,
+        output_transfer_fee,
+        base_input: true,
+        trade_fee_rate,
+        referral_fee: 0,
+        unfilled_amount: 0,
+    });
+
+    transfer_from_user_to_pool_vault(
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.input_token_account.to_account_info(),
+        input_vault.to_account_info(),
+        ctx.accounts.input_token_mint.to_account_info(),
+        ctx.accounts.light_token_program.to_account_info(),
+        amount_in,
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.light_token_cpi_authority.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    )?;
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        output_vault.to_account_info(),
+        ctx.accounts.output_token_account.to_account_info(),
+        ctx.accounts.output_token_mint.to_account_info(),
+        ctx.accounts.light_token_program.to_account_info(),
+        amount_out,
+        &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.light_token_cpi_authority.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    )?;
+
+    // update the previous price to the observation
+    let prices_x32 = pool_state.token_prices_x32(&vault_amounts);
+    ctx.accounts
+        .multi_observation_state
+        .update(block_timestamp, &prices_x32);
+    let pool_state = &mut ctx.accounts.multi_pool_state;
+    pool_state.protocol_fees[input_index] = pool_state.protocol_fees[input_index]
+        .checked_add(u64::try_from(result.protocol_fee).unwrap())
+        .unwrap();
+    pool_state.fund_fees[input_index] = pool_state.fund_fees[input_index]
+        .checked_add(u64::try_from(result.fund_fee).unwrap())
+        .unwrap();
+    pool_state.recent_epoch = Clock::get()?.epoch;
+    Ok(())
+}
@@ -0,0 +1,118 @@
+use super::deposit_multi_pool::MultiPoolTokenAccounts;
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::utils::token::*;
+use anchor_lang::prelude::*;
+use light_anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use light_token::instruction::BurnCpi;
+
+#[derive(Accounts)]
+pub struct WithdrawMultiPool<'info> {
+    /// Owner of the burned lp tokens
+    pub owner: Signer<'info>,
+
+    /// CHECK: pool vault authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub multi_pool_state: Box<Account<'info, MultiPoolState>>,
+
+    /// The program account for the most recent oracle observation
+    #[account(mut, address = multi_pool_state.observation_key)]
+    pub multi_observation_state: Box<Account<'info, MultiObservationState>>,
+
+    /// Owner lp token account
+    #[account(mut, token::authority = owner)]
+    pub owner_lp_token: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Lp mint
+    #[account(
+        mut,
+        address = multi_pool_state.lp_mint @ ErrorCode::IncorrectLpMint
+    )]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Light Token program for CPI
+    pub light_token_program: Interface<'info, TokenInterface>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: light-token CPI authority.
+    pub light_token_cpi_authority: AccountInfo<'info>,
+}
+
+/// Burns `lp_token_amount` and pays out every token of a multi-token pool in
+/// proportion to its reserves. See `MultiPoolTokenAccounts` for the
+/// remaining accounts.
+pub fn withdraw_multi_pool<'info>(
+    ctx: Context<'_, '_, 'info, 'info, WithdrawMultiPool<'info>>,
+    lp_token_amount: u64,
+    minimum_token_amounts: Vec<u64>,
+) -> Result<()> {
+    require_gt!(lp_token_amount, 0);
+    let pool_state = &ctx.accounts.multi_pool_state;
+    if !pool_state.get_status_by_bit(PoolStatusBitIndex::Withdraw) {
+        return err!(ErrorCode::NotApproved);
+    }
+    let token_accounts = MultiPoolTokenAccounts::load(pool_state, ctx.remaining_accounts)?;
+    require_eq!(
+        minimum_token_amounts.len(),
+        pool_state.token_count(),
+        ErrorCode::InvalidTokenCount
+    );
+    // Fees owed to the protocol and fund are no liquidity
+    let vault_amounts = pool_state.vault_amounts_without_fee(&token_accounts.vault_amounts());
+
+    BurnCpi {
+        source: ctx.accounts.owner_lp_token.to_account_info(),
+        mint: ctx.accounts.lp_mint.to_account_info(),
+        amount: lp_token_amount,
+        authority: ctx.accounts.owner.to_account_info(),
+        max_top_up: None,
+    }
+    .invoke()?;
+
+    for (i, vault) in token_accounts.vaults.iter().enumerate() {
+        // Round down so the withdrawal never takes from the other liquidity providers
+        let token_amount = u64::try_from(
+            u128::from(lp_token_amount) * u128::from(vault_amounts[i])
+                / u128::from(pool_state.lp_supply),
+        )
+        .unwrap();
+        require_gt!(token_amount, 0, ErrorCode::ZeroTradingTokens);
+        let transfer_fee = get_transfer_fee(&token_accounts.mints[i], token_amount)?;
+        require_gte!(
+            token_amount.checked_sub(transfer_fee).unwrap(),
+            minimum_token_amounts[i],
+            ErrorCode::ExceededSlippage
+        );
+        transfer_from_pool_vault_to_user(
+            ctx.accounts.authority.to_account_info(),
+            vault.to_account_info(),
+            token_accounts.user_token_accounts[i].clone(),
+            token_accounts.mints[i].clone(),
+            ctx.accounts.light_token_program.to_account_info(),
+            token_amount,
+            &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+            ctx.accounts.owner.to_account_info(),
+            ctx.accounts.light_token_cpi_authority.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        )?;
+    }
+
+    // update the previous price to the observation
+    let prices_x32 = pool_state.token_prices_x32(&vault_amounts);
+    ctx.accounts
+        .multi_observation_state
+        .update(oracle::block_timestamp(), &prices_x32);
+    let pool_state = &mut ctx.accounts.multi_pool_state;
+    pool_state.lp_supply = pool_state.lp_supply.checked_sub(lp_token_amount).unwrap();
+    pool_state.recent_epoch = Clock::get()?.epoch;
+    Ok(())
+}
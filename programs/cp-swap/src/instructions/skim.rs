@@ -0,0 +1,117 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::utils::token::*;
+use anchor_lang::prelude::*;
+use light_anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+pub struct Skim<'info> {
+    /// Anyone can skim, pays for the transfers
+    pub payer: Signer<'info>,
+
+    /// CHECK: pool vault authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub pool_state: Account<'info, PoolState>,
+
+    /// The address that holds pool tokens for token_0
+    #[account(
+        mut,
+        constraint = token_0_vault.key() == pool_state.token_0_vault
+    )]
+    pub token_0_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for token_1
+    #[account(
+        mut,
+        constraint = token_1_vault.key() == pool_state.token_1_vault
+    )]
+    pub token_1_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of token_0 vault
+    #[account(
+        address = token_0_vault.mint
+    )]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of token_1 vault
+    #[account(
+        address = token_1_vault.mint
+    )]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The address that receives the token_0 excess
+    #[account(mut)]
+    pub recipient_token_0_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that receives the token_1 excess
+    #[account(mut)]
+    pub recipient_token_1_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Light Token program for CPI
+    pub light_token_program: Interface<'info, TokenInterface>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: light-token CPI authority.
+    pub light_token_cpi_authority: AccountInfo<'info>,
+}
+
+/// Sends the vault balances above the tracked reserves of a
+/// `RESERVE_MODE_SKIM` pool to the recipients
+pub fn skim(ctx: Context<Skim>) -> Result<()> {
+    let pool_state = &mut ctx.accounts.pool_state;
+    if pool_state.reserve_mode != RESERVE_MODE_SKIM {
+        return err!(ErrorCode::NotApproved);
+    }
+    let excess_0 = ctx
+        .accounts
+        .token_0_vault
+        .amount
+        .saturating_sub(pool_state.token_0_reserve);
+    let excess_1 = ctx
+        .accounts
+        .token_1_vault
+        .amount
+        .saturating_sub(pool_state.token_1_reserve);
+    require!(excess_0 > 0 || excess_1 > 0, ErrorCode::ZeroTradingTokens);
+
+    if excess_0 > 0 {
+        transfer_from_pool_vault_to_user(
+            ctx.accounts.authority.to_account_info(),
+            ctx.accounts.token_0_vault.to_account_info(),
+            ctx.accounts.recipient_token_0_account.to_account_info(),
+            ctx.accounts.vault_0_mint.to_account_info(),
+            ctx.accounts.light_token_program.to_account_info(),
+            excess_0,
+            &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+            ctx.accounts.payer.to_account_info(),
+            ctx.accounts.light_token_cpi_authority.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        )?;
+    }
+    if excess_1 > 0 {
+        transfer_from_pool_vault_to_user(
+            ctx.accounts.authority.to_account_info(),
+            ctx.accounts.token_1_vault.to_account_info(),
+            ctx.accounts.recipient_token_1_account.to_account_info(),
+            ctx.accounts.vault_1_mint.to_account_info(),
+            ctx.accounts.light_token_program.to_account_info(),
+            excess_1,
+            &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+            ctx.accounts.payer.to_account_info(),
+            ctx.accounts.light_token_cpi_authority.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        )?;
+    }
+    pool_state.recent_epoch = Clock::get()?.epoch;
+
+    Ok(())
+}
@@ -1,13 +1,14 @@
 use super::swap_base_input::Swap;
-use crate::curve::{calculator::CurveCalculator, TradeDirection};
+use crate::curve::{calculator::CurveCalculator, Curve, Fees, TradeDirection};
 use crate::error::ErrorCode;
 use crate::states::*;
 use crate::utils::token::*;
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program;
+use light_anchor_spl::token_interface::TokenAccount;
 
-pub fn swap_base_output(
-    ctx: Context<Swap>,
+pub fn swap_base_output<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Swap<'info>>,
     max_amount_in: u64,
     amount_out_less_fee: u64,
 ) -> Result<()> {
@@ -20,6 +21,19 @@ pub fn swap_base_output(
     {
         return err!(ErrorCode::NotApproved);
     }
+    if block_timestamp < pool_state.cooldown_end_time {
+        return err!(ErrorCode::PoolInCooldown);
+    }
+    pool_state.update_weights(block_timestamp);
+    // Tokens sent to the vaults from outside the pool are left out
+    let input_vault_amount = pool_state.vault_amount(
+        &ctx.accounts.input_vault.key(),
+        ctx.accounts.input_vault.amount,
+    );
+    let output_vault_amount = pool_state.vault_amount(
+        &ctx.accounts.output_vault.key(),
+        ctx.accounts.output_vault.amount,
+    );
     let out_transfer_fee = get_transfer_inverse_fee(
         &ctx.accounts.output_token_mint.to_account_info(),
         amount_out_less_fee,
@@ -36,15 +50,10 @@ pub fn swap_base_output(
     ) = if ctx.accounts.input_vault.key() == pool_state.token_0_vault
         && ctx.accounts.output_vault.key() == pool_state.token_1_vault
     {
-        let (total_input_token_amount, total_output_token_amount) = pool_state
-            .vault_amount_without_fee(
-                ctx.accounts.input_vault.amount,
-                ctx.accounts.output_vault.amount,
-            );
-        let (token_0_price_x64, token_1_price_x64) = pool_state.token_price_x32(
-            ctx.accounts.input_vault.amount,
-            ctx.accounts.output_vault.amount,
-        );
+        let (total_input_token_amount, total_output_token_amount) =
+            pool_state.vault_amount_without_fee(input_vault_amount, output_vault_amount);
+        let (token_0_price_x64, token_1_price_x64) =
+            pool_state.token_price_x32(input_vault_amount, output_vault_amount);
 
         (
             TradeDirection::ZeroForOne,
@@ -56,15 +65,10 @@ pub fn swap_base_output(
     } else if ctx.accounts.input_vault.key() == pool_state.token_1_vault
         && ctx.accounts.output_vault.key() == pool_state.token_0_vault
     {
-        let (total_output_token_amount, total_input_token_amount) = pool_state
-            .vault_amount_without_fee(
-                ctx.accounts.output_vault.amount,
-                ctx.accounts.input_vault.amount,
-            );
-        let (token_0_price_x64, token_1_price_x64) = pool_state.token_price_x32(
-            ctx.accounts.output_vault.amount,
-            ctx.accounts.input_vault.amount,
-        );
+        let (total_output_token_amount, total_input_token_amount) =
+            pool_state.vault_amount_without_fee(output_vault_amount, input_vault_amount);
+        let (token_0_price_x64, token_1_price_x64) =
+            pool_state.token_price_x32(output_vault_amount, input_vault_amount);
 
         (
             TradeDirection::OneForZero,
@@ -76,28 +80,42 @@ pub fn swap_base_output(
     } else {
         return err!(ErrorCode::InvalidVault);
     };
-    let constant_before = u128::from(total_input_token_amount)
-        .checked_mul(u128::from(total_output_token_amount))
+    let curve = pool_state.swap_curve(&ctx.accounts.amm_config)?;
+    let trade_fee_rate = ctx.accounts.amm_config.effective_trade_fee_rate(
+        &ctx.accounts.observation_state,
+        token_0_price_x64,
+        pool_state.open_time,
+        block_timestamp,
+    );
+    let (total_token_0_amount, total_token_1_amount) =
+        trade_direction.token_amounts(total_input_token_amount, total_output_token_amount);
+    let constant_before = curve
+        .invariant(
+            u128::from(total_token_0_amount),
+            u128::from(total_token_1_amount),
+        )
         .unwrap();
 
     let result = CurveCalculator::swap_base_output(
+        &curve,
+        trade_direction,
         u128::from(actual_amount_out),
         u128::from(total_input_token_amount),
         u128::from(total_output_token_amount),
-        ctx.accounts.amm_config.trade_fee_rate,
-        ctx.accounts.amm_config.protocol_fee_rate,
-        ctx.accounts.amm_config.fund_fee_rate,
+        &ctx.accounts.amm_config.fee_rates(trade_fee_rate),
     )
     .ok_or(ErrorCode::ZeroTradingTokens)?;
 
-    let constant_after = u128::from(
+    let (new_swap_token_0_amount, new_swap_token_1_amount) = trade_direction.token_amounts(
         result
             .new_swap_source_amount
             .checked_sub(result.trade_fee)
             .unwrap(),
-    )
-    .checked_mul(u128::from(result.new_swap_destination_amount))
-    .unwrap();
+        result.new_swap_destination_amount,
+    );
+    let constant_after = curve
+        .invariant(new_swap_token_0_amount, new_swap_token_1_amount)
+        .unwrap();
 
     #[cfg(feature = "enable-log")]
     msg!(
@@ -131,8 +149,37 @@ pub fn swap_base_output(
     );
     let (output_transfer_amount, output_transfer_fee) = (actual_amount_out, out_transfer_fee);
 
-    let protocol_fee = u64::try_from(result.protocol_fee).unwrap();
+    // An optional referrer token account for the input token takes a share
+    // of the protocol fee
+    let referrer_token_account = match ctx.remaining_accounts.first() {
+        Some(account) => {
+            let referrer_token_account = InterfaceAccount::<TokenAccount>::try_from(account)?;
+            require_keys_eq!(
+                referrer_token_account.mint,
+                ctx.accounts.input_token_mint.key(),
+                ErrorCode::InvalidReferrer
+            );
+            Some(account)
+        }
+        None => None,
+    };
+    let referral_fee = match referrer_token_account {
+        Some(_) => u64::try_from(
+            Fees::referral_fee(
+                result.protocol_fee,
+                ctx.accounts.amm_config.referral_fee_rate,
+            )
+            .unwrap(),
+        )
+        .unwrap(),
+        None => 0,
+    };
+    let protocol_fee = u64::try_from(result.protocol_fee)
+        .unwrap()
+        .checked_sub(referral_fee)
+        .unwrap();
     let fund_fee = u64::try_from(result.fund_fee).unwrap();
+    let creator_fee = u64::try_from(result.creator_fee).unwrap();
 
     match trade_direction {
         TradeDirection::ZeroForOne => {
@@ -142,6 +189,10 @@ pub fn swap_base_output(
                 .unwrap();
             pool_state.fund_fees_token_0 =
                 pool_state.fund_fees_token_0.checked_add(fund_fee).unwrap();
+            pool_state.creator_fees_token_0 = pool_state
+                .creator_fees_token_0
+                .checked_add(creator_fee)
+                .unwrap();
         }
         TradeDirection::OneForZero => {
             pool_state.protocol_fees_token_1 = pool_state
@@ -150,9 +201,31 @@ pub fn swap_base_output(
                 .unwrap();
             pool_state.fund_fees_token_1 =
                 pool_state.fund_fees_token_1.checked_add(fund_fee).unwrap();
+            pool_state.creator_fees_token_1 = pool_state
+                .creator_fees_token_1
+                .checked_add(creator_fee)
+                .unwrap();
         }
     };
 
+    let (new_vault_0_amount, new_vault_1_amount) = trade_direction.token_amounts(
+        input_vault_amount
+            .checked_add(u64::try_from(result.source_amount_swapped).unwrap())
+            .unwrap()
+            .checked_sub(referral_fee)
+            .unwrap(),
+        output_vault_amount
+            .checked_sub(u64::try_from(result.destination_amount_swapped).unwrap())
+            .unwrap(),
+    );
+    pool_state.check_price_deviation(
+        &ctx.accounts.amm_config,
+        &ctx.accounts.observation_state,
+        new_vault_0_amount,
+        new_vault_1_amount,
+        block_timestamp,
+    )?;
+
     emit!(SwapEvent {
         pool_id,
         input_vault_before: total_input_token_amount,
@@ -161,7 +234,10 @@ pub fn swap_base_output(
         output_amount: u64::try_from(result.destination_amount_swapped).unwrap(),
         input_transfer_fee,
         output_transfer_fee,
-        base_input: false
+        base_input: false,
+        trade_fee_rate,
+        referral_fee,
+        unfilled_amount: 0,
     });
     require_gte!(constant_after, constant_before);
 
@@ -190,12 +266,42 @@ pub fn swap_base_output(
         ctx.accounts.system_program.to_account_info(),
     )?;
 
+    if let Some(referrer_token_account) = referrer_token_account {
+        transfer_from_pool_vault_to_user(
+            ctx.accounts.authority.to_account_info(),
+            ctx.accounts.input_vault.to_account_info(),
+            referrer_token_account.clone(),
+            ctx.accounts.input_token_mint.to_account_info(),
+            ctx.accounts.input_token_program.to_account_info(),
+            referral_fee,
+            &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+            ctx.accounts.payer.to_account_info(),
+            ctx.accounts.light_token_cpi_authority.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        )?;
+    }
+
+    ctx.accounts.observation_state.accumulate_swap(
+        trade_direction,
+        u64::try_from(result.source_amount_swapped).unwrap(),
+        u64::try_from(result.destination_amount_swapped).unwrap(),
+        u64::try_from(result.trade_fee).unwrap(),
+    );
     // update the previous price to the observation
     ctx.accounts.observation_state.update(
         oracle::block_timestamp(),
         token_0_price_x64,
         token_1_price_x64,
+        oracle::liquidity(total_token_0_amount, total_token_1_amount),
     );
+    pool_state.update_reserve(
+        &ctx.accounts.input_vault.to_account_info(),
+        ctx.accounts.input_vault.amount,
+    )?;
+    pool_state.update_reserve(
+        &ctx.accounts.output_vault.to_account_info(),
+        ctx.accounts.output_vault.amount,
+    )?;
     pool_state.recent_epoch = Clock::get()?.epoch;
 
     Ok(())
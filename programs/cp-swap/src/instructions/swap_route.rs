@@ -0,0 +1,318 @@
+use crate::curve::calculator::{Curve, CurveCalculator};
+use crate::curve::TradeDirection;
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::utils::token::*;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program;
+use light_anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Accounts of one hop in the remaining accounts of `swap_route`:
+/// amm_config, pool_state, observation_state, input_vault, output_vault and
+/// output_token_mint
+pub const ROUTE_HOP_ACCOUNTS: usize = 6;
+
+#[derive(Accounts)]
+pub struct SwapRoute<'info> {
+    /// The user performing the swap
+    pub payer: Signer<'info>,
+
+    /// CHECK: pool vault and lp mint authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// The user token account for the route input token
+    #[account(mut)]
+    pub input_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The user token account for the route output token
+    #[account(mut)]
+    pub output_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of the route input token
+    #[account(
+        address = input_token_account.mint
+    )]
+    pub input_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Light Token program for CPI
+    pub light_token_program: Interface<'info, TokenInterface>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: light_token CPI authority.
+    pub light_token_cpi_authority: AccountInfo<'info>,
+}
+
+pub fn swap_route<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SwapRoute<'info>>,
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> Result<()> {
+    let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
+    let hops: Vec<&[AccountInfo<'info>]> =
+        ctx.remaining_accounts.chunks(ROUTE_HOP_ACCOUNTS).collect();
+    if hops.is_empty()
+        || !ctx
+            .remaining_accounts
+            .len()
+            .is_multiple_of(ROUTE_HOP_ACCOUNTS)
+    {
+        return err!(ErrorCode::InvalidRoute);
+    }
+    // A pool visited twice would be priced off stale vault amounts
+    for (i, hop) in hops.iter().enumerate() {
+        if hops[..i].iter().any(|other| other[1].key == hop[1].key) {
+            return err!(ErrorCode::InvalidRoute);
+        }
+    }
+
+    let mut input_token_mint = ctx.accounts.input_token_mint.to_account_info();
+    let mut input_transfer_fee = get_transfer_fee(&input_token_mint, amount_in)?;
+    // Take transfer fees into account for actual amount transferred in
+    let mut actual_amount_in = amount_in.saturating_sub(input_transfer_fee);
+    let mut receive_amount = 0;
+    for (i, hop) in hops.iter().enumerate() {
+        require_gt!(actual_amount_in, 0);
+        let amm_config = Box::new(Account::<AmmConfig>::try_from(&hop[0])?);
+        let mut pool_state = Box::new(Account::<PoolState>::try_from(&hop[1])?);
+        let mut observation_state = Box::new(Account::<ObservationState>::try_from(&hop[2])?);
+        let input_vault = Box::new(InterfaceAccount::<TokenAccount>::try_from(&hop[3])?);
+        let output_vault = Box::new(InterfaceAccount::<TokenAccount>::try_from(&hop[4])?);
+        let output_token_mint = Box::new(InterfaceAccount::<Mint>::try_from(&hop[5])?);
+        require_keys_eq!(amm_config.key(), pool_state.amm_config);
+        require_keys_eq!(observation_state.key(), pool_state.observation_key);
+        require_keys_eq!(
+            input_vault.mint,
+            input_token_mint.key(),
+            ErrorCode::InvalidRoute
+        );
+        require_keys_eq!(output_vault.mint, output_token_mint.key());
+
+        let pool_id = pool_state.key();
+        if !pool_state.get_status_by_bit(PoolStatusBitIndex::Swap)
+            || block_timestamp < pool_state.open_time
+        {
+            return err!(ErrorCode::NotApproved);
+        }
+        if block_timestamp < pool_state.cooldown_end_time {
+            return err!(ErrorCode::PoolInCooldown);
+        }
+        pool_state.update_weights(block_timestamp);
+
+        // Past the first hop the input already arrived in the vault
+        let input_vault_balance = if i == 0 {
+            input_vault.amount
+        } else {
+            input_vault.amount.checked_sub(actual_amount_in).unwrap()
+        };
+        // Tokens sent to the vaults from outside the pool are left out
+        let input_vault_amount = pool_state.vault_amount(&input_vault.key(), input_vault_balance);
+        let output_vault_amount = pool_state.vault_amount(&output_vault.key(), output_vault.amount);
+
+        // Calculate the trade amounts and the price before swap
+        let (trade_direction, total_input_token_amount, total_output_token_amount) =
+            if input_vault.key() == pool_state.token_0_vault
+                && output_vault.key() == pool_state.token_1_vault
+            {
+                let (total_input_token_amount, total_output_token_amount) =
+                    pool_state.vault_amount_without_fee(input_vault_amount, output_vault_amount);
+                (
+                    TradeDirection::ZeroForOne,
+                    total_input_token_amount,
+                    total_output_token_amount,
+                )
+            } else if input_vault.key() == pool_state.token_1_vault
+                && output_vault.key() == pool_state.token_0_vault
+            {
+                let (total_output_token_amount, total_input_token_amount) =
+                    pool_state.vault_amount_without_fee(output_vault_amount, input_vault_amount);
+                (
+                    TradeDirection::OneForZero,
+                    total_input_token_amount,
+                    total_output_token_amount,
+                )
+            } else {
+                return err!(ErrorCode::InvalidVault);
+            };
+        let (vault_0_amount, vault_1_amount) =
+            trade_direction.token_amounts(input_vault_amount, output_vault_amount);
+        let (token_0_price_x64, token_1_price_x64) =
+            pool_state.token_price_x32(vault_0_amount, vault_1_amount);
+        let curve = pool_state.swap_curve(&amm_config)?;
+        let trade_fee_rate = amm_config.effective_trade_fee_rate(
+            &observation_state,
+            token_0_price_x64,
+            pool_state.open_time,
+            block_timestamp,
+        );
+        let (total_token_0_amount, total_token_1_amount) =
+            trade_direction.token_amounts(total_input_token_amount, total_output_token_amount);
+        let constant_before = curve
+            .invariant(
+                u128::from(total_token_0_amount),
+                u128::from(total_token_1_amount),
+            )
+            .unwrap();
+
+        let result = CurveCalculator::swap_base_input(
+            &curve,
+            trade_direction,
+            u128::from(actual_amount_in),
+            u128::from(total_input_token_amount),
+            u128::from(total_output_token_amount),
+            &amm_config.fee_rates(trade_fee_rate),
+        )
+        .ok_or(ErrorCode::ZeroTradingTokens)?;
+
+        let (new_swap_token_0_amount, new_swap_token_1_amount) = trade_direction.token_amounts(
+            result
+                .new_swap_source_amount
+                .checked_sub(result.trade_fee)
+                .unwrap(),
+            result.new_swap_destination_amount,
+        );
+        let constant_after = curve
+            .invariant(new_swap_token_0_amount, new_swap_token_1_amount)
+            .unwrap();
+        require_gte!(constant_after, constant_before);
+        require_eq!(
+            u64::try_from(result.source_amount_swapped).unwrap(),
+            actual_amount_in
+        );
+
+        let amount_out = u64::try_from(result.destination_amount_swapped).unwrap();
+        let output_transfer_fee =
+            get_transfer_fee(&output_token_mint.to_account_info(), amount_out)?;
+        let amount_received = amount_out.checked_sub(output_transfer_fee).unwrap();
+
+        let protocol_fee = u64::try_from(result.protocol_fee).unwrap();
+        let fund_fee = u64::try_from(result.fund_fee).unwrap();
+        let creator_fee = u64::try_from(result.creator_fee).unwrap();
+        match trade_direction {
+            TradeDirection::ZeroForOne => {
+                pool_state.protocol_fees_token_0 = pool_state
+                    .protocol_fees_token_0
+                    .checked_add(protocol_fee)
+                    .unwrap();
+                pool_state.fund_fees_token_0 =
+                    pool_state.fund_fees_token_0.checked_add(fund_fee).unwrap();
+                pool_state.creator_fees_token_0 = pool_state
+                    .creator_fees_token_0
+                    .checked_add(creator_fee)
+                    .unwrap();
+            }
+            TradeDirection::OneForZero => {
+                pool_state.protocol_fees_token_1 = pool_state
+                    .protocol_fees_token_1
+                    .checked_add(protocol_fee)
+                    .unwrap();
+                pool_state.fund_fees_token_1 =
+                    pool_state.fund_fees_token_1.checked_add(fund_fee).unwrap();
+                pool_state.creator_fees_token_1 = pool_state
+                    .creator_fees_token_1
+                    .checked_add(creator_fee)
+                    .unwrap();
+            }
+        };
+        let (new_vault_0_amount, new_vault_1_amount) = trade_direction.token_amounts(
+            input_vault_amount.checked_add(actual_amount_in).unwrap(),
+            output_vault_amount.checked_sub(amount_out).unwrap(),
+        );
+        pool_state.check_price_deviation(
+            &amm_config,
+            &observation_state,
+            new_vault_0_amount,
+            new_vault_1_amount,
+            block_timestamp,
+        )?;
+
+        emit!(SwapEvent {
+            pool_id,
+            input_vault_before: total_input_token_amount,
+            output_vault_before: total_output_token_amount,
+            input_amount: actual_amount_in,
+            output_amount: amount_out,
+            input_transfer_fee,
+            output_transfer_fee,
+            base_input: true,
+            trade_fee_rate,
+            referral_fee: 0,
+            unfilled_amount: 0,
+        });
+
+        if i == 0 {
+            transfer_from_user_to_pool_vault(
+                ctx.accounts.payer.to_account_info(),
+                ctx.accounts.input_token_account.to_account_info(),
+                input_vault.to_account_info(),
+                input_token_mint.clone(),
+                ctx.accounts.light_token_program.to_account_info(),
+                amount_in,
+                ctx.accounts.payer.to_account_info(),
+                ctx.accounts.light_token_cpi_authority.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            )?;
+        }
+        // Intermediate tokens move straight into the input vault of the next pool
+        let destination = match hops.get(i + 1) {
+            Some(next_hop) => next_hop[3].clone(),
+            None => ctx.accounts.output_token_account.to_account_info(),
+        };
+        transfer_from_pool_vault_to_user(
+            ctx.accounts.authority.to_account_info(),
+            output_vault.to_account_info(),
+            destination,
+            output_token_mint.to_account_info(),
+            ctx.accounts.light_token_program.to_account_info(),
+            amount_out,
+            &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+            ctx.accounts.payer.to_account_info(),
+            ctx.accounts.light_token_cpi_authority.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        )?;
+
+        observation_state.accumulate_swap(
+            trade_direction,
+            actual_amount_in,
+            amount_out,
+            u64::try_from(result.trade_fee).unwrap(),
+        );
+        // update the previous price to the observation
+        observation_state.update(
+            oracle::block_timestamp(),
+            token_0_price_x64,
+            token_1_price_x64,
+            oracle::liquidity(total_token_0_amount, total_token_1_amount),
+        );
+        pool_state.update_reserve(&input_vault.to_account_info(), input_vault_balance)?;
+        pool_state.update_reserve(&output_vault.to_account_info(), output_vault.amount)?;
+        pool_state.recent_epoch = Clock::get()?.epoch;
+        pool_state.exit(&crate::id())?;
+        observation_state.exit(&crate::id())?;
+
+        input_token_mint = output_token_mint.to_account_info();
+        input_transfer_fee = output_transfer_fee;
+        actual_amount_in = amount_received;
+        receive_amount = amount_received;
+    }
+
+    require_keys_eq!(
+        ctx.accounts.output_token_account.mint,
+        input_token_mint.key(),
+        ErrorCode::InvalidRoute
+    );
+    require_gt!(receive_amount, 0);
+    require_gte!(
+        receive_amount,
+        minimum_amount_out,
+        ErrorCode::ExceededSlippage
+    );
+
+    Ok(())
+}
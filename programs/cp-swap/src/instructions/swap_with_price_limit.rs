@@ -0,0 +1,282 @@
+use crate::curve::calculator::{Curve, CurveCalculator, CurveType};
+use crate::curve::{ConstantProductCurve, TradeDirection};
+use crate::error::ErrorCode;
+use crate::instructions::Swap;
+use crate::states::*;
+use crate::utils::token::*;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program;
+
+pub fn swap_with_price_limit(
+    ctx: Context<Swap>,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    price_limit_x32: u128,
+) -> Result<()> {
+    let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
+    let pool_id = ctx.accounts.pool_state.key();
+    let pool_state = &mut ctx.accounts.pool_state;
+    if !pool_state.get_status_by_bit(PoolStatusBitIndex::Swap)
+        || block_timestamp < pool_state.open_time
+    {
+        return err!(ErrorCode::NotApproved);
+    }
+    if block_timestamp < pool_state.cooldown_end_time {
+        return err!(ErrorCode::PoolInCooldown);
+    }
+    pool_state.update_weights(block_timestamp);
+    // Tokens sent to the vaults from outside the pool are left out
+    let input_vault_amount = pool_state.vault_amount(
+        &ctx.accounts.input_vault.key(),
+        ctx.accounts.input_vault.amount,
+    );
+    let output_vault_amount = pool_state.vault_amount(
+        &ctx.accounts.output_vault.key(),
+        ctx.accounts.output_vault.amount,
+    );
+    // The fill is solved in closed form on x * y = k
+    if CurveType::try_from(pool_state.curve_type)? != CurveType::ConstantProduct {
+        return err!(ErrorCode::InvalidCurve);
+    }
+
+    // Calculate the trade amounts and the price before swap
+    let (
+        trade_direction,
+        total_input_token_amount,
+        total_output_token_amount,
+        token_0_price_x64,
+        token_1_price_x64,
+    ) = if ctx.accounts.input_vault.key() == pool_state.token_0_vault
+        && ctx.accounts.output_vault.key() == pool_state.token_1_vault
+    {
+        let (total_input_token_amount, total_output_token_amount) =
+            pool_state.vault_amount_without_fee(input_vault_amount, output_vault_amount);
+        let (token_0_price_x64, token_1_price_x64) =
+            pool_state.token_price_x32(input_vault_amount, output_vault_amount);
+
+        (
+            TradeDirection::ZeroForOne,
+            total_input_token_amount,
+            total_output_token_amount,
+            token_0_price_x64,
+            token_1_price_x64,
+        )
+    } else if ctx.accounts.input_vault.key() == pool_state.token_1_vault
+        && ctx.accounts.output_vault.key() == pool_state.token_0_vault
+    {
+        let (total_output_token_amount, total_input_token_amount) =
+            pool_state.vault_amount_without_fee(output_vault_amount, input_vault_amount);
+        let (token_0_price_x64, token_1_price_x64) =
+            pool_state.token_price_x32(output_vault_amount, input_vault_amount);
+
+        (
+            TradeDirection::OneForZero,
+            total_input_token_amount,
+            total_output_token_amount,
+            token_0_price_x64,
+            token_1_price_x64,
+        )
+    } else {
+        return err!(ErrorCode::InvalidVault);
+    };
+
+    // Fill only up to the amount that moves the price to the limit
+    let max_amount_in = ConstantProductCurve::max_source_amount_for_price_limit(
+        price_limit_x32,
+        u128::from(total_input_token_amount),
+        u128::from(total_output_token_amount),
+        trade_direction,
+    )
+    .ok_or(ErrorCode::InvalidInput)?;
+    let input_token_mint = ctx.accounts.input_token_mint.to_account_info();
+    let transfer_fee = get_transfer_fee(&input_token_mint, amount_in)?;
+    // Take transfer fees into account for actual amount transferred in
+    let mut actual_amount_in = amount_in.saturating_sub(transfer_fee);
+    let mut input_transfer_amount = amount_in;
+    let mut input_transfer_fee = transfer_fee;
+    if u128::from(actual_amount_in) > max_amount_in {
+        actual_amount_in = u64::try_from(max_amount_in).unwrap();
+        require_gt!(actual_amount_in, 0, ErrorCode::PriceLimitReached);
+        input_transfer_fee = get_transfer_inverse_fee(&input_token_mint, actual_amount_in)?;
+        input_transfer_amount = actual_amount_in.checked_add(input_transfer_fee).unwrap();
+    }
+    require_gt!(actual_amount_in, 0);
+    let unfilled_amount = amount_in.saturating_sub(input_transfer_amount);
+
+    let curve = pool_state.swap_curve(&ctx.accounts.amm_config)?;
+    let trade_fee_rate = ctx.accounts.amm_config.effective_trade_fee_rate(
+        &ctx.accounts.observation_state,
+        token_0_price_x64,
+        pool_state.open_time,
+        block_timestamp,
+    );
+    let (total_token_0_amount, total_token_1_amount) =
+        trade_direction.token_amounts(total_input_token_amount, total_output_token_amount);
+    let constant_before = curve
+        .invariant(
+            u128::from(total_token_0_amount),
+            u128::from(total_token_1_amount),
+        )
+        .unwrap();
+
+    let result = CurveCalculator::swap_base_input(
+        &curve,
+        trade_direction,
+        u128::from(actual_amount_in),
+        u128::from(total_input_token_amount),
+        u128::from(total_output_token_amount),
+        &ctx.accounts.amm_config.fee_rates(trade_fee_rate),
+    )
+    .ok_or(ErrorCode::ZeroTradingTokens)?;
+
+    let (new_swap_token_0_amount, new_swap_token_1_amount) = trade_direction.token_amounts(
+        result
+            .new_swap_source_amount
+            .checked_sub(result.trade_fee)
+            .unwrap(),
+        result.new_swap_destination_amount,
+    );
+    let constant_after = curve
+        .invariant(new_swap_token_0_amount, new_swap_token_1_amount)
+        .unwrap();
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "source_amount_swapped:{}, destination_amount_swapped:{}, trade_fee:{}, constant_before:{},constant_after:{}",
+        result.source_amount_swapped,
+        result.destination_amount_swapped,
+        result.trade_fee,
+        constant_before,
+        constant_after
+    );
+    require_eq!(
+        u64::try_from(result.source_amount_swapped).unwrap(),
+        actual_amount_in
+    );
+    let (output_transfer_amount, output_transfer_fee) = {
+        let amount_out = u64::try_from(result.destination_amount_swapped).unwrap();
+        let transfer_fee = get_transfer_fee(
+            &ctx.accounts.output_token_mint.to_account_info(),
+            amount_out,
+        )?;
+        let amount_received = amount_out.checked_sub(transfer_fee).unwrap();
+        require_gt!(amount_received, 0);
+        require_gte!(
+            amount_received,
+            minimum_amount_out,
+            ErrorCode::ExceededSlippage
+        );
+        (amount_out, transfer_fee)
+    };
+
+    let protocol_fee = u64::try_from(result.protocol_fee).unwrap();
+    let fund_fee = u64::try_from(result.fund_fee).unwrap();
+    let creator_fee = u64::try_from(result.creator_fee).unwrap();
+
+    match trade_direction {
+        TradeDirection::ZeroForOne => {
+            pool_state.protocol_fees_token_0 = pool_state
+                .protocol_fees_token_0
+                .checked_add(protocol_fee)
+                .unwrap();
+            pool_state.fund_fees_token_0 =
+                pool_state.fund_fees_token_0.checked_add(fund_fee).unwrap();
+            pool_state.creator_fees_token_0 = pool_state
+                .creator_fees_token_0
+                .checked_add(creator_fee)
+                .unwrap();
+        }
+        TradeDirection::OneForZero => {
+            pool_state.protocol_fees_token_1 = pool_state
+                .protocol_fees_token_1
+                .checked_add(protocol_fee)
+                .unwrap();
+            pool_state.fund_fees_token_1 =
+                pool_state.fund_fees_token_1.checked_add(fund_fee).unwrap();
+            pool_state.creator_fees_token_1 = pool_state
+                .creator_fees_token_1
+                .checked_add(creator_fee)
+                .unwrap();
+        }
+    };
+
+    let (new_vault_0_amount, new_vault_1_amount) = trade_direction.token_amounts(
+        input_vault_amount
+            .checked_add(u64::try_from(result.source_amount_swapped).unwrap())
+            .unwrap(),
+        output_vault_amount
+            .checked_sub(u64::try_from(result.destination_amount_swapped).unwrap())
+            .unwrap(),
+    );
+    pool_state.check_price_deviation(
+        &ctx.accounts.amm_config,
+        &ctx.accounts.observation_state,
+        new_vault_0_amount,
+        new_vault_1_amount,
+        block_timestamp,
+    )?;
+
+    emit!(SwapEvent {
+        pool_id,
+        input_vault_before: total_input_token_amount,
+        output_vault_before: total_output_token_amount,
+        input_amount: u64::try_from(result.source_amount_swapped).unwrap(),
+        output_amount: u64::try_from(result.destination_amount_swapped).unwrap(),
+        input_transfer_fee,
+        output_transfer_fee,
+        base_input: true,
+        trade_fee_rate,
+        referral_fee: 0,
+        unfilled_amount,
+    });
+    require_gte!(constant_after, constant_before);
+
+    transfer_from_user_to_pool_vault(
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.input_token_account.to_account_info(),
+        ctx.accounts.input_vault.to_account_info(),
+        input_token_mint,
+        ctx.accounts.input_token_program.to_account_info(),
+        input_transfer_amount,
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.light_token_cpi_authority.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    )?;
+
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.output_vault.to_account_info(),
+        ctx.accounts.output_token_account.to_account_info(),
+        ctx.accounts.output_token_mint.to_account_info(),
+        ctx.accounts.output_token_program.to_account_info(),
+        output_transfer_amount,
+        &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.light_token_cpi_authority.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    )?;
+
+    ctx.accounts.observation_state.accumulate_swap(
+        trade_direction,
+        u64::try_from(result.source_amount_swapped).unwrap(),
+        u64::try_from(result.destination_amount_swapped).unwrap(),
+        u64::try_from(result.trade_fee).unwrap(),
+    );
+    // update the previous price to the observation
+    ctx.accounts.observation_state.update(
+        oracle::block_timestamp(),
+        token_0_price_x64,
+        token_1_price_x64,
+        oracle::liquidity(total_token_0_amount, total_token_1_amount),
+    );
+    pool_state.update_reserve(
+        &ctx.accounts.input_vault.to_account_info(),
+        ctx.accounts.input_vault.amount,
+    )?;
+    pool_state.update_reserve(
+        &ctx.accounts.output_vault.to_account_info(),
+        ctx.accounts.output_vault.amount,
+    )?;
+    pool_state.recent_epoch = Clock::get()?.epoch;
+
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+use light_anchor_spl::token_interface::TokenAccount;
+
+#[derive(Accounts)]
+pub struct SyncReserves<'info> {
+    /// Anyone can sync a `RESERVE_MODE_SYNC` pool
+    #[account(mut)]
+    pub pool_state: Account<'info, PoolState>,
+
+    /// The program account for the most recent oracle observation
+    #[account(mut, address = pool_state.observation_key)]
+    pub observation_state: Box<Account<'info, ObservationState>>,
+
+    /// The address that holds pool tokens for token_0
+    #[account(constraint = token_0_vault.key() == pool_state.token_0_vault)]
+    pub token_0_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for token_1
+    #[account(constraint = token_1_vault.key() == pool_state.token_1_vault)]
+    pub token_1_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+}
+
+/// Adopts the vault balances of a `RESERVE_MODE_SYNC` pool as its reserves
+pub fn sync(ctx: Context<SyncReserves>) -> Result<()> {
+    let pool_state = &mut ctx.accounts.pool_state;
+    if pool_state.reserve_mode != RESERVE_MODE_SYNC {
+        return err!(ErrorCode::NotApproved);
+    }
+    pool_state.update_weights(oracle::block_timestamp());
+    // update the previous price to the observation
+    let (vault_0_amount, vault_1_amount) = pool_state.vault_amounts(
+        ctx.accounts.token_0_vault.amount,
+        ctx.accounts.token_1_vault.amount,
+    );
+    let (token_0_price_x32, token_1_price_x32) =
+        pool_state.token_price_x32(vault_0_amount, vault_1_amount);
+    let (total_token_0_amount, total_token_1_amount) =
+        pool_state.vault_amount_without_fee(vault_0_amount, vault_1_amount);
+    ctx.accounts.observation_state.update(
+        oracle::block_timestamp(),
+        token_0_price_x32,
+        token_1_price_x32,
+        oracle::liquidity(total_token_0_amount, total_token_1_amount),
+    );
+
+    pool_state.token_0_reserve = ctx.accounts.token_0_vault.amount;
+    pool_state.token_1_reserve = ctx.accounts.token_1_vault.amount;
+    // The balances must still cover the accrued fees
+    require!(
+        pool_state.token_0_reserve
+            > pool_state.protocol_fees_token_0
+                + pool_state.fund_fees_token_0
+                + pool_state.creator_fees_token_0
+            && pool_state.token_1_reserve
+                > pool_state.protocol_fees_token_1
+                    + pool_state.fund_fees_token_1
+                    + pool_state.creator_fees_token_1,
+        ErrorCode::EmptySupply
+    );
+    pool_state.recent_epoch = Clock::get()?.epoch;
+
+    Ok(())
+}
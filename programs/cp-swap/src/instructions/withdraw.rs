@@ -1,5 +1,6 @@
 use crate::curve::CurveCalculator;
 use crate::curve::RoundDirection;
+use crate::curve::SwapCurve;
 use crate::error::ErrorCode;
 use crate::states::*;
 use crate::utils::token::*;
@@ -116,11 +117,19 @@ pub fn withdraw(
     if !pool_state.get_status_by_bit(PoolStatusBitIndex::Withdraw) {
         return err!(ErrorCode::NotApproved);
     }
-    let (total_token_0_amount, total_token_1_amount) = pool_state.vault_amount_without_fee(
+    pool_state.update_weights(oracle::block_timestamp());
+    // Tokens sent to the vaults from outside the pool are left out
+    let (vault_0_amount, vault_1_amount) = pool_state.vault_amounts(
         ctx.accounts.token_0_vault.amount,
         ctx.accounts.token_1_vault.amount,
     );
+    let (total_token_0_amount, total_token_1_amount) =
+        pool_state.vault_amount_without_fee(vault_0_amount, vault_1_amount);
+    let (token_0_price_x32, token_1_price_x32) =
+        pool_state.token_price_x32(vault_0_amount, vault_1_amount);
+    // Every curve converts lp tokens in proportion to the reserves
     let results = CurveCalculator::lp_tokens_to_trading_tokens(
+        &SwapCurve::ConstantProduct,
         u128::from(lp_token_amount),
         u128::from(pool_state.lp_supply),
         u128::from(total_token_0_amount),
@@ -226,6 +235,23 @@ pub fn withdraw(
         ctx.accounts.light_token_cpi_authority.to_account_info(),
         ctx.accounts.system_program.to_account_info(),
     )?;
+    // update the previous price to the observation, when it is passed
+    oracle::update_optional_observation(
+        ctx.remaining_accounts,
+        pool_state.observation_key,
+        oracle::block_timestamp(),
+        token_0_price_x32,
+        token_1_price_x32,
+        oracle::liquidity(total_token_0_amount, total_token_1_amount),
+    )?;
+    pool_state.update_reserve(
+        &ctx.accounts.token_0_vault.to_account_info(),
+        ctx.accounts.token_0_vault.amount,
+    )?;
+    pool_state.update_reserve(
+        &ctx.accounts.token_1_vault.to_account_info(),
+        ctx.accounts.token_1_vault.amount,
+    )?;
     pool_state.recent_epoch = Clock::get()?.epoch;
 
     Ok(())
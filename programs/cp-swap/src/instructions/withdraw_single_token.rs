@@ -0,0 +1,363 @@
+use crate::curve::calculator::CurveCalculator;
+use crate::curve::{Curve, RoundDirection, TradeDirection};
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::utils::token::*;
+use anchor_lang::prelude::*;
+use light_anchor_spl::{
+    token::Token,
+    token_interface::{Mint, Token2022, TokenAccount, TokenInterface},
+};
+use light_token::instruction::BurnCpi;
+
+#[derive(Accounts)]
+pub struct WithdrawSingleToken<'info> {
+    /// Pays to mint the position
+    pub owner: Signer<'info>,
+
+    /// CHECK: pool vault and lp mint authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// The factory state to read protocol fees
+    #[account(address = pool_state.amm_config)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// Pool state account
+    #[account(mut)]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    /// The program account for the most recent oracle observation
+    #[account(mut, address = pool_state.observation_key)]
+    pub observation_state: Box<Account<'info, ObservationState>>,
+
+    #[account(
+        mut,
+        token::authority = owner
+    )]
+    pub owner_lp_token: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The token account that receives the withdrawn token
+    #[account(
+        mut,
+        token::mint = output_vault.mint,
+    )]
+    pub output_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The vault of the token swapped back into the pool
+    pub input_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The vault that pays out the withdrawn token
+    #[account(mut)]
+    pub output_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// token Program
+    pub token_program: Program<'info, Token>,
+
+    /// Token program 2022
+    pub token_program_2022: Program<'info, Token2022>,
+
+    /// The mint of the withdrawn token
+    #[account(
+        address = output_vault.mint
+    )]
+    pub output_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Lp mint
+    #[account(
+        mut,
+        address = pool_state.lp_mint @ ErrorCode::IncorrectLpMint
+    )]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: light-token CPI authority.
+    pub light_token_cpi_authority: AccountInfo<'info>,
+
+    /// Light Token program for CPI
+    pub light_token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn withdraw_single_token(
+    ctx: Context<WithdrawSingleToken>,
+    lp_token_amount: u64,
+    minimum_amount_out: u64,
+) -> Result<()> {
+    require_gt!(lp_token_amount, 0);
+    let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
+    let pool_id = ctx.accounts.pool_state.key();
+    let pool_state = &mut ctx.accounts.pool_state;
+    if !pool_state.get_status_by_bit(PoolStatusBitIndex::Withdraw)
+        || !pool_state.get_status_by_bit(PoolStatusBitIndex::Swap)
+        || block_timestamp < pool_state.open_time
+    {
+        return err!(ErrorCode::NotApproved);
+    }
+    if block_timestamp < pool_state.cooldown_end_time {
+        return err!(ErrorCode::PoolInCooldown);
+    }
+    pool_state.update_weights(block_timestamp);
+    // Tokens sent to the vaults from outside the pool are left out
+    let input_vault_amount = pool_state.vault_amount(
+        &ctx.accounts.input_vault.key(),
+        ctx.accounts.input_vault.amount,
+    );
+    let output_vault_amount = pool_state.vault_amount(
+        &ctx.accounts.output_vault.key(),
+        ctx.accounts.output_vault.amount,
+    );
+
+    // The unwanted side is swapped back into the pool, so it is the swap input
+    let (trade_direction, total_input_token_amount, total_output_token_amount) =
+        if ctx.accounts.input_vault.key() == pool_state.token_0_vault
+            && ctx.accounts.output_vault.key() == pool_state.token_1_vault
+        {
+            let (total_input_token_amount, total_output_token_amount) =
+                pool_state.vault_amount_without_fee(input_vault_amount, output_vault_amount);
+            (
+                TradeDirection::ZeroForOne,
+                total_input_token_amount,
+                total_output_token_amount,
+            )
+        } else if ctx.accounts.input_vault.key() == pool_state.token_1_vault
+            && ctx.accounts.output_vault.key() == pool_state.token_0_vault
+        {
+            let (total_output_token_amount, total_input_token_amount) =
+                pool_state.vault_amount_without_fee(output_vault_amount, input_vault_amount);
+            (
+                TradeDirection::OneForZero,
+                total_input_token_amount,
+                total_output_token_amount,
+            )
+        } else {
+            return err!(ErrorCode::InvalidVault);
+        };
+    let (vault_0_amount, vault_1_amount) =
+        trade_direction.token_amounts(input_vault_amount, output_vault_amount);
+    let (token_0_price_x32, token_1_price_x32) =
+        pool_state.token_price_x32(vault_0_amount, vault_1_amount);
+    let (total_token_0_amount, total_token_1_amount) =
+        trade_direction.token_amounts(total_input_token_amount, total_output_token_amount);
+
+    let curve = pool_state.swap_curve(&ctx.accounts.amm_config)?;
+    let results = CurveCalculator::lp_tokens_to_trading_tokens(
+        &curve,
+        u128::from(lp_token_amount),
+        u128::from(pool_state.lp_supply),
+        u128::from(total_token_0_amount),
+        u128::from(total_token_1_amount),
+        RoundDirection::Floor,
+    )
+    .ok_or(ErrorCode::ZeroTradingTokens)?;
+    if results.token_0_amount == 0 || results.token_1_amount == 0 {
+        return err!(ErrorCode::ZeroTradingTokens);
+    }
+    let token_0_amount = std::cmp::min(
+        total_token_0_amount,
+        u64::try_from(results.token_0_amount).unwrap(),
+    );
+    let token_1_amount = std::cmp::min(
+        total_token_1_amount,
+        u64::try_from(results.token_1_amount).unwrap(),
+    );
+    let (withdrawn_input_amount, withdrawn_output_amount) = match trade_direction {
+        TradeDirection::ZeroForOne => (token_0_amount, token_1_amount),
+        TradeDirection::OneForZero => (token_1_amount, token_0_amount),
+    };
+
+    // Swap the unwanted side back into the pool left after the withdraw
+    let swap_input_token_amount = total_input_token_amount
+        .checked_sub(withdrawn_input_amount)
+        .unwrap();
+    let swap_output_token_amount = total_output_token_amount
+        .checked_sub(withdrawn_output_amount)
+        .unwrap();
+    let trade_fee_rate = ctx.accounts.amm_config.effective_trade_fee_rate(
+        &ctx.accounts.observation_state,
+        token_0_price_x32,
+        pool_state.open_time,
+        block_timestamp,
+    );
+    let result = CurveCalculator::swap_base_input(
+        &curve,
+        trade_direction,
+        u128::from(withdrawn_input_amount),
+        u128::from(swap_input_token_amount),
+        u128::from(swap_output_token_amount),
+        &ctx.accounts.amm_config.fee_rates(trade_fee_rate),
+    )
+    .ok_or(ErrorCode::ZeroTradingTokens)?;
+
+    let (swap_token_0_amount, swap_token_1_amount) =
+        trade_direction.token_amounts(swap_input_token_amount, swap_output_token_amount);
+    let constant_before = curve
+        .invariant(
+            u128::from(swap_token_0_amount),
+            u128::from(swap_token_1_amount),
+        )
+        .unwrap();
+    let (new_swap_token_0_amount, new_swap_token_1_amount) = trade_direction.token_amounts(
+        result
+            .new_swap_source_amount
+            .checked_sub(result.trade_fee)
+            .unwrap(),
+        result.new_swap_destination_amount,
+    );
+    let constant_after = curve
+        .invariant(new_swap_token_0_amount, new_swap_token_1_amount)
+        .unwrap();
+    require_gte!(constant_after, constant_before);
+
+    let amount_out = withdrawn_output_amount
+        .checked_add(u64::try_from(result.destination_amount_swapped).unwrap())
+        .unwrap();
+    let (receive_amount, output_transfer_fee) = {
+        let transfer_fee = get_transfer_fee(
+            &ctx.accounts.output_token_mint.to_account_info(),
+            amount_out,
+        )?;
+        (amount_out.checked_sub(transfer_fee).unwrap(), transfer_fee)
+    };
+
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "token_0_amount:{}, token_1_amount:{}, destination_amount_swapped:{}, receive_amount:{}, output_transfer_fee:{}",
+        token_0_amount,
+        token_1_amount,
+        result.destination_amount_swapped,
+        receive_amount,
+        output_transfer_fee
+    );
+    emit!(LpChangeEvent {
+        pool_id,
+        lp_amount_before: pool_state.lp_supply,
+        token_0_vault_before: total_token_0_amount,
+        token_1_vault_before: total_token_1_amount,
+        token_0_amount,
+        token_1_amount,
+        token_0_transfer_fee: 0,
+        token_1_transfer_fee: 0,
+        change_type: 1
+    });
+    emit!(SwapEvent {
+        pool_id,
+        input_vault_before: swap_input_token_amount,
+        output_vault_before: swap_output_token_amount,
+        input_amount: u64::try_from(result.source_amount_swapped).unwrap(),
+        output_amount: u64::try_from(result.destination_amount_swapped).unwrap(),
+        input_transfer_fee: 0,
+        output_transfer_fee,
+        base_input: true,
+        trade_fee_rate,
+        referral_fee: 0,
+        unfilled_amount: 0,
+    });
+
+    require_gt!(receive_amount, 0);
+    require_gte!(
+        receive_amount,
+        minimum_amount_out,
+        ErrorCode::ExceededSlippage
+    );
+
+    let protocol_fee = u64::try_from(result.protocol_fee).unwrap();
+    let fund_fee = u64::try_from(result.fund_fee).unwrap();
+    let creator_fee = u64::try_from(result.creator_fee).unwrap();
+    match trade_direction {
+        TradeDirection::ZeroForOne => {
+            pool_state.protocol_fees_token_0 = pool_state
+                .protocol_fees_token_0
+                .checked_add(protocol_fee)
+                .unwrap();
+            pool_state.fund_fees_token_0 =
+                pool_state.fund_fees_token_0.checked_add(fund_fee).unwrap();
+            pool_state.creator_fees_token_0 = pool_state
+                .creator_fees_token_0
+                .checked_add(creator_fee)
+                .unwrap();
+        }
+        TradeDirection::OneForZero => {
+            pool_state.protocol_fees_token_1 = pool_state
+                .protocol_fees_token_1
+                .checked_add(protocol_fee)
+                .unwrap();
+            pool_state.fund_fees_token_1 =
+                pool_state.fund_fees_token_1.checked_add(fund_fee).unwrap();
+            pool_state.creator_fees_token_1 = pool_state
+                .creator_fees_token_1
+                .checked_add(creator_fee)
+                .unwrap();
+        }
+    };
+    let (new_vault_0_amount, new_vault_1_amount) = trade_direction.token_amounts(
+        input_vault_amount,
+        output_vault_amount.checked_sub(amount_out).unwrap(),
+    );
+    pool_state.check_price_deviation(
+        &ctx.accounts.amm_config,
+        &ctx.accounts.observation_state,
+        new_vault_0_amount,
+        new_vault_1_amount,
+        block_timestamp,
+    )?;
+
+    BurnCpi {
+        source: ctx.accounts.owner_lp_token.to_account_info(),
+        mint: ctx.accounts.lp_mint.to_account_info(),
+        amount: lp_token_amount,
+        authority: ctx.accounts.owner.to_account_info(),
+        max_top_up: None,
+    }
+    .invoke()?;
+
+    pool_state.lp_supply = pool_state.lp_supply.checked_sub(lp_token_amount).unwrap();
+
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.output_vault.to_account_info(),
+        ctx.accounts.output_token_account.to_account_info(),
+        ctx.accounts.output_token_mint.to_account_info(),
+        if ctx.accounts.output_token_mint.to_account_info().owner == ctx.accounts.token_program.key
+        {
+            ctx.accounts.token_program.to_account_info()
+        } else {
+            ctx.accounts.token_program_2022.to_account_info()
+        },
+        amount_out,
+        &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.light_token_cpi_authority.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    )?;
+
+    ctx.accounts.observation_state.accumulate_swap(
+        trade_direction,
+        u64::try_from(result.source_amount_swapped).unwrap(),
+        u64::try_from(result.destination_amount_swapped).unwrap(),
+        u64::try_from(result.trade_fee).unwrap(),
+    );
+    // update the previous price to the observation
+    ctx.accounts.observation_state.update(
+        oracle::block_timestamp(),
+        token_0_price_x32,
+        token_1_price_x32,
+        oracle::liquidity(total_token_0_amount, total_token_1_amount),
+    );
+    pool_state.update_reserve(
+        &ctx.accounts.input_vault.to_account_info(),
+        ctx.accounts.input_vault.amount,
+    )?;
+    pool_state.update_reserve(
+        &ctx.accounts.output_vault.to_account_info(),
+        ctx.accounts.output_vault.amount,
+    )?;
+    pool_state.recent_epoch = Clock::get()?.epoch;
+
+    Ok(())
+}
@@ -9,7 +9,9 @@ pub mod utils;
 use crate::curve::fees::FEE_RATE_DENOMINATOR_VALUE;
 pub use crate::instructions::initialize::{Initialize, InitializeParams};
 pub use crate::states::{
-    ObservationState, PackedObservationState, PackedPoolState, PoolState, OBSERVATION_SEED,
+    LaunchPool, MultiObservationState, MultiPoolState, ObservationState, PackedLaunchPool,
+    PackedMultiObservationState, PackedMultiPoolState, PackedObservationState, PackedPoolState,
+    PoolState, LAUNCH_POOL_SEED, LAUNCH_POOL_VAULT_SEED, MULTI_POOL_SEED, OBSERVATION_SEED,
     POOL_SEED, POOL_VAULT_SEED,
 };
 use anchor_lang::prelude::*;
@@ -45,6 +47,20 @@ pub fn program_rent_sponsor() -> Pubkey {
     Pubkey::from(PROGRAM_RENT_SPONSOR_DATA.0)
 }
 
+/// Fails once the unix timestamp `deadline` has passed
+pub fn check_deadline(deadline: u64) -> Result<()> {
+    check_deadline_at(deadline, Clock::get()?.unix_timestamp as u64)
+}
+
+fn check_deadline_at(deadline: u64, block_timestamp: u64) -> Result<()> {
+    require_gte!(
+        deadline,
+        block_timestamp,
+        error::ErrorCode::DeadlineExceeded
+    );
+    Ok(())
+}
+
 pub mod admin {
     use super::{pubkey, Pubkey};
     #[cfg(feature = "devnet")]
@@ -82,6 +98,9 @@ pub mod raydium_cp_swap {
     /// * `trade_fee_rate` - Trade fee rate, can be changed.
     /// * `protocol_fee_rate` - The rate of protocol fee within trade fee.
     /// * `fund_fee_rate` - The rate of fund fee within trade fee.
+    /// * `create_pool_fee` - Fee for create a new pool.
+    /// * `curve_type` - The invariant of pools created with this config, 0: constant product, 1: stable, 2: weighted.
+    /// * `amp` - The amplification coefficient of the stable curve, ignored otherwise.
     ///
     pub fn create_amm_config(
         ctx: Context<CreateAmmConfig>,
@@ -90,6 +109,8 @@ pub mod raydium_cp_swap {
         protocol_fee_rate: u64,
         fund_fee_rate: u64,
         create_pool_fee: u64,
+        curve_type: u8,
+        amp: u64,
     ) -> Result<()> {
         assert!(trade_fee_rate < FEE_RATE_DENOMINATOR_VALUE);
         assert!(protocol_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
@@ -102,6 +123,7 @@ pub mod raydium_cp_swap {
             protocol_fee_rate,
             fund_fee_rate,
             create_pool_fee,
+            CurveParams { curve_type, amp },
         )
     }
 
@@ -116,7 +138,16 @@ pub mod raydium_cp_swap {
     /// * `fund_fee_rate`- The new fund fee rate of amm config, be set when `param` is 2
     /// * `new_owner`- The config's new owner, be set when `param` is 3
     /// * `new_fund_owner`- The config's new fund owner, be set when `param` is 4
-    /// * `param`- The value can be 0 | 1 | 2 | 3 | 4, otherwise will report a error
+    /// * `dynamic_fee_max_rate`- The cap of the dynamic trade fee, 0 disables it, be set when `param` is 7
+    /// * `dynamic_fee_decay_period`- The decay of the dynamic trade fee in seconds, be set when `param` is 8
+    /// * `max_price_deviation_rate`- The max price move away from the oracle, 0 disables it, be set when `param` is 9
+    /// * `price_deviation_cooldown`- Seconds swaps are halted after an abnormal move, 0 rejects it instead, be set when `param` is 10
+    /// * `referral_fee_rate`- The referrer share of the protocol fee, be set when `param` is 11
+    /// * `creator_fee_rate`- The pool creator fee rate, be set when `param` is 12
+    /// * `launch_fee_rate`- The trade fee right after a pool opens, 0 disables it, be set when `param` is 13
+    /// * `launch_fee_duration`- The decay of the launch fee in seconds, be set when `param` is 14
+    /// * `launch_fee_decay`- 0 for a linear and 1 for an exponential launch fee decay, be set when `param` is 15
+    /// * `param`- The value can be 0 | 1 | 2 | 3 | 4 | 5 | 6 | 7 | 8 | 9 | 10 | 11 | 12 | 13 | 14 | 15, otherwise will report a error
     ///
     pub fn update_amm_config(ctx: Context<UpdateAmmConfig>, param: u8, value: u64) -> Result<()> {
         instructions::update_amm_config(ctx, param, value)
@@ -133,6 +164,20 @@ pub mod raydium_cp_swap {
         instructions::update_pool_status(ctx, status)
     }
 
+    /// Update the minimum seconds between two oracle observations of a pool
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `update_duration` - The new duration in seconds, must be positive
+    ///
+    pub fn update_observation_duration(
+        ctx: Context<UpdateObservationDuration>,
+        update_duration: u64,
+    ) -> Result<()> {
+        instructions::update_observation_duration(ctx, update_duration)
+    }
+
     /// Collect the protocol fee accrued to the pool
     ///
     /// # Arguments
@@ -165,12 +210,46 @@ pub mod raydium_cp_swap {
         instructions::collect_fund_fee(ctx, amount_0_requested, amount_1_requested)
     }
 
+    /// Collect the creator fee accrued to the pool, signed by the pool creator
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `amount_0_requested` - The maximum amount of token_0 to send, can be 0 to collect fees in only token_1
+    /// * `amount_1_requested` - The maximum amount of token_1 to send, can be 0 to collect fees in only token_0
+    ///
+    pub fn collect_creator_fee(
+        ctx: Context<CollectCreatorFee>,
+        amount_0_requested: u64,
+        amount_1_requested: u64,
+    ) -> Result<()> {
+        instructions::collect_creator_fee(ctx, amount_0_requested, amount_1_requested)
+    }
+
+    /// Set the allowlist of wallets that can swap before open_time, signed by
+    /// the pool creator before the pool opens
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `early_access_root` - Merkle root of the allowlisted wallets and their caps, zero disables early access
+    /// * `early_access_time` - The time allowlisted wallets can start to swap, before open_time
+    ///
+    pub fn set_early_access(
+        ctx: Context<SetEarlyAccess>,
+        early_access_root: [u8; 32],
+        early_access_time: u64,
+    ) -> Result<()> {
+        instructions::set_early_access(ctx, early_access_root, early_access_time)
+    }
+
     /// Creates a pool for the given token pair and the initial price
     ///
     /// # Arguments
     ///
     /// * `ctx`- The context of accounts
-    /// * `params` - Initialize parameters including init_amount_0, init_amount_1, open_time
+    /// * `params` - Initialize parameters including init_amount_0, init_amount_1, open_time, token_0_weight,
+    ///   observation_update_duration and the liquidity bootstrapping schedule
     ///
     pub fn initialize<'info>(
         ctx: Context<'_, '_, '_, 'info, Initialize<'info>>,
@@ -179,11 +258,228 @@ pub mod raydium_cp_swap {
         instructions::initialize(ctx, params)
     }
 
-    /// Deposit lp token to the pool
+    /// Creates a launch pool selling the whole supply of a token along a
+    /// constant product curve with a virtual quote reserve
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `params` - Launch parameters including virtual_quote_reserve and migration_market_cap
+    ///
+    pub fn create_launch_pool<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateLaunchPool<'info>>,
+        params: CreateLaunchPoolParams,
+    ) -> Result<()> {
+        instructions::create_launch_pool(ctx, params)
+    }
+
+    /// Buy the launched token with the quote token, or sell it back, until
+    /// the launch reaches its migration market cap
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `amount_in` -  input amount to transfer, output to DESTINATION is based on the curve
+    /// * `minimum_amount_out` -  Minimum amount of output token, prevents excessive slippage
+    ///
+    pub fn swap_launch_pool(
+        ctx: Context<SwapLaunchPool>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+    ) -> Result<()> {
+        instructions::swap_launch_pool(ctx, amount_in, minimum_amount_out)
+    }
+
+    /// Migrate a launch that reached its market cap into a constant product
+    /// pool, whose liquidity stays locked
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `params` - The proof and bumps to create the pool accounts
+    ///
+    pub fn migrate_launch_pool<'info>(
+        ctx: Context<'_, '_, '_, 'info, MigrateLaunchPool<'info>>,
+        params: MigrateLaunchPoolParams,
+    ) -> Result<()> {
+        instructions::migrate_launch_pool(ctx, params)
+    }
+
+    /// Creates a pool of three to eight tokens with a trade fee per token
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts, the remaining accounts are a mint, vault and creator token account per token
+    /// * `params` - Pool parameters including init_amounts and trade_fee_rates
+    ///
+    pub fn initialize_multi_pool<'info>(
+        ctx: Context<'_, '_, 'info, 'info, InitializeMultiPool<'info>>,
+        params: InitializeMultiPoolParams,
+    ) -> Result<()> {
+        instructions::initialize_multi_pool(ctx, params)
+    }
+
+    /// Swap an exact input between any two tokens of a multi-token pool
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts, the remaining accounts are the pool vaults
+    /// * `amount_in` -  input amount to transfer, output to DESTINATION is based on the curve
+    /// * `minimum_amount_out` -  Minimum amount of output token, prevents excessive slippage
+    ///
+    pub fn swap_multi_pool<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SwapMultiPool<'info>>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+    ) -> Result<()> {
+        instructions::swap_multi_pool(ctx, amount_in, minimum_amount_out)
+    }
+
+    /// Deposit every token of a multi-token pool in proportion to its reserves
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts, the remaining accounts are the pool vaults, mints and owner token accounts
+    /// * `lp_token_amount` - Pool token amount to mint
+    /// * `maximum_token_amounts` -  Maximum amount of each token to deposit, prevents excessive slippage
+    ///
+    pub fn deposit_multi_pool<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DepositMultiPool<'info>>,
+        lp_token_amount: u64,
+        maximum_token_amounts: Vec<u64>,
+    ) -> Result<()> {
+        instructions::deposit_multi_pool(ctx, lp_token_amount, maximum_token_amounts)
+    }
+
+    /// Withdraw every token of a multi-token pool in proportion to its reserves
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts, the remaining accounts are the pool vaults, mints and recipient token accounts
+    /// * `lp_token_amount` - Pool token amount to burn
+    /// * `minimum_token_amounts` -  Minimum amount of each token to receive, prevents excessive slippage
+    ///
+    pub fn withdraw_multi_pool<'info>(
+        ctx: Context<'_, '_, 'info, 'info, WithdrawMultiPool<'info>>,
+        lp_token_amount: u64,
+        minimum_token_amounts: Vec<u64>,
+    ) -> Result<()> {
+        instructions::withdraw_multi_pool(ctx, lp_token_amount, minimum_token_amounts)
+    }
+
+    /// Update multi-token pool status for given value, see `update_pool_status`
     ///
     /// # Arguments
     ///
     /// * `ctx`- The context of accounts
+    /// * `status` - The value of status
+    ///
+    pub fn update_multi_pool_status(ctx: Context<UpdateMultiPoolStatus>, status: u8) -> Result<()> {
+        instructions::update_multi_pool_status(ctx, status)
+    }
+
+    /// Collect the protocol fee accrued to a multi-token pool
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts, the remaining accounts are the pool vaults, mints and recipient token accounts
+    /// * `amounts_requested` - The maximum amount of each token to send, can be 0 to skip a token
+    ///
+    pub fn collect_multi_pool_protocol_fee<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CollectMultiPoolProtocolFee<'info>>,
+        amounts_requested: Vec<u64>,
+    ) -> Result<()> {
+        instructions::collect_multi_pool_protocol_fee(ctx, amounts_requested)
+    }
+
+    /// Collect the fund fee accrued to a multi-token pool
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts, the remaining accounts are the pool vaults, mints and recipient token accounts
+    /// * `amounts_requested` - The maximum amount of each token to send, can be 0 to skip a token
+    ///
+    pub fn collect_multi_pool_fund_fee<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CollectMultiPoolFundFee<'info>>,
+        amounts_requested: Vec<u64>,
+    ) -> Result<()> {
+        instructions::collect_multi_pool_fund_fee(ctx, amounts_requested)
+    }
+
+    /// Send the vault balances above the tracked reserves of a skim mode pool
+    /// to the recipients
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn skim(ctx: Context<Skim>) -> Result<()> {
+        instructions::skim(ctx)
+    }
+
+    /// Adopt the vault balances of a sync mode pool as its reserves
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn sync(ctx: Context<SyncReserves>) -> Result<()> {
+        instructions::sync(ctx)
+    }
+
+    /// Grow the oracle observation ring buffer of a pool, the payer funds the
+    /// extra rent
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `observation_cardinality` - The new number of observations, at most `OBSERVATION_NUM`
+    ///
+    pub fn increase_observation_cardinality(
+        ctx: Context<IncreaseObservationCardinality>,
+        observation_cardinality: u16,
+    ) -> Result<()> {
+        instructions::increase_observation_cardinality(ctx, observation_cardinality)
+    }
+
+    /// Rewrite an observation account of the first layout, two observations
+    /// of the cumulative prices, into the current layout
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn migrate_observation_state(ctx: Context<MigrateObservationState>) -> Result<()> {
+        instructions::migrate_observation_state(ctx)
+    }
+
+    /// Rewrite a pool of the first layout into the current one, as a
+    /// constant product pool with equal weights
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn migrate_pool_state(ctx: Context<MigratePoolState>) -> Result<()> {
+        instructions::migrate_pool_state(ctx)
+    }
+
+    /// End the weight schedule of a liquidity bootstrapping pool once its sale
+    /// ended, pools ending at equal weights become constant product pools
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn graduate(ctx: Context<Graduate>) -> Result<()> {
+        instructions::graduate(ctx)
+    }
+
+    /// Deposit lp token to the pool
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts, the pool observation account can be passed as the remaining account to update the oracle
     /// * `lp_token_amount` - Pool token amount to transfer. token_a and token_b amount are set by the current exchange rate and size of the pool
     /// * `maximum_token_0_amount` -  Maximum token 0 amount to deposit, prevents excessive slippage
     /// * `maximum_token_1_amount` - Maximum token 1 amount to deposit, prevents excessive slippage
@@ -202,11 +498,76 @@ pub mod raydium_cp_swap {
         )
     }
 
-    /// Withdraw lp for token0 and token1
+    /// Deposit lp token to the pool, fails after `deadline`
     ///
     /// # Arguments
     ///
     /// * `ctx`- The context of accounts
+    /// * `lp_token_amount` - Pool token amount to transfer
+    /// * `maximum_token_0_amount` -  Maximum token 0 amount to deposit, prevents excessive slippage
+    /// * `maximum_token_1_amount` - Maximum token 1 amount to deposit, prevents excessive slippage
+    /// * `deadline` - Unix timestamp after which the transaction is rejected
+    ///
+    pub fn deposit_with_deadline(
+        ctx: Context<Deposit>,
+        lp_token_amount: u64,
+        maximum_token_0_amount: u64,
+        maximum_token_1_amount: u64,
+        deadline: u64,
+    ) -> Result<()> {
+        check_deadline(deadline)?;
+        instructions::deposit(
+            ctx,
+            lp_token_amount,
+            maximum_token_0_amount,
+            maximum_token_1_amount,
+        )
+    }
+
+    /// Deposit up to the given token amounts, the lp amount is set by the current reserves
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `maximum_token_0_amount` - Maximum token 0 amount to deposit, before transfer fees
+    /// * `maximum_token_1_amount` - Maximum token 1 amount to deposit, before transfer fees
+    /// * `minimum_lp_token_amount` - Minimum lp token amount to receive, prevents excessive slippage
+    ///
+    pub fn deposit_by_token_amounts(
+        ctx: Context<Deposit>,
+        maximum_token_0_amount: u64,
+        maximum_token_1_amount: u64,
+        minimum_lp_token_amount: u64,
+    ) -> Result<()> {
+        instructions::deposit_by_token_amounts(
+            ctx,
+            maximum_token_0_amount,
+            maximum_token_1_amount,
+            minimum_lp_token_amount,
+        )
+    }
+
+    /// Deposit a single token to the pool, part of it is swapped into the other token first
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `amount_in` - Amount of the input token to transfer, before transfer fees
+    /// * `minimum_lp_token_amount` - Minimum lp token amount to receive, prevents excessive slippage
+    ///
+    pub fn deposit_single_token(
+        ctx: Context<DepositSingleToken>,
+        amount_in: u64,
+        minimum_lp_token_amount: u64,
+    ) -> Result<()> {
+        instructions::deposit_single_token(ctx, amount_in, minimum_lp_token_amount)
+    }
+
+    /// Withdraw lp for token0 and token1
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts, the pool observation account can be passed as the remaining account to update the oracle
     /// * `lp_token_amount` - Amount of pool tokens to burn. User receives an output of token a and b based on the percentage of the pool tokens that are returned.
     /// * `minimum_token_0_amount` -  Minimum amount of token 0 to receive, prevents excessive slippage
     /// * `minimum_token_1_amount` -  Minimum amount of token 1 to receive, prevents excessive slippage
@@ -225,6 +586,48 @@ pub mod raydium_cp_swap {
         )
     }
 
+    /// Withdraw lp for token0 and token1, fails after `deadline`
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `lp_token_amount` - Amount of pool tokens to burn
+    /// * `minimum_token_0_amount` -  Minimum amount of token 0 to receive, prevents excessive slippage
+    /// * `minimum_token_1_amount` -  Minimum amount of token 1 to receive, prevents excessive slippage
+    /// * `deadline` - Unix timestamp after which the transaction is rejected
+    ///
+    pub fn withdraw_with_deadline(
+        ctx: Context<Withdraw>,
+        lp_token_amount: u64,
+        minimum_token_0_amount: u64,
+        minimum_token_1_amount: u64,
+        deadline: u64,
+    ) -> Result<()> {
+        check_deadline(deadline)?;
+        instructions::withdraw(
+            ctx,
+            lp_token_amount,
+            minimum_token_0_amount,
+            minimum_token_1_amount,
+        )
+    }
+
+    /// Withdraw lp for a single token, the other side is swapped back into the pool
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `lp_token_amount` - Amount of pool tokens to burn
+    /// * `minimum_amount_out` - Minimum amount of the output token to receive, prevents excessive slippage
+    ///
+    pub fn withdraw_single_token(
+        ctx: Context<WithdrawSingleToken>,
+        lp_token_amount: u64,
+        minimum_amount_out: u64,
+    ) -> Result<()> {
+        instructions::withdraw_single_token(ctx, lp_token_amount, minimum_amount_out)
+    }
+
     /// Swap the tokens in the pool base input amount
     ///
     /// # Arguments
@@ -233,11 +636,34 @@ pub mod raydium_cp_swap {
     /// * `amount_in` -  input amount to transfer, output to DESTINATION is based on the exchange rate
     /// * `minimum_amount_out` -  Minimum amount of output token, prevents excessive slippage
     ///
-    pub fn swap_base_input(
-        ctx: Context<Swap>,
+    /// An optional referrer token account for the input token can be passed as the first
+    /// remaining account, it receives `referral_fee_rate` of the protocol fee. Any account
+    /// qualifies, including one of the payer, so the share works as a rebate to the trader.
+    ///
+    pub fn swap_base_input<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Swap<'info>>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+    ) -> Result<()> {
+        instructions::swap_base_input(ctx, amount_in, minimum_amount_out)
+    }
+
+    /// Swap the tokens in the pool base input amount, fails after `deadline`
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `amount_in` -  input amount to transfer, output to DESTINATION is based on the exchange rate
+    /// * `minimum_amount_out` -  Minimum amount of output token, prevents excessive slippage
+    /// * `deadline` - Unix timestamp after which the transaction is rejected
+    ///
+    pub fn swap_base_input_with_deadline<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Swap<'info>>,
         amount_in: u64,
         minimum_amount_out: u64,
+        deadline: u64,
     ) -> Result<()> {
+        check_deadline(deadline)?;
         instructions::swap_base_input(ctx, amount_in, minimum_amount_out)
     }
 
@@ -249,7 +675,131 @@ pub mod raydium_cp_swap {
     /// * `max_amount_in` -  input amount prevents excessive slippage
     /// * `amount_out` -  amount of output token
     ///
-    pub fn swap_base_output(ctx: Context<Swap>, max_amount_in: u64, amount_out: u64) -> Result<()> {
+    /// An optional referrer token account for the input token can be passed as the first
+    /// remaining account, it receives `referral_fee_rate` of the protocol fee. Any account
+    /// qualifies, including one of the payer, so the share works as a rebate to the trader.
+    ///
+    pub fn swap_base_output<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Swap<'info>>,
+        max_amount_in: u64,
+        amount_out: u64,
+    ) -> Result<()> {
         instructions::swap_base_output(ctx, max_amount_in, amount_out)
     }
+
+    /// Swap the tokens in the pool base output amount, fails after `deadline`
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `max_amount_in` -  input amount prevents excessive slippage
+    /// * `amount_out` -  amount of output token
+    /// * `deadline` - Unix timestamp after which the transaction is rejected
+    ///
+    pub fn swap_base_output_with_deadline<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Swap<'info>>,
+        max_amount_in: u64,
+        amount_out: u64,
+        deadline: u64,
+    ) -> Result<()> {
+        check_deadline(deadline)?;
+        instructions::swap_base_output(ctx, max_amount_in, amount_out)
+    }
+
+    /// Swap up to `amount_in` of the input token, stopping once the pool price
+    /// reaches the limit, the unfilled remainder stays with the user
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `amount_in` -  maximum input amount to transfer
+    /// * `minimum_amount_out` -  Minimum amount of output token, prevents excessive slippage
+    /// * `price_limit_x32` - Limit of the token_0 price, token_1 per token_0 as a Q32,
+    ///   a floor when selling token_0 and a ceiling when selling token_1
+    ///
+    pub fn swap_with_price_limit(
+        ctx: Context<Swap>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        price_limit_x32: u128,
+    ) -> Result<()> {
+        instructions::swap_with_price_limit(ctx, amount_in, minimum_amount_out, price_limit_x32)
+    }
+
+    /// Swap the tokens in the pool base input amount between early_access_time
+    /// and open_time, for wallets on the pool's allowlist
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `amount_in` -  input amount to transfer, output to DESTINATION is based on the exchange rate
+    /// * `minimum_amount_out` -  Minimum amount of output token, prevents excessive slippage
+    /// * `max_amount_in` - Cap of the payer in the allowlist, on its total input before open_time
+    /// * `proof` - Merkle proof of the payer and its cap in the allowlist
+    ///
+    pub fn swap_base_input_early_access<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SwapEarlyAccess<'info>>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        max_amount_in: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::swap_base_input_early_access(
+            ctx,
+            amount_in,
+            minimum_amount_out,
+            max_amount_in,
+            proof,
+        )
+    }
+
+    /// Swap through an ordered route of pools, passed as remaining accounts
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts, each hop takes `ROUTE_HOP_ACCOUNTS` remaining accounts
+    /// * `amount_in` -  input amount to transfer into the first pool
+    /// * `minimum_amount_out` -  Minimum amount of output token out of the last pool, prevents excessive slippage
+    ///
+    pub fn swap_route<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SwapRoute<'info>>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+    ) -> Result<()> {
+        instructions::swap_route(ctx, amount_in, minimum_amount_out)
+    }
+
+    /// Borrow tokens from the pool and repay them with fees in a callback to the borrower program
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts, the remaining accounts are passed to the callback
+    /// * `amount_0_out` - Amount of token_0 to borrow
+    /// * `amount_1_out` - Amount of token_1 to borrow
+    /// * `data` - Passed through to the `flash_swap_callback` of the borrower program
+    ///
+    pub fn flash_swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, FlashSwap<'info>>,
+        amount_0_out: u64,
+        amount_1_out: u64,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        instructions::flash_swap(ctx, amount_0_out, amount_1_out, data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deadline_passes_until_exceeded() {
+        let now = 1_700_000_000;
+        assert!(check_deadline_at(now + 1, now).is_ok());
+        assert!(check_deadline_at(now, now).is_ok());
+        assert_eq!(
+            check_deadline_at(now, now + 1).unwrap_err(),
+            error::ErrorCode::DeadlineExceeded.into()
+        );
+    }
 }
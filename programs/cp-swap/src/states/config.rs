@@ -1,3 +1,5 @@
+use crate::curve::{FeeRates, Fees, FEE_RATE_DENOMINATOR_VALUE};
+use crate::states::ObservationState;
 use anchor_lang::prelude::*;
 
 pub const AMM_CONFIG_SEED: &str = "amm_config";
@@ -24,10 +26,154 @@ pub struct AmmConfig {
     pub protocol_owner: Pubkey,
     /// Address of the fund fee owner
     pub fund_owner: Pubkey,
+    /// The invariant pools of this config trade against, see `CurveType`
+    pub curve_type: u8,
+    /// Decay of the launch fee, see `LAUNCH_FEE_DECAY_LINEAR` and `LAUNCH_FEE_DECAY_EXPONENTIAL`
+    pub launch_fee_decay: u8,
+    pub padding_u8: [u8; 6],
+    /// Amplification coefficient, only used by the stable curve
+    pub amp: u64,
+    /// Upper bound of the dynamic trade fee, denominated in hundredths of a bip (10^-6).
+    /// Zero disables dynamic fees, otherwise `trade_fee_rate` is the base rate.
+    pub dynamic_fee_max_rate: u64,
+    /// Seconds after the latest observation over which the dynamic fee decays
+    /// back to the base rate
+    pub dynamic_fee_decay_period: u64,
+    /// Max distance of the post swap price from the latest observation,
+    /// denominated in hundredths of a bip (10^-6). Zero disables the check.
+    pub max_price_deviation_rate: u64,
+    /// Seconds swaps are halted for after the price deviated too far. Zero
+    /// rejects the deviating swap instead.
+    pub price_deviation_cooldown: u64,
+    /// The referrer share of the protocol fee, denominated in hundredths of a bip (10^-6)
+    pub referral_fee_rate: u64,
+    /// The pool creator fee, denominated in hundredths of a bip (10^-6)
+    pub creator_fee_rate: u64,
+    /// Trade fee right after a pool opens, denominated in hundredths of a bip (10^-6).
+    /// Zero disables the launch fee.
+    pub launch_fee_rate: u64,
+    /// Seconds after open_time over which the launch fee decays to `trade_fee_rate`
+    pub launch_fee_duration: u64,
     /// padding
-    pub padding: [u64; 16],
+    pub padding: [u64; 6],
 }
 
 impl AmmConfig {
-    pub const LEN: usize = 8 + 1 + 1 + 2 + 4 * 8 + 32 * 2 + 8 * 16;
+    pub const LEN: usize =
+        8 + 1 + 1 + 2 + 4 * 8 + 32 * 2 + 1 + 1 + 6 + 8 + 2 * 8 + 2 * 8 + 2 * 8 + 2 * 8 + 8 * 6;
+
+    /// The fee rates of a swap charged `trade_fee_rate`
+    pub fn fee_rates(&self, trade_fee_rate: u64) -> FeeRates {
+        FeeRates {
+            trade_fee_rate,
+            protocol_fee_rate: self.protocol_fee_rate,
+            fund_fee_rate: self.fund_fee_rate,
+            creator_fee_rate: self.creator_fee_rate,
+        }
+    }
+
+    /// The trade fee rate of a swap, raised in dynamic fee mode by how far the
+    /// spot price moved away from the pool's recent observations, and during
+    /// the launch period of a pool opened at `open_time`
+    pub fn effective_trade_fee_rate(
+        &self,
+        observation_state: &ObservationState,
+        token_0_price_x32: u128,
+        open_time: u64,
+        block_timestamp: u64,
+    ) -> u64 {
+        let launch_fee_rate = Fees::launch_fee_rate(
+            self.trade_fee_rate,
+            self.launch_fee_rate,
+            self.launch_fee_duration,
+            self.launch_fee_decay,
+            block_timestamp.saturating_sub(open_time),
+        );
+        launch_fee_rate.max(self.dynamic_trade_fee_rate(
+            observation_state,
+            token_0_price_x32,
+            block_timestamp,
+        ))
+    }
+
+    fn dynamic_trade_fee_rate(
+        &self,
+        observation_state: &ObservationState,
+        token_0_price_x32: u128,
+        block_timestamp: u64,
+    ) -> u64 {
+        if self.dynamic_fee_max_rate == 0 {
+            return self.trade_fee_rate;
+        }
+        match observation_state.recent_token_0_price_x32() {
+            Some((reference_price_x32, observed_at)) => Fees::dynamic_fee_rate(
+                self.trade_fee_rate,
+                self.dynamic_fee_max_rate,
+                self.dynamic_fee_decay_period,
+                token_0_price_x32,
+                reference_price_x32,
+                block_timestamp.saturating_sub(observed_at),
+            ),
+            None => self.trade_fee_rate,
+        }
+    }
+
+    /// Whether the token_0 price moved further than `max_price_deviation_rate`
+    /// away from the pool's recent observations
+    pub fn price_deviation_exceeded(
+        &self,
+        observation_state: &ObservationState,
+        token_0_price_x32: u128,
+    ) -> bool {
+        if self.max_price_deviation_rate == 0 {
+            return false;
+        }
+        match observation_state.recent_token_0_price_x32() {
+            Some((reference_price_x32, _)) if reference_price_x32 > 0 => {
+                token_0_price_x32
+                    .abs_diff(reference_price_x32)
+                    .saturating_mul(u128::from(FEE_RATE_DENOMINATOR_VALUE))
+                    / reference_price_x32
+                    > u128::from(self.max_price_deviation_rate)
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::states::{oracle::block_timestamp_mock, Q32};
+
+    #[test]
+    fn len_matches_layout() {
+        let amm_config = AmmConfig::default();
+        assert_eq!(8 + amm_config.try_to_vec().unwrap().len(), AmmConfig::LEN);
+        // new fields come out of the padding, the account size never changes
+        assert_eq!(AmmConfig::LEN, 236);
+    }
+
+    #[test]
+    fn price_deviation_against_latest_observation() {
+        let mut observation_state = ObservationState::default();
+        observation_state.initialize(Pubkey::default(), 0);
+        let start = block_timestamp_mock();
+        observation_state.update(start, Q32, Q32, 0);
+        observation_state.update(start + 20, 100 * Q32, Q32 / 100, 0);
+        observation_state.update(start + 40, 100 * Q32, Q32 / 100, 0);
+
+        let mut amm_config = AmmConfig::default();
+        assert!(!amm_config.price_deviation_exceeded(&observation_state, 200 * Q32));
+
+        // 5% band around the observed price of 100
+        amm_config.max_price_deviation_rate = 50_000;
+        assert!(!amm_config.price_deviation_exceeded(&observation_state, 105 * Q32));
+        assert!(!amm_config.price_deviation_exceeded(&observation_state, 95 * Q32));
+        assert!(amm_config.price_deviation_exceeded(&observation_state, 106 * Q32));
+        assert!(amm_config.price_deviation_exceeded(&observation_state, 94 * Q32));
+
+        // nothing to compare against yet
+        assert!(!amm_config.price_deviation_exceeded(&ObservationState::default(), 200 * Q32));
+    }
 }
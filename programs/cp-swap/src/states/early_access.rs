@@ -0,0 +1,19 @@
+use anchor_lang::prelude::*;
+
+pub const EARLY_ACCESS_SEED: &str = "early_access";
+
+/// Tracks what a wallet swapped in during the early access window of a pool
+#[account]
+#[derive(Default, Debug)]
+pub struct EarlyAccessRecord {
+    /// Bump to identify PDA
+    pub bump: u8,
+    pub pool_id: Pubkey,
+    pub owner: Pubkey,
+    /// Total input amount swapped, capped by the wallet's allowlist entry
+    pub amount_in: u64,
+}
+
+impl EarlyAccessRecord {
+    pub const LEN: usize = 8 + 1 + 32 * 2 + 8;
+}
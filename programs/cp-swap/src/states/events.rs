@@ -36,4 +36,28 @@ pub struct SwapEvent {
     pub input_transfer_fee: u64,
     pub output_transfer_fee: u64,
     pub base_input: bool,
+    /// effective trade fee rate of the swap, see `AmmConfig::effective_trade_fee_rate`
+    pub trade_fee_rate: u64,
+    /// part of the protocol fee paid to the referrer, in input token
+    pub referral_fee: u64,
+    /// part of the input amount left unswapped by a price limit
+    pub unfilled_amount: u64,
+}
+
+/// Emitted when flash swap
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct FlashSwapEvent {
+    pub pool_id: Pubkey,
+    /// pool vault sub trade fees
+    pub token_0_vault_before: u64,
+    /// pool vault sub trade fees
+    pub token_1_vault_before: u64,
+    pub amount_0_out: u64,
+    pub amount_1_out: u64,
+    /// repaid on top of the borrowed amounts, as received by the vaults
+    pub amount_0_in: u64,
+    pub amount_1_in: u64,
+    /// effective trade fee rate of the swap, see `AmmConfig::effective_trade_fee_rate`
+    pub trade_fee_rate: u64,
 }
@@ -0,0 +1,88 @@
+use crate::states::Q32;
+use anchor_lang::prelude::*;
+use light_sdk::LightDiscriminator;
+use light_token::anchor::{CompressionInfo, LightAccount};
+
+pub const LAUNCH_POOL_SEED: &str = "launch_pool";
+pub const LAUNCH_POOL_VAULT_SEED: &str = "launch_pool_vault";
+
+/// Sells a token along x * y = k with a virtual quote reserve on top of the
+/// quote tokens actually paid in, until the market cap of the token reaches
+/// `migration_market_cap` and the reserves move into a standard pool
+#[derive(Default, Debug, InitSpace, LightAccount)]
+#[account]
+#[repr(C)]
+pub struct LaunchPool {
+    pub compression_info: Option<CompressionInfo>,
+    pub creator: Pubkey,
+    /// The config of the launch fee and of the pool it migrates into
+    pub amm_config: Pubkey,
+    /// The token being launched
+    pub token_mint: Pubkey,
+    /// The token it is paid with
+    pub quote_mint: Pubkey,
+    pub token_vault: Pubkey,
+    pub quote_vault: Pubkey,
+    /// Quote amount the curve prices with on top of the quote vault
+    pub virtual_quote_reserve: u64,
+    /// Market cap in quote tokens at which buying stops and the launch can migrate
+    pub migration_market_cap: u64,
+    /// The standard pool the reserves migrated into, default until then
+    pub pool_id: Pubkey,
+    pub padding: [u64; 4],
+}
+
+impl LaunchPool {
+    /// Quote reserve of the curve, virtual and paid in
+    pub fn quote_reserve(&self, quote_vault_amount: u64) -> u64 {
+        self.virtual_quote_reserve
+            .checked_add(quote_vault_amount)
+            .unwrap()
+    }
+
+    /// Price of the token in quote tokens as a Q32
+    pub fn token_price_x32(&self, token_vault_amount: u64, quote_vault_amount: u64) -> u128 {
+        u128::from(self.quote_reserve(quote_vault_amount)) * Q32
+            / u128::from(token_vault_amount.max(1))
+    }
+
+    /// Whether the token reached its migration market cap at the given reserves,
+    /// quote_reserve / token_vault * supply >= migration_market_cap without
+    /// rounding the price
+    pub fn migration_reached(
+        &self,
+        token_vault_amount: u64,
+        quote_vault_amount: u64,
+        token_supply: u64,
+    ) -> bool {
+        u128::from(self.quote_reserve(quote_vault_amount)) * u128::from(token_supply)
+            >= u128::from(self.migration_market_cap) * u128::from(token_vault_amount)
+    }
+
+    pub fn is_migrated(&self) -> bool {
+        self.pool_id != Pubkey::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migration_at_market_cap() {
+        let launch_pool = LaunchPool {
+            virtual_quote_reserve: 30_000,
+            migration_market_cap: 400_000,
+            ..Default::default()
+        };
+        // 1_000_000 tokens of which 900_000 are still on sale, at a price of
+        // (30_000 + 60_000) / 900_000 = 0.1 the market cap is 100_000
+        assert_eq!(launch_pool.token_price_x32(900_000, 60_000), Q32 / 10);
+        assert!(!launch_pool.migration_reached(900_000, 60_000, 1_000_000));
+        // 250_000 left at (30_000 + 70_000) / 250_000 = 0.4, exactly the
+        // migration market cap
+        assert!(launch_pool.migration_reached(250_000, 70_000, 1_000_000));
+        assert!(!launch_pool.migration_reached(250_001, 70_000, 1_000_000));
+        assert!(!launch_pool.is_migrated());
+    }
+}
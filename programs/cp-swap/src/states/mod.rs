@@ -9,3 +9,12 @@ pub use events::*;
 
 pub mod oracle;
 pub use oracle::*;
+
+pub mod early_access;
+pub use early_access::*;
+
+pub mod launch_pool;
+pub use launch_pool::*;
+
+pub mod multi_pool;
+pub use multi_pool::*;
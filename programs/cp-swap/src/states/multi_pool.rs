@@ -0,0 +1,249 @@
+use crate::states::{PoolStatusBitIndex, OBSERVATION_UPDATE_DURATION_DEFAULT, Q32};
+use crate::utils::{exp2_x64, log2_x64};
+use anchor_lang::prelude::*;
+use light_sdk::interface::CompressedInitSpace;
+use light_sdk::LightDiscriminator;
+use light_token::anchor::{CompressionInfo, LightAccount};
+
+pub const MULTI_POOL_SEED: &str = "multi_pool";
+/// Most tokens a multi-token pool can hold
+pub const MULTI_POOL_MAX_TOKENS: usize = 8;
+/// Fewest tokens a multi-token pool can hold, two token pools use `PoolState`
+pub const MULTI_POOL_MIN_TOKENS: usize = 3;
+/// Size of the multi-token observation ring buffer
+pub const MULTI_OBSERVATION_NUM: usize = 4;
+
+/// A pool of three or more tokens on the equal weight product invariant
+/// x_0 * x_1 * ... * x_n = k. A swap between two tokens leaves the other
+/// reserves alone, so it is an x * y = k swap between the pair.
+#[derive(Default, Debug, InitSpace, LightAccount)]
+#[account]
+#[repr(C)]
+pub struct MultiPoolState {
+    pub compression_info: Option<CompressionInfo>,
+    /// Which config the pool belongs
+    pub amm_config: Pubkey,
+    /// pool creator
+    pub pool_creator: Pubkey,
+    /// Pool tokens are issued when tokens are deposited into the pool
+    pub lp_mint: Pubkey,
+    /// The observation account of the pool
+    pub observation_key: Pubkey,
+    pub auth_bump: u8,
+    /// Number of tokens in the pool, the rest of the token arrays is unused
+    pub token_count: u8,
+    pub lp_mint_decimals: u8,
+    /// Bitwise representation of the state of the pool, see `PoolState::status`
+    pub status: u8,
+    /// Token vaults in ascending order of their mints
+    pub vaults: [Pubkey; MULTI_POOL_MAX_TOKENS],
+    /// Decimals of each token mint
+    pub mint_decimals: [u8; MULTI_POOL_MAX_TOKENS],
+    /// Trade fee rate charged on the input when selling each token, the fee
+    /// stays in the pool for liquidity providers but for the protocol and
+    /// fund shares of the config
+    pub trade_fee_rates: [u64; MULTI_POOL_MAX_TOKENS],
+    /// Protocol fees owed in each token, held in its vault
+    pub protocol_fees: [u64; MULTI_POOL_MAX_TOKENS],
+    /// Fund fees owed in each token, held in its vault
+    pub fund_fees: [u64; MULTI_POOL_MAX_TOKENS],
+    /// True circulating supply without burns and lock ups
+    pub lp_supply: u64,
+    /// The timestamp allowed for swap in the pool.
+    pub open_time: u64,
+    /// recent epoch
+    pub recent_epoch: u64,
+    pub padding: [u64; 4],
+}
+
+// Compressed accounts can't hold more than 800 bytes
+const _: () = assert!(8 + MultiPoolState::COMPRESSED_INIT_SPACE <= 800);
+
+impl MultiPoolState {
+    pub fn token_count(&self) -> usize {
+        usize::from(self.token_count)
+    }
+
+    pub fn set_status(&mut self, status: u8) {
+        self.status = status;
+    }
+
+    /// True unless the admin disabled the operation, see
+    /// `PoolState::get_status_by_bit`
+    pub fn get_status_by_bit(&self, bit: PoolStatusBitIndex) -> bool {
+        self.status & (1 << (bit as u8)) == 0
+    }
+
+    /// Vault amounts less the protocol and fund fees they hold
+    pub fn vault_amounts_without_fee(&self, vault_amounts: &[u64]) -> Vec<u64> {
+        vault_amounts
+            .iter()
+            .zip(self.protocol_fees.iter().zip(self.fund_fees.iter()))
+            .map(|(amount, (protocol_fee, fund_fee))| {
+                amount.checked_sub(protocol_fee + fund_fee).unwrap()
+            })
+            .collect()
+    }
+
+    /// Price of one whole token in whole token 0 as a Q32, the reserve ratio
+    /// scaled by the mint decimals: price_i = (x_0 / 10^d_0) / (x_i / 10^d_i)
+    pub fn token_prices_x32(&self, vault_amounts: &[u64]) -> [u128; MULTI_POOL_MAX_TOKENS] {
+        let mut prices = [0u128; MULTI_POOL_MAX_TOKENS];
+        let decimals_0 = self.mint_decimals[0];
+        for ((price, amount), decimals) in
+            prices.iter_mut().zip(vault_amounts).zip(self.mint_decimals)
+        {
+            let amount = u128::from(*amount).max(1);
+            let amount_0_x32 = u128::from(vault_amounts[0]) * Q32;
+            *price = if decimals >= decimals_0 {
+                amount_0_x32.saturating_mul(10u128.saturating_pow(u32::from(decimals - decimals_0)))
+                    / amount
+            } else {
+                amount_0_x32
+                    / amount.saturating_mul(10u128.saturating_pow(u32::from(decimals_0 - decimals)))
+            };
+        }
+        prices
+    }
+}
+
+/// LP supply of a new multi-token pool, the geometric mean of its reserves
+pub fn multi_pool_initial_liquidity(vault_amounts: &[u64]) -> Option<u64> {
+    let mut log2_sum_x64 = 0u128;
+    for amount in vault_amounts {
+        log2_sum_x64 = log2_sum_x64.checked_add(log2_x64(u128::from(*amount), 1)?)?;
+    }
+    let liquidity_x64 = exp2_x64(log2_sum_x64 / vault_amounts.len() as u128)?;
+    u64::try_from(liquidity_x64 >> 64).ok()
+}
+
+#[derive(Default, Clone, Copy, AnchorSerialize, AnchorDeserialize, InitSpace, Debug)]
+pub struct MultiObservation {
+    pub block_timestamp: u64,
+    /// Cumulative price of each token in token 0, see
+    /// `MultiPoolState::token_prices_x32`
+    pub cumulative_prices_x32: [u128; MULTI_POOL_MAX_TOKENS],
+}
+
+#[derive(Default, Debug, InitSpace, LightAccount)]
+#[account]
+pub struct MultiObservationState {
+    pub compression_info: Option<CompressionInfo>,
+    pub initialized: bool,
+    pub observation_index: u16,
+    pub pool_id: Pubkey,
+    /// Minimum seconds between two written observations, updates in between
+    /// are skipped
+    pub update_duration: u64,
+    pub observations: [MultiObservation; MULTI_OBSERVATION_NUM],
+}
+
+// Compressed accounts can't hold more than 800 bytes
+const _: () = assert!(8 + MultiObservationState::COMPRESSED_INIT_SPACE <= 800);
+
+impl MultiObservationState {
+    pub fn initialize(&mut self, pool_id: Pubkey) {
+        self.pool_id = pool_id;
+        self.update_duration = OBSERVATION_UPDATE_DURATION_DEFAULT;
+    }
+
+    pub fn update(&mut self, block_timestamp: u64, prices_x32: &[u128; MULTI_POOL_MAX_TOKENS]) {
+        let observation_index = self.observation_index as usize;
+        if !self.initialized {
+            self.initialized = true;
+            self.observations[observation_index] = MultiObservation {
+                block_timestamp,
+                ..Default::default()
+            };
+            return;
+        }
+        let last_observation = self.observations[observation_index];
+        let delta_time = block_timestamp.saturating_sub(last_observation.block_timestamp);
+        if delta_time < self.update_duration.max(1) {
+            return;
+        }
+        let next_observation_index = (observation_index + 1) % MULTI_OBSERVATION_NUM;
+        let next_observation = &mut self.observations[next_observation_index];
+        next_observation.block_timestamp = block_timestamp;
+        for (i, price_x32) in prices_x32.iter().enumerate() {
+            next_observation.cumulative_prices_x32[i] = last_observation.cumulative_prices_x32[i]
+                .wrapping_add(price_x32.checked_mul(delta_time.into()).unwrap());
+        }
+        self.observation_index = next_observation_index as u16;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn geometric_mean_liquidity() {
+        assert_eq!(
+            multi_pool_initial_liquidity(&[1_000_000, 1_000_000, 1_000_000]),
+            Some(999_999)
+        );
+        // (1_000 * 8_000 * 125_000) ^ (1/3) = 10_000, rounded down
+        let liquidity = multi_pool_initial_liquidity(&[1_000, 8_000, 125_000]).unwrap();
+        assert!((9_990..=10_000).contains(&liquidity));
+    }
+
+    #[test]
+    fn status_and_fees() {
+        let mut pool_state = MultiPoolState {
+            token_count: 3,
+            ..Default::default()
+        };
+        assert!(pool_state.get_status_by_bit(PoolStatusBitIndex::Swap));
+        pool_state.set_status(1 << PoolStatusBitIndex::Swap as u8);
+        assert!(!pool_state.get_status_by_bit(PoolStatusBitIndex::Swap));
+        assert!(pool_state.get_status_by_bit(PoolStatusBitIndex::Deposit));
+
+        pool_state.protocol_fees[1] = 30;
+        pool_state.fund_fees[1] = 10;
+        pool_state.fund_fees[2] = 5;
+        assert_eq!(
+            pool_state.vault_amounts_without_fee(&[1_000, 500, 4_000]),
+            vec![1_000, 460, 3_995]
+        );
+    }
+
+    #[test]
+    fn token_prices_scale_by_decimals() {
+        let mut mint_decimals = [0u8; MULTI_POOL_MAX_TOKENS];
+        mint_decimals[..3].copy_from_slice(&[6, 9, 3]);
+        let pool_state = MultiPoolState {
+            token_count: 3,
+            mint_decimals,
+            ..Default::default()
+        };
+        // 1 token 0 = 1 token 1 = 1 token 2
+        let prices = pool_state.token_prices_x32(&[1_000_000, 1_000_000_000, 1_000]);
+        assert_eq!(prices[..3], [Q32, Q32, Q32]);
+        // 2 token 0 for 1 token 1, half a token 0 for 1 token 2
+        let prices = pool_state.token_prices_x32(&[2_000_000, 1_000_000_000, 4_000]);
+        assert_eq!(prices[..3], [Q32, 2 * Q32, Q32 / 2]);
+    }
+
+    #[test]
+    fn observation_accumulates_prices() {
+        let pool_state = MultiPoolState {
+            token_count: 3,
+            ..Default::default()
+        };
+        let prices = pool_state.token_prices_x32(&[1_000, 500, 4_000]);
+        assert_eq!(prices[..3], [Q32, 2 * Q32, Q32 / 4]);
+
+        let mut observation_state = MultiObservationState::default();
+        observation_state.initialize(Pubkey::default());
+        observation_state.update(100, &prices);
+        // skipped, within the update duration
+        observation_state.update(110, &prices);
+        assert_eq!(observation_state.observation_index, 0);
+        observation_state.update(120, &prices);
+        let observation = observation_state.observations[1];
+        assert_eq!(observation.block_timestamp, 120);
+        assert_eq!(observation.cumulative_prices_x32[1], 40 * Q32);
+        assert_eq!(observation.cumulative_prices_x32[2], 5 * Q32);
+    }
+}
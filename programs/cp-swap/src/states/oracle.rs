@@ -1,4 +1,9 @@
+use crate::curve::TradeDirection;
+use crate::error::ErrorCode;
+use crate::states::Q32;
+use crate::utils::{exp2_x64, log2_x64, U128, U256};
 use anchor_lang::prelude::*;
+use light_sdk::interface::CompressedInitSpace;
 use light_sdk::LightDiscriminator;
 use light_token::anchor::{CompressionInfo, LightAccount};
 
@@ -6,7 +11,15 @@ use light_token::anchor::{CompressionInfo, LightAccount};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 pub const OBSERVATION_SEED: &str = "observation";
-pub const OBSERVATION_NUM: usize = 2;
+/// Most observations the ring buffer can grow to. The log2 price and
+/// liquidity accumulators make an observation 56 bytes instead of the 40 of
+/// the two cumulative prices alone, so the 800 byte limit of a compressed
+/// account leaves room for 12 of them rather than 16.
+pub const OBSERVATION_NUM: usize = 12;
+/// Observations the ring buffer of a new pool starts with, see
+/// `increase_observation_cardinality`
+pub const OBSERVATION_CARDINALITY_DEFAULT: usize = 2;
+/// Default minimum seconds between two written observations
 pub const OBSERVATION_UPDATE_DURATION_DEFAULT: u64 = 15;
 
 #[derive(Default, Clone, Copy, AnchorSerialize, AnchorDeserialize, InitSpace, Debug)]
@@ -14,6 +27,13 @@ pub struct Observation {
     pub block_timestamp: u64,
     pub cumulative_token_0_price_x32: u128,
     pub cumulative_token_1_price_x32: u128,
+    /// Cumulative log2 of the token_0 price in Q32.32, the token_1 price is
+    /// its negation. It wraps, differences over less than a year stay exact.
+    /// See `geometric_mean_price_x32`.
+    pub cumulative_log2_token_0_price_x32: i64,
+    /// Cumulative log2 of the liquidity, sqrt(x * y) of the reserves, in
+    /// Q32.32. It wraps like the log2 price. See `geometric_mean_liquidity`.
+    pub cumulative_log2_liquidity_x32: u64,
 }
 
 #[derive(Default, Debug, InitSpace, LightAccount)]
@@ -23,16 +43,67 @@ pub struct ObservationState {
     pub initialized: bool,
     pub observation_index: u16,
     pub pool_id: Pubkey,
-    pub observations: [Observation; OBSERVATION_NUM],
-    pub padding: [u64; 4],
+    /// Minimum seconds between two written observations, updates in between
+    /// are skipped
+    pub update_duration: u64,
+    /// The observation ring buffer, its length is the cardinality
+    #[max_len(OBSERVATION_NUM)]
+    pub observations: Vec<Observation>,
+    /// Swapped amounts of each token, bought and sold, wrapping on overflow.
+    /// Observations don't snapshot the volume and fee counters, readers take
+    /// the difference between two reads of their own.
+    pub cumulative_token_0_volume: u64,
+    pub cumulative_token_1_volume: u64,
+    /// Trade fees paid in each token, wrapping on overflow
+    pub cumulative_token_0_fees: u64,
+    pub cumulative_token_1_fees: u64,
 }
 
+// Compressed accounts can't hold more than 800 bytes, and `OBSERVATION_NUM`
+// is the most observations that fit
+const _: () = assert!(8 + ObservationState::COMPRESSED_INIT_SPACE <= 800);
+const _: () = assert!(8 + ObservationState::COMPRESSED_INIT_SPACE + Observation::INIT_SPACE > 800);
+
 impl ObservationState {
+    /// Account space with room for `cardinality` observations
+    pub fn space(cardinality: usize) -> usize {
+        Self::INIT_SPACE - OBSERVATION_NUM * Observation::INIT_SPACE
+            + cardinality * Observation::INIT_SPACE
+    }
+
+    pub fn initialize(&mut self, pool_id: Pubkey, update_duration: u64) {
+        self.pool_id = pool_id;
+        self.update_duration = update_duration;
+        self.observations = vec![Observation::default(); OBSERVATION_CARDINALITY_DEFAULT];
+    }
+
+    pub fn cardinality(&self) -> usize {
+        self.observations.len()
+    }
+
+    /// Grows the ring buffer to `cardinality` observations. The new slots go
+    /// right after the latest observation, so they are written before the
+    /// oldest observations get overwritten.
+    pub fn increase_cardinality(&mut self, cardinality: usize) -> Result<()> {
+        require!(
+            cardinality > self.cardinality() && cardinality <= OBSERVATION_NUM,
+            ErrorCode::InvalidInput
+        );
+        let next_observation_index = self.observation_index as usize + 1;
+        let new_slots = cardinality - self.cardinality();
+        self.observations.splice(
+            next_observation_index..next_observation_index,
+            vec![Observation::default(); new_slots],
+        );
+        Ok(())
+    }
+
     pub fn update(
         &mut self,
         block_timestamp: u64,
         token_0_price_x32: u128,
         token_1_price_x32: u128,
+        liquidity: u128,
     ) {
         let observation_index = self.observation_index;
 
@@ -41,15 +112,20 @@ impl ObservationState {
             self.observations[observation_index as usize].block_timestamp = block_timestamp;
             self.observations[observation_index as usize].cumulative_token_0_price_x32 = 0;
             self.observations[observation_index as usize].cumulative_token_1_price_x32 = 0;
+            self.observations[observation_index as usize].cumulative_log2_token_0_price_x32 = 0;
+            self.observations[observation_index as usize].cumulative_log2_liquidity_x32 = 0;
         } else {
             let last_observation = self.observations[observation_index as usize];
             let delta_time = block_timestamp.saturating_sub(last_observation.block_timestamp);
-            if delta_time < OBSERVATION_UPDATE_DURATION_DEFAULT {
+            if delta_time < self.update_duration.max(1) {
                 return;
             }
             let delta_token_0_price_x32 = token_0_price_x32.checked_mul(delta_time.into()).unwrap();
             let delta_token_1_price_x32 = token_1_price_x32.checked_mul(delta_time.into()).unwrap();
-            let next_observation_index = if observation_index as usize == OBSERVATION_NUM - 1 {
+            let delta_log2_token_0_price_x32 = log2_price_x32(token_0_price_x32, token_1_price_x32)
+                .wrapping_mul(delta_time as i64);
+            let delta_log2_liquidity_x32 = log2_liquidity_x32(liquidity).wrapping_mul(delta_time);
+            let next_observation_index = if observation_index as usize == self.cardinality() - 1 {
                 0
             } else {
                 observation_index + 1
@@ -63,9 +139,317 @@ impl ObservationState {
                 last_observation
                     .cumulative_token_1_price_x32
                     .wrapping_add(delta_token_1_price_x32);
+            self.observations[next_observation_index as usize].cumulative_log2_token_0_price_x32 =
+                last_observation
+                    .cumulative_log2_token_0_price_x32
+                    .wrapping_add(delta_log2_token_0_price_x32);
+            self.observations[next_observation_index as usize].cumulative_log2_liquidity_x32 =
+                last_observation
+                    .cumulative_log2_liquidity_x32
+                    .wrapping_add(delta_log2_liquidity_x32);
             self.observation_index = next_observation_index;
         }
     }
+
+    /// Adds a swap to the volume and fee counters
+    pub fn accumulate_swap(
+        &mut self,
+        trade_direction: TradeDirection,
+        input_amount: u64,
+        output_amount: u64,
+        trade_fee: u64,
+    ) {
+        let (token_0_volume, token_1_volume) =
+            trade_direction.token_amounts(input_amount, output_amount);
+        self.cumulative_token_0_volume =
+            self.cumulative_token_0_volume.wrapping_add(token_0_volume);
+        self.cumulative_token_1_volume =
+            self.cumulative_token_1_volume.wrapping_add(token_1_volume);
+        match trade_direction {
+            TradeDirection::ZeroForOne => {
+                self.cumulative_token_0_fees = self.cumulative_token_0_fees.wrapping_add(trade_fee)
+            }
+            TradeDirection::OneForZero => {
+                self.cumulative_token_1_fees = self.cumulative_token_1_fees.wrapping_add(trade_fee)
+            }
+        }
+    }
+
+    /// The time weighted token_0 price between the two most recent
+    /// observations, and the timestamp of the latest one
+    pub fn recent_token_0_price_x32(&self) -> Option<(u128, u64)> {
+        if !self.initialized {
+            return None;
+        }
+        let last_observation = self.observations[self.observation_index as usize];
+        let previous_observation_index = if self.observation_index == 0 {
+            self.cardinality() - 1
+        } else {
+            self.observation_index as usize - 1
+        };
+        let previous_observation = self.observations[previous_observation_index];
+        let delta_time = last_observation
+            .block_timestamp
+            .checked_sub(previous_observation.block_timestamp)?;
+        if previous_observation.block_timestamp == 0 || delta_time == 0 {
+            return None;
+        }
+        Some((
+            last_observation
+                .cumulative_token_0_price_x32
+                .wrapping_sub(previous_observation.cumulative_token_0_price_x32)
+                / u128::from(delta_time),
+            last_observation.block_timestamp,
+        ))
+    }
+
+    /// Number of slots not written yet, they always follow the latest
+    /// observation
+    fn unwritten_count(&self) -> usize {
+        let cardinality = self.cardinality();
+        (1..cardinality)
+            .take_while(|i| {
+                self.observations[(self.observation_index as usize + i) % cardinality]
+                    .block_timestamp
+                    == 0
+            })
+            .count()
+    }
+
+    /// Number of written observations
+    fn observation_count(&self) -> usize {
+        if !self.initialized {
+            0
+        } else {
+            self.cardinality() - self.unwritten_count()
+        }
+    }
+
+    /// The i-th written observation, from the oldest to the latest
+    fn observation_at(&self, i: usize) -> Observation {
+        let oldest_index = self.observation_index as usize + 1 + self.unwritten_count();
+        self.observations[(oldest_index + i) % self.cardinality()]
+    }
+
+    /// The cumulative prices at `timestamp`, interpolated between the
+    /// surrounding observations. After the latest observation the current
+    /// prices of the pool are accumulated. None if `timestamp` is older than
+    /// the buffer.
+    pub fn cumulative_price_x32_at(
+        &self,
+        timestamp: u64,
+        token_0_price_x32: u128,
+        token_1_price_x32: u128,
+    ) -> Option<(u128, u128)> {
+        let count = self.observation_count();
+        if count == 0 || timestamp < self.observation_at(0).block_timestamp {
+            return None;
+        }
+        let latest = self.observation_at(count - 1);
+        if timestamp >= latest.block_timestamp {
+            let delta_time = u128::from(timestamp - latest.block_timestamp);
+            return Some((
+                latest
+                    .cumulative_token_0_price_x32
+                    .wrapping_add(token_0_price_x32.checked_mul(delta_time)?),
+                latest
+                    .cumulative_token_1_price_x32
+                    .wrapping_add(token_1_price_x32.checked_mul(delta_time)?),
+            ));
+        }
+        // binary search the last observation at or before timestamp
+        let (mut low, mut high) = (0, count - 1);
+        while high - low > 1 {
+            let mid = (low + high) / 2;
+            if self.observation_at(mid).block_timestamp <= timestamp {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        let before = self.observation_at(low);
+        let after = self.observation_at(high);
+        let interpolate = |cumulative_before: u128, cumulative_after: u128| -> Option<u128> {
+            let delta = U256::from(cumulative_after.wrapping_sub(cumulative_before))
+                .checked_mul(U256::from(timestamp - before.block_timestamp))?
+                .checked_div(U256::from(after.block_timestamp - before.block_timestamp))?;
+            Some(cumulative_before.wrapping_add(u128::try_from(delta).ok()?))
+        };
+        Some((
+            interpolate(
+                before.cumulative_token_0_price_x32,
+                after.cumulative_token_0_price_x32,
+            )?,
+            interpolate(
+                before.cumulative_token_1_price_x32,
+                after.cumulative_token_1_price_x32,
+            )?,
+        ))
+    }
+
+    /// The time weighted average token_0 and token_1 prices between two
+    /// timestamps. `token_0_price_x32` and `token_1_price_x32` are the current
+    /// prices of the pool, used past the latest observation.
+    pub fn twap_price_x32(
+        &self,
+        start_time: u64,
+        end_time: u64,
+        token_0_price_x32: u128,
+        token_1_price_x32: u128,
+    ) -> Option<(u128, u128)> {
+        if end_time <= start_time {
+            return None;
+        }
+        let (start_token_0, start_token_1) =
+            self.cumulative_price_x32_at(start_time, token_0_price_x32, token_1_price_x32)?;
+        let (end_token_0, end_token_1) =
+            self.cumulative_price_x32_at(end_time, token_0_price_x32, token_1_price_x32)?;
+        let delta_time = u128::from(end_time - start_time);
+        Some((
+            end_token_0.wrapping_sub(start_token_0) / delta_time,
+            end_token_1.wrapping_sub(start_token_1) / delta_time,
+        ))
+    }
+}
+
+/// An observation of the first `ObservationState` layout
+#[derive(Default, Clone, Copy, AnchorSerialize, AnchorDeserialize, InitSpace, Debug)]
+pub struct ObservationV0 {
+    pub block_timestamp: u64,
+    pub cumulative_token_0_price_x32: u128,
+    pub cumulative_token_1_price_x32: u128,
+}
+
+/// `ObservationState` as first deployed, with two observations of the
+/// cumulative prices only. Accounts in this layout don't deserialize as
+/// `ObservationState` until `migrate_observation_state` rewrites them.
+#[derive(Default, AnchorSerialize, AnchorDeserialize, InitSpace, Debug)]
+pub struct ObservationStateV0 {
+    pub compression_info: Option<CompressionInfo>,
+    pub initialized: bool,
+    pub observation_index: u16,
+    pub pool_id: Pubkey,
+    pub observations: [ObservationV0; 2],
+    pub padding: [u64; 4],
+}
+
+impl ObservationStateV0 {
+    /// The observation state in the current layout. Only the latest
+    /// observation carries over, the older one lacks the log2 price and
+    /// liquidity accumulators that TWAPs across it would need.
+    pub fn migrate(self) -> ObservationState {
+        let mut observation_state = ObservationState {
+            compression_info: self.compression_info,
+            initialized: self.initialized,
+            ..Default::default()
+        };
+        observation_state.initialize(self.pool_id, OBSERVATION_UPDATE_DURATION_DEFAULT);
+        let latest = self.observations[usize::from(self.observation_index) % 2];
+        observation_state.observations[0] = Observation {
+            block_timestamp: latest.block_timestamp,
+            cumulative_token_0_price_x32: latest.cumulative_token_0_price_x32,
+            cumulative_token_1_price_x32: latest.cumulative_token_1_price_x32,
+            ..Default::default()
+        };
+        observation_state
+    }
+}
+
+/// log2 of the token_0 price in Q32.32, taken from whichever direction of
+/// the price is at least one so both keep their precision
+fn log2_price_x32(token_0_price_x32: u128, token_1_price_x32: u128) -> i64 {
+    if token_0_price_x32 >= Q32 {
+        (log2_x64(token_0_price_x32, Q32).unwrap() >> 32) as i64
+    } else {
+        -((log2_x64(token_1_price_x32.max(Q32), Q32).unwrap() >> 32) as i64)
+    }
+}
+
+/// log2 of the liquidity in Q32.32, an empty pool counts as liquidity one
+fn log2_liquidity_x32(liquidity: u128) -> u64 {
+    (log2_x64(liquidity.max(1), 1).unwrap() >> 32) as u64
+}
+
+/// Converts the difference of two `cumulative_log2_token_0_price_x32` taken
+/// `delta_time` seconds apart into the geometric mean token_0 and token_1
+/// prices over that time. Unlike the arithmetic means, the two are exact
+/// inverses of each other.
+pub fn geometric_mean_price_x32(
+    cumulative_log2_token_0_price_delta_x32: i64,
+    delta_time: u64,
+) -> Option<(u128, u128)> {
+    if delta_time == 0 {
+        return None;
+    }
+    let mean_log2_token_0_price_x32 =
+        i128::from(cumulative_log2_token_0_price_delta_x32) / i128::from(delta_time);
+    // 2^|mean| in Q64.64
+    let price_x64 = exp2_x64(mean_log2_token_0_price_x32.unsigned_abs() << 32)?;
+    let (higher_price_x32, lower_price_x32) = (price_x64 >> 32, (1u128 << 96) / price_x64);
+    if mean_log2_token_0_price_x32 >= 0 {
+        Some((higher_price_x32, lower_price_x32))
+    } else {
+        Some((lower_price_x32, higher_price_x32))
+    }
+}
+
+/// Converts the difference of two `cumulative_log2_liquidity_x32` taken
+/// `delta_time` seconds apart into the geometric mean liquidity over that time
+pub fn geometric_mean_liquidity(
+    cumulative_log2_liquidity_delta_x32: u64,
+    delta_time: u64,
+) -> Option<u128> {
+    if delta_time == 0 {
+        return None;
+    }
+    let mean_log2_liquidity_x32 = u128::from(cumulative_log2_liquidity_delta_x32 / delta_time);
+    Some(exp2_x64(mean_log2_liquidity_x32 << 32)? >> 64)
+}
+
+/// Depth of a pool with the given reserves, sqrt(k) of the constant product
+pub fn liquidity(token_0_amount: u64, token_1_amount: u64) -> u128 {
+    U128::from(token_0_amount)
+        .checked_mul(token_1_amount.into())
+        .unwrap()
+        .integer_sqrt()
+        .as_u128()
+}
+
+/// Writes an observation to the pool's observation account when it is passed
+/// as the first remaining account. Deposits, withdrawals and fee collections
+/// take it this way so their account lists stay the same, without it the
+/// oracle skips them.
+pub fn update_optional_observation(
+    remaining_accounts: &[AccountInfo],
+    observation_key: Pubkey,
+    block_timestamp: u64,
+    token_0_price_x32: u128,
+    token_1_price_x32: u128,
+    liquidity: u128,
+) -> Result<()> {
+    let Some(account_info) = remaining_accounts.first() else {
+        return Ok(());
+    };
+    require_keys_eq!(
+        account_info.key(),
+        observation_key,
+        ErrorCode::InvalidAccountData
+    );
+    require_keys_eq!(
+        *account_info.owner,
+        crate::ID,
+        ErrorCode::InvalidAccountData
+    );
+    let mut observation_state =
+        ObservationState::try_deserialize(&mut &account_info.try_borrow_data()?[..])?;
+    observation_state.update(
+        block_timestamp,
+        token_0_price_x32,
+        token_1_price_x32,
+        liquidity,
+    );
+    observation_state.try_serialize(&mut &mut account_info.try_borrow_mut_data()?[..])?;
+    Ok(())
 }
 
 pub fn block_timestamp() -> u64 {
@@ -79,3 +463,359 @@ pub fn block_timestamp_mock() -> u64 {
         .unwrap()
         .as_secs()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::states::{PoolState, Q32};
+
+    /// An observation state grown to the largest ring buffer
+    fn full_observation_state(update_duration: u64) -> ObservationState {
+        let mut observation_state = ObservationState::default();
+        observation_state.initialize(Pubkey::default(), update_duration);
+        observation_state
+            .increase_cardinality(OBSERVATION_NUM)
+            .unwrap();
+        observation_state
+    }
+
+    /// Observations written every 20s, token_0 price going 1, 2, 3, ...
+    fn observation_state(updates: u64) -> ObservationState {
+        let mut observation_state = full_observation_state(0);
+        let start = block_timestamp_mock();
+        for i in 0..updates {
+            observation_state.update(start + 20 * i, u128::from(i) * Q32, Q32, 0);
+        }
+        observation_state
+    }
+
+    #[test]
+    fn update_skips_within_update_duration() {
+        let mut observation_state = full_observation_state(1);
+        let start = block_timestamp_mock();
+        observation_state.update(start, Q32, Q32, 0);
+        observation_state.update(start, Q32, Q32, 0);
+        observation_state.update(start + 1, Q32, Q32, 0);
+        assert_eq!(observation_state.observation_index, 1);
+
+        observation_state.update_duration = 60;
+        observation_state.update(start + 60, Q32, Q32, 0);
+        assert_eq!(observation_state.observation_index, 1);
+        observation_state.update(start + 61, Q32, Q32, 0);
+        assert_eq!(observation_state.observation_index, 2);
+    }
+
+    #[test]
+    fn geometric_mean_is_consistent_in_both_directions() {
+        let mut observation_state = full_observation_state(0);
+        let start = block_timestamp_mock();
+        observation_state.update(start, 0, 0, 0);
+        // token_0 price 4 for 20s, then 1/4 for 20s
+        observation_state.update(start + 20, 4 * Q32, Q32 / 4, 0);
+        observation_state.update(start + 40, Q32 / 4, 4 * Q32, 0);
+        let observations = observation_state.observations;
+        assert_eq!(
+            geometric_mean_price_x32(
+                observations[1]
+                    .cumulative_log2_token_0_price_x32
+                    .wrapping_sub(observations[0].cumulative_log2_token_0_price_x32),
+                20
+            ),
+            Some((4 * Q32, Q32 / 4))
+        );
+        assert_eq!(
+            geometric_mean_price_x32(
+                observations[2]
+                    .cumulative_log2_token_0_price_x32
+                    .wrapping_sub(observations[1].cumulative_log2_token_0_price_x32),
+                20
+            ),
+            Some((Q32 / 4, 4 * Q32))
+        );
+        // the geometric mean of 4 and 1/4 is 1, the arithmetic one is 2.125
+        assert_eq!(
+            geometric_mean_price_x32(
+                observations[2]
+                    .cumulative_log2_token_0_price_x32
+                    .wrapping_sub(observations[0].cumulative_log2_token_0_price_x32),
+                40
+            ),
+            Some((Q32, Q32))
+        );
+    }
+
+    #[test]
+    fn log2_accumulators_wrap() {
+        let mut observation_state = full_observation_state(0);
+        let start = block_timestamp_mock();
+        observation_state.update(start, 0, 0, 0);
+        observation_state.observations[0].cumulative_log2_token_0_price_x32 = i64::MAX;
+        observation_state.observations[0].cumulative_log2_liquidity_x32 = u64::MAX;
+        observation_state.update(start + 20, 4 * Q32, Q32 / 4, 1 << 20);
+        let observations = &observation_state.observations;
+        assert_eq!(
+            geometric_mean_price_x32(
+                observations[1]
+                    .cumulative_log2_token_0_price_x32
+                    .wrapping_sub(observations[0].cumulative_log2_token_0_price_x32),
+                20
+            ),
+            Some((4 * Q32, Q32 / 4))
+        );
+        assert_eq!(
+            geometric_mean_liquidity(
+                observations[1]
+                    .cumulative_log2_liquidity_x32
+                    .wrapping_sub(observations[0].cumulative_log2_liquidity_x32),
+                20
+            ),
+            Some(1 << 20)
+        );
+    }
+
+    #[test]
+    fn accumulates_across_mixed_instructions() {
+        let mut pool_state = PoolState {
+            token_0_weight: 500_000,
+            token_1_weight: 500_000,
+            ..Default::default()
+        };
+        let mut observation_state = full_observation_state(OBSERVATION_UPDATE_DURATION_DEFAULT);
+        let start = block_timestamp_mock();
+        // every instruction records the price before it changes the reserves
+        let record = |observation_state: &mut ObservationState,
+                      pool_state: &PoolState,
+                      seconds: u64,
+                      vault_0: u64,
+                      vault_1: u64| {
+            let (token_0_price_x32, token_1_price_x32) =
+                pool_state.token_price_x32(vault_0, vault_1);
+            let (token_0_amount, token_1_amount) =
+                pool_state.vault_amount_without_fee(vault_0, vault_1);
+            observation_state.update(
+                start + seconds,
+                token_0_price_x32,
+                token_1_price_x32,
+                liquidity(token_0_amount, token_1_amount),
+            );
+        };
+        // initialize at 1:1
+        record(&mut observation_state, &pool_state, 0, 1_000_000, 1_000_000);
+        // swap at 20s moves the token_0 price to 1/4
+        observation_state.accumulate_swap(TradeDirection::ZeroForOne, 1_000_000, 500_000, 2_500);
+        record(
+            &mut observation_state,
+            &pool_state,
+            20,
+            1_000_000,
+            1_000_000,
+        );
+        // deposit at 40s doubles the reserves
+        record(&mut observation_state, &pool_state, 40, 2_000_000, 500_000);
+        // withdraw at 45s halves them again, too soon for a new observation
+        record(
+            &mut observation_state,
+            &pool_state,
+            45,
+            4_000_000,
+            1_000_000,
+        );
+        // protocol fees of a swap are collected at 70s, they never count as reserves
+        pool_state.protocol_fees_token_0 = 1_000;
+        record(&mut observation_state, &pool_state, 70, 2_001_000, 500_000);
+        pool_state.protocol_fees_token_0 = 0;
+        // swap at 100s
+        observation_state.accumulate_swap(TradeDirection::OneForZero, 100, 400, 1);
+        record(&mut observation_state, &pool_state, 100, 2_000_000, 500_000);
+
+        assert_eq!(observation_state.observation_index, 4);
+        let latest = observation_state.observations[4];
+        assert_eq!(latest.block_timestamp, start + 100);
+        // 1 for 20s, then 1/4 for 80s
+        assert_eq!(latest.cumulative_token_0_price_x32, 40 * Q32);
+        assert_eq!(latest.cumulative_token_1_price_x32, 340 * Q32);
+        assert_eq!(latest.cumulative_log2_token_0_price_x32, -160 << 32);
+        assert_eq!(
+            observation_state.twap_price_x32(start + 20, start + 100, 0, 0),
+            Some((Q32 / 4, 4 * Q32))
+        );
+        // sqrt(k) stays at 1_000_000 apart from the skipped withdraw, the
+        // log2 rounding loses less than a unit in a million
+        let mean_liquidity = geometric_mean_liquidity(
+            latest
+                .cumulative_log2_liquidity_x32
+                .wrapping_sub(observation_state.observations[0].cumulative_log2_liquidity_x32),
+            100,
+        )
+        .unwrap();
+        assert!((999_999..=1_000_000).contains(&mean_liquidity));
+        assert_eq!(observation_state.cumulative_token_0_volume, 1_000_400);
+        assert_eq!(observation_state.cumulative_token_1_volume, 500_100);
+        assert_eq!(observation_state.cumulative_token_0_fees, 2_500);
+        assert_eq!(observation_state.cumulative_token_1_fees, 1);
+    }
+
+    #[test]
+    fn twap_interpolates_between_observations() {
+        let observation_state = observation_state(5);
+        let start = observation_state.observations[0].block_timestamp;
+        // prices 1 over [0, 20], 2 over [20, 40], 3 over [40, 60]
+        assert_eq!(
+            observation_state.twap_price_x32(start, start + 20, 0, 0),
+            Some((Q32, Q32))
+        );
+        assert_eq!(
+            observation_state.twap_price_x32(start + 10, start + 50, 0, 0),
+            Some((2 * Q32, Q32))
+        );
+        assert_eq!(
+            observation_state.twap_price_x32(start + 30, start + 40, 0, 0),
+            Some((2 * Q32, Q32))
+        );
+    }
+
+    #[test]
+    fn twap_extrapolates_with_current_price() {
+        let observation_state = observation_state(3);
+        let start = observation_state.observations[0].block_timestamp;
+        // latest observation at 40, current price 5 afterwards
+        assert_eq!(
+            observation_state.twap_price_x32(start + 20, start + 60, 5 * Q32, Q32),
+            Some((((2 * 20 + 5 * 20) * Q32) / 40, Q32))
+        );
+    }
+
+    #[test]
+    fn twap_after_ring_buffer_wraps() {
+        let updates = OBSERVATION_NUM as u64 + 5;
+        let observation_state = observation_state(updates);
+        let latest = observation_state.observations[observation_state.observation_index as usize]
+            .block_timestamp;
+        let oldest = latest - 20 * (OBSERVATION_NUM as u64 - 1);
+        // the price over the interval ending at update i is i
+        assert_eq!(
+            observation_state.twap_price_x32(latest - 20, latest, 0, 0),
+            Some((u128::from(updates - 1) * Q32, Q32))
+        );
+        assert!(observation_state
+            .twap_price_x32(oldest, latest, 0, 0)
+            .is_some());
+        assert_eq!(
+            observation_state.twap_price_x32(oldest - 1, latest, 0, 0),
+            None
+        );
+    }
+
+    #[test]
+    fn migrate_first_layout() {
+        let start = block_timestamp_mock();
+        let pool_id = Pubkey::new_unique();
+        let observation_state_v0 = ObservationStateV0 {
+            compression_info: Some(CompressionInfo::default()),
+            initialized: true,
+            observation_index: 1,
+            pool_id,
+            observations: [
+                ObservationV0 {
+                    block_timestamp: start,
+                    cumulative_token_0_price_x32: Q32,
+                    cumulative_token_1_price_x32: Q32,
+                },
+                ObservationV0 {
+                    block_timestamp: start + 20,
+                    cumulative_token_0_price_x32: 21 * Q32,
+                    cumulative_token_1_price_x32: 11 * Q32,
+                },
+            ],
+            ..Default::default()
+        };
+        let data = observation_state_v0.try_to_vec().unwrap();
+        assert_eq!(data.len(), ObservationStateV0::INIT_SPACE);
+
+        let mut observation_state = ObservationStateV0::deserialize(&mut data.as_slice())
+            .unwrap()
+            .migrate();
+        assert_eq!(observation_state.pool_id, pool_id);
+        assert_eq!(
+            observation_state.cardinality(),
+            OBSERVATION_CARDINALITY_DEFAULT
+        );
+        assert_eq!(
+            observation_state.try_to_vec().unwrap().len(),
+            ObservationState::space(OBSERVATION_CARDINALITY_DEFAULT)
+        );
+        // the cumulative prices carry on from the latest observation
+        observation_state.update(start + 40, 2 * Q32, Q32, 0);
+        assert_eq!(
+            observation_state.twap_price_x32(start + 20, start + 40, 0, 0),
+            Some((2 * Q32, Q32))
+        );
+        assert_eq!(
+            observation_state.observations[1].cumulative_token_0_price_x32,
+            61 * Q32
+        );
+    }
+
+    #[test]
+    fn twap_across_grown_buffer() {
+        let mut observation_state = ObservationState::default();
+        observation_state.initialize(Pubkey::default(), 0);
+        let start = block_timestamp_mock();
+        // the price over the interval ending at update i is i
+        let update = |observation_state: &mut ObservationState, i: u64| {
+            observation_state.update(start + 20 * i, u128::from(i) * Q32, Q32, 0)
+        };
+        for i in 0..3 {
+            update(&mut observation_state, i);
+        }
+        // two slots only keep [20, 40]
+        assert_eq!(
+            observation_state.twap_price_x32(start + 20, start + 40, 0, 0),
+            Some((2 * Q32, Q32))
+        );
+        assert_eq!(
+            observation_state.twap_price_x32(start, start + 40, 0, 0),
+            None
+        );
+
+        observation_state
+            .increase_cardinality(OBSERVATION_NUM)
+            .unwrap();
+        assert_eq!(observation_state.cardinality(), OBSERVATION_NUM);
+        assert!(observation_state
+            .increase_cardinality(OBSERVATION_NUM)
+            .is_err());
+        // the grown buffer keeps the old observations
+        assert_eq!(
+            observation_state.twap_price_x32(start + 20, start + 40, 0, 0),
+            Some((2 * Q32, Q32))
+        );
+
+        // the new slots fill up before the oldest observation is overwritten
+        for i in 3..=OBSERVATION_NUM as u64 {
+            update(&mut observation_state, i);
+        }
+        let latest = start + 20 * OBSERVATION_NUM as u64;
+        assert_eq!(
+            observation_state.twap_price_x32(start + 20, latest, 0, 0),
+            Some((
+                u128::from((2..=OBSERVATION_NUM as u64).sum::<u64>()) * Q32
+                    / (OBSERVATION_NUM as u128 - 1),
+                Q32
+            ))
+        );
+        update(&mut observation_state, OBSERVATION_NUM as u64 + 1);
+        assert_eq!(
+            observation_state.twap_price_x32(start + 20, latest, 0, 0),
+            None
+        );
+        assert_eq!(
+            observation_state.twap_price_x32(start + 40, latest + 20, 0, 0),
+            Some((
+                u128::from((3..=OBSERVATION_NUM as u64 + 1).sum::<u64>()) * Q32
+                    / (OBSERVATION_NUM as u128 - 1),
+                Q32
+            ))
+        );
+    }
+}
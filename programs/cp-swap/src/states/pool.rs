@@ -1,7 +1,12 @@
+use crate::curve::{CurveType, SwapCurve, WEIGHT_DENOMINATOR};
+use crate::error::ErrorCode;
+use crate::states::{AmmConfig, ObservationState};
 use anchor_lang::prelude::*;
 use light_anchor_spl::token_interface::Mint;
+use light_sdk::interface::CompressedInitSpace;
 use light_sdk::LightDiscriminator;
 use light_token::anchor::{CompressionInfo, LightAccount};
+use light_token::utils::get_token_account_balance;
 use std::ops::{BitAnd, BitOr, BitXor};
 
 pub const POOL_SEED: &str = "pool";
@@ -10,6 +15,16 @@ pub const POOL_VAULT_SEED: &str = "pool_vault";
 
 pub const Q32: u128 = (u32::MAX as u128) + 1;
 
+/// The pool prices with the live vault balances, tokens sent to a vault
+/// directly move the price
+pub const RESERVE_MODE_LIVE: u8 = 0;
+/// The pool prices with its tracked reserves, tokens sent to a vault directly
+/// are excess anyone can `skim`
+pub const RESERVE_MODE_SKIM: u8 = 1;
+/// The pool prices with its tracked reserves until anyone `sync`s them to the
+/// vault balances, for tokens whose balances rebase
+pub const RESERVE_MODE_SYNC: u8 = 2;
+
 pub enum PoolStatusBitIndex {
     Deposit,
     Withdraw,
@@ -49,9 +64,44 @@ pub struct PoolState {
     pub fund_fees_token_1: u64,
     pub open_time: u64,
     pub recent_epoch: u64,
-    pub padding: [u64; 1],
+    /// Weight of token_0 for weighted pools, denominated in `WEIGHT_DENOMINATOR`
+    pub token_0_weight: u64,
+    /// Weight of token_1 for weighted pools, denominated in `WEIGHT_DENOMINATOR`
+    pub token_1_weight: u64,
+    /// Swaps are halted until this timestamp after the price deviated too far
+    /// from the oracle, see `AmmConfig::price_deviation_cooldown`
+    pub cooldown_end_time: u64,
+    /// Creator fees accrued in token_0, see `AmmConfig::creator_fee_rate`
+    pub creator_fees_token_0: u64,
+    /// Creator fees accrued in token_1, see `AmmConfig::creator_fee_rate`
+    pub creator_fees_token_1: u64,
+    /// Merkle root of the wallets allowed to swap before open_time, with
+    /// their caps, see `merkle_leaf`
+    pub early_access_root: [u8; 32],
+    /// Allowlisted wallets can swap from this timestamp until open_time
+    pub early_access_time: u64,
+    /// The token_0 weight of a liquidity bootstrapping pool moves linearly
+    /// from `lbp_start_weight_0` to `lbp_end_weight_0` between these times
+    pub lbp_start_time: u64,
+    /// Zero unless the pool is a liquidity bootstrapping pool that has not
+    /// graduated yet
+    pub lbp_end_time: u64,
+    pub lbp_start_weight_0: u64,
+    pub lbp_end_weight_0: u64,
+    /// The invariant this pool trades against, see `CurveType`
+    pub curve_type: u8,
+    /// How the pool reads its reserves, see `RESERVE_MODE_LIVE`
+    pub reserve_mode: u8,
+    pub padding: [u8; 6],
+    /// Balance of token_0_vault the pool accounts for, accrued fees included
+    pub token_0_reserve: u64,
+    /// Balance of token_1_vault the pool accounts for, accrued fees included
+    pub token_1_reserve: u64,
 }
 
+// Compressed accounts can't hold more than 800 bytes
+const _: () = assert!(8 + PoolState::COMPRESSED_INIT_SPACE <= 800);
+
 impl PoolState {
     pub fn initialize(
         &mut self,
@@ -66,7 +116,9 @@ impl PoolState {
         token_1_mint: &InterfaceAccount<Mint>,
         lp_mint: &AccountInfo,
         observation_key: Pubkey,
+        curve: &SwapCurve,
     ) {
+        let (token_0_weight, token_1_weight) = curve.weights();
         self.amm_config = amm_config.key();
         self.pool_creator = pool_creator.key();
         self.token_0_vault = token_0_vault;
@@ -88,7 +140,20 @@ impl PoolState {
         self.fund_fees_token_1 = 0;
         self.open_time = open_time;
         self.recent_epoch = Clock::get().unwrap().epoch;
-        self.padding = [0u64; 1];
+        self.token_0_weight = token_0_weight;
+        self.token_1_weight = token_1_weight;
+        self.cooldown_end_time = 0;
+        self.creator_fees_token_0 = 0;
+        self.creator_fees_token_1 = 0;
+        self.early_access_root = [0u8; 32];
+        self.early_access_time = 0;
+        self.lbp_start_time = 0;
+        self.lbp_end_time = 0;
+        self.lbp_start_weight_0 = 0;
+        self.lbp_end_weight_0 = 0;
+        self.curve_type = curve.curve_type() as u8;
+        self.reserve_mode = RESERVE_MODE_LIVE;
+        self.padding = [0u8; 6];
     }
 
     pub fn set_status(&mut self, status: u8) {
@@ -113,26 +178,359 @@ impl PoolState {
     pub fn vault_amount_without_fee(&self, vault_0: u64, vault_1: u64) -> (u64, u64) {
         (
             vault_0
-                .checked_sub(self.protocol_fees_token_0 + self.fund_fees_token_0)
+                .checked_sub(
+                    self.protocol_fees_token_0 + self.fund_fees_token_0 + self.creator_fees_token_0,
+                )
                 .unwrap(),
             vault_1
-                .checked_sub(self.protocol_fees_token_1 + self.fund_fees_token_1)
+                .checked_sub(
+                    self.protocol_fees_token_1 + self.fund_fees_token_1 + self.creator_fees_token_1,
+                )
                 .unwrap(),
         )
     }
 
+    /// Balance of one of the pool vaults the pool accounts for: the live
+    /// balance, or in the tracked modes the tracked reserve capped by it
+    pub fn vault_amount(&self, vault: &Pubkey, vault_amount: u64) -> u64 {
+        let reserve = if *vault == self.token_0_vault {
+            self.token_0_reserve
+        } else {
+            self.token_1_reserve
+        };
+        if self.reserve_mode == RESERVE_MODE_LIVE {
+            vault_amount
+        } else {
+            reserve.min(vault_amount)
+        }
+    }
+
+    /// `vault_amount` of both vaults
+    pub fn vault_amounts(&self, vault_0: u64, vault_1: u64) -> (u64, u64) {
+        (
+            self.vault_amount(&self.token_0_vault, vault_0),
+            self.vault_amount(&self.token_1_vault, vault_1),
+        )
+    }
+
+    /// Moves the tracked reserve of a vault by the change of its balance since
+    /// `vault_amount_before`, so tokens sent to the vault from outside the
+    /// pool stay excess
+    pub fn update_reserve(&mut self, vault: &AccountInfo, vault_amount_before: u64) -> Result<()> {
+        let vault_amount_after =
+            get_token_account_balance(vault).map_err(|_| ErrorCode::InvalidAccountData)?;
+        let reserve = (i128::from(self.vault_amount(vault.key, vault_amount_before))
+            + i128::from(vault_amount_after)
+            - i128::from(vault_amount_before))
+        .clamp(0, i128::from(vault_amount_after)) as u64;
+        if *vault.key == self.token_0_vault {
+            self.token_0_reserve = reserve;
+        } else {
+            self.token_1_reserve = reserve;
+        }
+        Ok(())
+    }
+
+    pub fn swap_curve(&self, amm_config: &AmmConfig) -> Result<SwapCurve> {
+        SwapCurve::new(
+            self.curve_type,
+            amm_config.amp,
+            self.token_0_weight,
+            self.token_1_weight,
+        )
+    }
+
+    /// Whether the pool is a liquidity bootstrapping pool that has not graduated
+    pub fn is_lbp(&self) -> bool {
+        self.lbp_end_time != 0
+    }
+
+    /// Moves the weights of a liquidity bootstrapping pool along its schedule
+    pub fn update_weights(&mut self, block_timestamp: u64) {
+        if !self.is_lbp() {
+            return;
+        }
+        let elapsed = u128::from(
+            block_timestamp.clamp(self.lbp_start_time, self.lbp_end_time) - self.lbp_start_time,
+        );
+        let duration = u128::from(self.lbp_end_time - self.lbp_start_time);
+        let (start, end) = (
+            u128::from(self.lbp_start_weight_0),
+            u128::from(self.lbp_end_weight_0),
+        );
+        let token_0_weight = if end >= start {
+            start + (end - start) * elapsed / duration
+        } else {
+            start - (start - end) * elapsed / duration
+        };
+        self.token_0_weight = token_0_weight as u64;
+        self.token_1_weight = WEIGHT_DENOMINATOR - self.token_0_weight;
+    }
+
+    /// Ends the schedule of a liquidity bootstrapping pool at its final
+    /// weights. At equal weights the weighted invariant is x * y = k, so such
+    /// pools become constant product pools, the others stay weighted.
+    pub fn graduate(&mut self, block_timestamp: u64) {
+        self.update_weights(block_timestamp);
+        if self.token_0_weight == self.token_1_weight {
+            self.curve_type = CurveType::ConstantProduct as u8;
+        }
+        self.lbp_start_time = 0;
+        self.lbp_end_time = 0;
+        self.lbp_start_weight_0 = 0;
+        self.lbp_end_weight_0 = 0;
+    }
+
+    /// Rejects the swap, or halts the swaps after it, when the pool price at
+    /// the given vault amounts deviates too far from the oracle
+    pub fn check_price_deviation(
+        &mut self,
+        amm_config: &AmmConfig,
+        observation_state: &ObservationState,
+        vault_0: u64,
+        vault_1: u64,
+        block_timestamp: u64,
+    ) -> Result<()> {
+        let (token_0_price_x32, _) = self.token_price_x32(vault_0, vault_1);
+        if amm_config.price_deviation_exceeded(observation_state, token_0_price_x32) {
+            // A rejected swap can't persist state, so the cooldown lets this one
+            // through and halts the ones after it
+            if amm_config.price_deviation_cooldown == 0 {
+                return err!(ErrorCode::PriceDeviationExceeded);
+            }
+            self.cooldown_end_time = block_timestamp
+                .checked_add(amm_config.price_deviation_cooldown)
+                .unwrap();
+        }
+        Ok(())
+    }
+
+    /// Spot prices of the pool, for weighted pools the reserve ratio is scaled
+    /// by the weight ratio: price_0 = (y / w_1) / (x / w_0)
     pub fn token_price_x32(&self, vault_0: u64, vault_1: u64) -> (u128, u128) {
         let (token_0_amount, token_1_amount) = self.vault_amount_without_fee(vault_0, vault_1);
         (
-            token_1_amount as u128 * Q32 as u128 / token_0_amount as u128,
-            token_0_amount as u128 * Q32 as u128 / token_1_amount as u128,
+            token_1_amount as u128 * Q32 as u128 * self.token_0_weight as u128
+                / (token_0_amount as u128 * self.token_1_weight as u128),
+            token_0_amount as u128 * Q32 as u128 * self.token_1_weight as u128
+                / (token_1_amount as u128 * self.token_0_weight as u128),
         )
     }
 }
 
+/// `PoolState` as first deployed, before curves and weights. Pools
+/// in this layout don't deserialize as `PoolState` until
+/// `migrate_pool_state` rewrites them.
+#[derive(Default, AnchorSerialize, AnchorDeserialize, InitSpace, Debug)]
+pub struct PoolStateV0 {
+    pub compression_info: Option<CompressionInfo>,
+    pub amm_config: Pubkey,
+    pub pool_creator: Pubkey,
+    pub token_0_vault: Pubkey,
+    pub token_1_vault: Pubkey,
+    pub lp_mint: Pubkey,
+    pub token_0_mint: Pubkey,
+    pub token_1_mint: Pubkey,
+    pub token_0_program: Pubkey,
+    pub token_1_program: Pubkey,
+    pub observation_key: Pubkey,
+    pub auth_bump: u8,
+    pub status: u8,
+    pub lp_mint_decimals: u8,
+    pub mint_0_decimals: u8,
+    pub mint_1_decimals: u8,
+    pub lp_supply: u64,
+    pub protocol_fees_token_0: u64,
+    pub protocol_fees_token_1: u64,
+    pub fund_fees_token_0: u64,
+    pub fund_fees_token_1: u64,
+    pub open_time: u64,
+    pub recent_epoch: u64,
+    pub padding: [u64; 1],
+}
+
+impl PoolStateV0 {
+    /// The pool state in the current layout, a constant product pool with
+    /// equal weights pricing with its live vault balances, as it traded before
+    pub fn migrate(self) -> PoolState {
+        PoolState {
+            compression_info: self.compression_info,
+            amm_config: self.amm_config,
+            pool_creator: self.pool_creator,
+            token_0_vault: self.token_0_vault,
+            token_1_vault: self.token_1_vault,
+            lp_mint: self.lp_mint,
+            token_0_mint: self.token_0_mint,
+            token_1_mint: self.token_1_mint,
+            token_0_program: self.token_0_program,
+            token_1_program: self.token_1_program,
+            observation_key: self.observation_key,
+            auth_bump: self.auth_bump,
+            status: self.status,
+            lp_mint_decimals: self.lp_mint_decimals,
+            mint_0_decimals: self.mint_0_decimals,
+            mint_1_decimals: self.mint_1_decimals,
+            lp_supply: self.lp_supply,
+            protocol_fees_token_0: self.protocol_fees_token_0,
+            protocol_fees_token_1: self.protocol_fees_token_1,
+            fund_fees_token_0: self.fund_fees_token_0,
+            fund_fees_token_1: self.fund_fees_token_1,
+            open_time: self.open_time,
+            recent_epoch: self.recent_epoch,
+            token_0_weight: WEIGHT_DENOMINATOR / 2,
+            token_1_weight: WEIGHT_DENOMINATOR / 2,
+            curve_type: CurveType::ConstantProduct as u8,
+            reserve_mode: RESERVE_MODE_LIVE,
+            ..Default::default()
+        }
+    }
+}
+
 #[cfg(test)]
 pub mod pool_test {
     use super::*;
+    use crate::curve::WEIGHT_DENOMINATOR;
+
+    #[test]
+    fn migrate_first_layout() {
+        let pool_state_v0 = PoolStateV0 {
+            compression_info: Some(CompressionInfo::default()),
+            amm_config: Pubkey::new_unique(),
+            token_0_vault: Pubkey::new_unique(),
+            token_1_vault: Pubkey::new_unique(),
+            status: 4,
+            mint_0_decimals: 6,
+            lp_supply: 1_000,
+            protocol_fees_token_0: 10,
+            fund_fees_token_1: 20,
+            open_time: 100,
+            ..Default::default()
+        };
+        let data = pool_state_v0.try_to_vec().unwrap();
+        assert_eq!(data.len(), PoolStateV0::INIT_SPACE);
+
+        let pool_state = PoolStateV0::deserialize(&mut data.as_slice())
+            .unwrap()
+            .migrate();
+        assert_eq!(
+            pool_state.try_to_vec().unwrap().len(),
+            PoolState::INIT_SPACE
+        );
+        assert_eq!(pool_state.amm_config, pool_state_v0.amm_config);
+        assert_eq!(pool_state.token_1_vault, pool_state_v0.token_1_vault);
+        assert_eq!(pool_state.status, 4);
+        assert_eq!(pool_state.mint_0_decimals, 6);
+        assert_eq!(pool_state.lp_supply, 1_000);
+        assert_eq!(pool_state.protocol_fees_token_0, 10);
+        assert_eq!(pool_state.fund_fees_token_1, 20);
+        assert_eq!(pool_state.open_time, 100);
+        // old pools keep trading as constant product pools on their live balances
+        assert_eq!(
+            (pool_state.token_0_weight, pool_state.token_1_weight),
+            (WEIGHT_DENOMINATOR / 2, WEIGHT_DENOMINATOR / 2)
+        );
+        assert_eq!(pool_state.curve_type, CurveType::ConstantProduct as u8);
+        assert_eq!(pool_state.reserve_mode, RESERVE_MODE_LIVE);
+        assert!(!pool_state.is_lbp());
+        assert_eq!(pool_state.vault_amounts(1_500, 2_000), (1_500, 2_000));
+    }
+
+    #[test]
+    fn vault_amounts_follow_reserve_mode() {
+        let mut pool_state = PoolState {
+            token_0_vault: Pubkey::new_unique(),
+            token_1_vault: Pubkey::new_unique(),
+            token_0_reserve: 1_000,
+            token_1_reserve: 2_000,
+            ..Default::default()
+        };
+        // Live pools price with whatever the vaults hold
+        assert_eq!(pool_state.vault_amounts(1_500, 2_000), (1_500, 2_000));
+        // Tracked pools leave out donations and cap at a shrunk balance
+        pool_state.reserve_mode = RESERVE_MODE_SKIM;
+        assert_eq!(pool_state.vault_amounts(1_500, 1_800), (1_000, 1_800));
+        assert_eq!(
+            pool_state.vault_amount(&pool_state.token_1_vault, 2_500),
+            2_000
+        );
+    }
+
+    #[test]
+    fn token_price_x32_accounts_for_weights() {
+        let mut pool_state = PoolState {
+            token_0_weight: WEIGHT_DENOMINATOR / 2,
+            token_1_weight: WEIGHT_DENOMINATOR / 2,
+            ..Default::default()
+        };
+        assert_eq!(pool_state.token_price_x32(1_000, 2_000), (2 * Q32, Q32 / 2));
+
+        // An 80/20 pool holding 4x more value in token_0 is priced 1:1
+        pool_state.token_0_weight = 800_000;
+        pool_state.token_1_weight = 200_000;
+        assert_eq!(pool_state.token_price_x32(4_000, 1_000), (Q32, Q32));
+    }
+
+    #[test]
+    fn update_weights_follows_lbp_schedule() {
+        let mut pool_state = PoolState {
+            token_0_weight: 900_000,
+            token_1_weight: 100_000,
+            lbp_start_time: 1_000,
+            lbp_end_time: 2_000,
+            lbp_start_weight_0: 900_000,
+            lbp_end_weight_0: WEIGHT_DENOMINATOR / 2,
+            ..Default::default()
+        };
+        pool_state.update_weights(500);
+        assert_eq!(
+            (pool_state.token_0_weight, pool_state.token_1_weight),
+            (900_000, 100_000)
+        );
+        pool_state.update_weights(1_250);
+        assert_eq!(
+            (pool_state.token_0_weight, pool_state.token_1_weight),
+            (800_000, 200_000)
+        );
+        pool_state.update_weights(3_000);
+        assert_eq!(
+            (pool_state.token_0_weight, pool_state.token_1_weight),
+            (500_000, 500_000)
+        );
+
+        // graduated pools keep their weights
+        pool_state.lbp_end_time = 0;
+        pool_state.token_0_weight = 600_000;
+        pool_state.update_weights(1_250);
+        assert_eq!(pool_state.token_0_weight, 600_000);
+    }
+
+    #[test]
+    fn graduate_keeps_final_weights() {
+        let lbp = |lbp_end_weight_0| PoolState {
+            token_0_weight: 900_000,
+            token_1_weight: 100_000,
+            lbp_start_time: 1_000,
+            lbp_end_time: 2_000,
+            lbp_start_weight_0: 900_000,
+            lbp_end_weight_0,
+            curve_type: CurveType::Weighted as u8,
+            ..Default::default()
+        };
+        let mut pool_state = lbp(WEIGHT_DENOMINATOR / 2);
+        pool_state.graduate(2_000);
+        assert!(!pool_state.is_lbp());
+        assert_eq!(pool_state.curve_type, CurveType::ConstantProduct as u8);
+
+        // an 80/20 end stays weighted instead of jumping to the 50/50 price
+        let mut pool_state = lbp(800_000);
+        pool_state.graduate(3_000);
+        assert!(!pool_state.is_lbp());
+        assert_eq!(pool_state.curve_type, CurveType::Weighted as u8);
+        assert_eq!(
+            (pool_state.token_0_weight, pool_state.token_1_weight),
+            (800_000, 200_000)
+        );
+    }
 
     mod pool_status_test {
         use super::*;
@@ -45,3 +45,177 @@ impl CheckedCeilDiv for u128 {
         Some((quotient, rhs))
     }
 }
+
+/// 1.0 in the Q64.64 fixed point format used by `log2_x64` and `exp2_x64`
+pub const ONE_X64: u128 = 1 << 64;
+
+/// 2^(2^-i) in Q64.64 for i in 1..=63, rounded down
+const EXP2_FRACTIONS_X64: [u128; 63] = [
+    0x16a09e667f3bcc908,
+    0x1306fe0a31b7152de,
+    0x1172b83c7d517adcd,
+    0x10b5586cf9890f629,
+    0x1059b0d31585743ae,
+    0x102c9a3e778060ee6,
+    0x10163da9fb33356d8,
+    0x100b1afa5abcbed61,
+    0x10058c86da1c09ea1,
+    0x1002c605e2e8cec50,
+    0x100162f3904051fa1,
+    0x1000b175effdc76ba,
+    0x100058ba01fb9f96d,
+    0x10002c5cc37da9491,
+    0x1000162e525ee0547,
+    0x10000b17255775c04,
+    0x1000058b91b5bc9ae,
+    0x100002c5c89d5ec6c,
+    0x10000162e43f4f831,
+    0x100000b1721bcfc99,
+    0x10000058b90cf1e6d,
+    0x1000002c5c863b73f,
+    0x100000162e430e5a1,
+    0x1000000b172183551,
+    0x100000058b90c0b48,
+    0x10000002c5c8601cc,
+    0x1000000162e42fff0,
+    0x10000000b17217fba,
+    0x1000000058b90bfcd,
+    0x100000002c5c85fe3,
+    0x10000000162e42ff0,
+    0x100000000b17217f8,
+    0x10000000058b90bfb,
+    0x1000000002c5c85fd,
+    0x100000000162e42fe,
+    0x1000000000b17217f,
+    0x100000000058b90bf,
+    0x10000000002c5c85f,
+    0x1000000000162e42f,
+    0x10000000000b17217,
+    0x1000000000058b90b,
+    0x100000000002c5c85,
+    0x10000000000162e42,
+    0x100000000000b1721,
+    0x10000000000058b90,
+    0x1000000000002c5c8,
+    0x100000000000162e4,
+    0x1000000000000b172,
+    0x100000000000058b9,
+    0x10000000000002c5c,
+    0x1000000000000162e,
+    0x10000000000000b17,
+    0x1000000000000058b,
+    0x100000000000002c5,
+    0x10000000000000162,
+    0x100000000000000b1,
+    0x10000000000000058,
+    0x1000000000000002c,
+    0x10000000000000016,
+    0x1000000000000000b,
+    0x10000000000000005,
+    0x10000000000000002,
+    0x10000000000000001,
+];
+
+/// Binary logarithm of `numerator / denominator` in Q64.64, rounded down.
+///
+/// Only ratios >= 1 are supported, so the result is never negative.
+pub fn log2_x64(numerator: u128, denominator: u128) -> Option<u128> {
+    if denominator == 0 || numerator < denominator {
+        return None;
+    }
+    let ratio_x64 = (U256::from(numerator) << 64).checked_div(U256::from(denominator))?;
+    // integer part from the most significant bit, then normalize into [1, 2)
+    let integer_bits = ratio_x64.bits() - 65;
+    let mut result = (integer_bits as u128) << 64;
+    let mut r = ratio_x64 >> integer_bits;
+    let two_x64 = U256::from(ONE_X64) << 1;
+    // each squaring doubles the exponent, so r >= 2 yields the next fractional bit
+    for i in 1..=64 {
+        r = r.checked_mul(r)? >> 64;
+        if r >= two_x64 {
+            r >>= 1;
+            result |= 1u128 << (64 - i);
+        }
+    }
+    Some(result)
+}
+
+/// 2^y for a Q64.64 exponent, returned in Q64.64 and rounded down.
+///
+/// Returns None if the result does not fit in a u128.
+pub fn exp2_x64(y: u128) -> Option<u128> {
+    let integer_bits = y >> 64;
+    if integer_bits >= 64 {
+        return None;
+    }
+    let mut result = U256::from(ONE_X64);
+    for (i, fraction) in EXP2_FRACTIONS_X64.iter().enumerate() {
+        if y & (1u128 << (63 - i)) != 0 {
+            result = result.checked_mul(U256::from(*fraction))? >> 64;
+        }
+    }
+    u128::try_from(result << integer_bits).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, proptest::prelude::*};
+
+    fn to_f64(x64: u128) -> f64 {
+        x64 as f64 / ONE_X64 as f64
+    }
+
+    #[test]
+    fn log2_exact_powers() {
+        assert_eq!(log2_x64(1, 1), Some(0));
+        assert_eq!(log2_x64(2, 1), Some(ONE_X64));
+        assert_eq!(log2_x64(1 << 100, 1), Some(100 * ONE_X64));
+        assert_eq!(log2_x64(3, 6), None);
+        assert_eq!(log2_x64(1, 0), None);
+    }
+
+    #[test]
+    fn exp2_exact_powers() {
+        assert_eq!(exp2_x64(0), Some(ONE_X64));
+        assert_eq!(exp2_x64(ONE_X64), Some(2 * ONE_X64));
+        assert_eq!(exp2_x64(63 * ONE_X64), Some(ONE_X64 << 63));
+        assert_eq!(exp2_x64(64 * ONE_X64), None);
+    }
+
+    proptest! {
+        #[test]
+        fn log2_matches_float(
+            numerator in 1..u128::MAX,
+            denominator in 1..u64::MAX,
+        ) {
+            let denominator = denominator as u128;
+            prop_assume!(numerator >= denominator);
+            let expected = (numerator as f64 / denominator as f64).log2();
+            let actual = to_f64(log2_x64(numerator, denominator).unwrap());
+            prop_assert!((actual - expected).abs() <= 1e-9 * expected.max(1.0));
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn exp2_matches_float(y in 0..(63 * ONE_X64)) {
+            let expected = to_f64(y).exp2();
+            let actual = exp2_x64(y).unwrap() as f64 / ONE_X64 as f64;
+            prop_assert!((actual - expected).abs() <= 1e-12 * expected);
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn exp2_inverts_log2(numerator in 1..u64::MAX, denominator in 1..u64::MAX) {
+            prop_assume!(numerator >= denominator);
+            let log = log2_x64(numerator as u128, denominator as u128).unwrap();
+            let ratio_x64 = (U256::from(numerator) << 64) / U256::from(denominator);
+            let ratio_x64 = ratio_x64.as_u128();
+            let actual = exp2_x64(log).unwrap();
+            // both rounded down, within a relative error of 2^-50
+            prop_assert!(actual <= ratio_x64);
+            prop_assert!(ratio_x64 - actual <= (ratio_x64 >> 50) + 1);
+        }
+    }
+}
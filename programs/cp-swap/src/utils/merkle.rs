@@ -0,0 +1,52 @@
+//! Merkle proofs over sha256 with sorted pairs, as built by the common
+//! off-chain allowlist tooling
+use anchor_lang::solana_program::hash::hashv;
+
+/// Leaf of a wallet in an allowlist together with its cap
+pub fn merkle_leaf(wallet: &[u8], cap: u64) -> [u8; 32] {
+    hashv(&[wallet, &cap.to_le_bytes()]).to_bytes()
+}
+
+/// Whether `leaf` is in the tree with the given `root`
+pub fn verify_merkle_proof(proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        if node <= *sibling {
+            hashv(&[&node, sibling]).to_bytes()
+        } else {
+            hashv(&[sibling, &node]).to_bytes()
+        }
+    });
+    computed == root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parent(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+        if a <= b {
+            hashv(&[&a, &b]).to_bytes()
+        } else {
+            hashv(&[&b, &a]).to_bytes()
+        }
+    }
+
+    #[test]
+    fn verify_proofs_of_a_small_tree() {
+        let leaves: Vec<[u8; 32]> = (0..4u8).map(|i| merkle_leaf(&[i; 32], 100)).collect();
+        let left = parent(leaves[0], leaves[1]);
+        let right = parent(leaves[2], leaves[3]);
+        let root = parent(left, right);
+
+        assert!(verify_merkle_proof(&[leaves[1], right], root, leaves[0]));
+        assert!(verify_merkle_proof(&[leaves[2], left], root, leaves[3]));
+        // a different cap is a different leaf
+        assert!(!verify_merkle_proof(
+            &[leaves[1], right],
+            root,
+            merkle_leaf(&[0; 32], 101)
+        ));
+        assert!(!verify_merkle_proof(&[leaves[1], left], root, leaves[0]));
+        assert!(!verify_merkle_proof(&[], root, leaves[0]));
+    }
+}
@@ -0,0 +1,139 @@
+/// Integration tests for collecting the pool creator fee.
+use light_program_test::{utils::assert::assert_rpc_error, Rpc};
+use raydium_cp_swap::{curve::Fees, error::ErrorCode};
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+
+mod helpers;
+use helpers::*;
+
+const POOL_AMOUNT: u64 = 500_000;
+const AMOUNT_IN: u64 = 100_000;
+// Rate set by `create_amm_config`
+const TRADE_FEE_RATE: u64 = 2500;
+const CREATOR_FEE_RATE: u64 = 100_000;
+
+#[tokio::test]
+async fn test_collect_creator_fee() {
+    let program_id = raydium_cp_swap::ID;
+    let mut setup = setup_pool_environment(program_id, 70).await;
+    let admin = get_admin_keypair();
+
+    // Param 12 sets the creator fee rate
+    let update_ix =
+        build_update_amm_config_instruction(program_id, setup.amm_config, 12, CREATOR_FEE_RATE);
+    setup
+        .env
+        .rpc
+        .create_and_send_transaction(
+            &[update_ix],
+            &setup.env.payer.pubkey(),
+            &[&setup.env.payer, &admin],
+        )
+        .await
+        .expect("Update creator fee rate should succeed");
+
+    let proof_result =
+        get_pool_create_accounts_proof(&setup.env.rpc, &program_id, &setup.pdas).await;
+    let init_ix = build_initialize_instruction(
+        program_id,
+        setup.creator.pubkey(),
+        setup.amm_config,
+        &setup.pdas,
+        &setup.tokens,
+        setup.env.config_pda,
+        &proof_result,
+        POOL_AMOUNT,
+        POOL_AMOUNT,
+        0,
+    );
+    setup
+        .env
+        .rpc
+        .create_and_send_transaction(&[init_ix], &setup.creator.pubkey(), &[&setup.creator])
+        .await
+        .unwrap();
+
+    // A swap of token 0 accrues the creator fee in token 0
+    let swap_ix = build_swap_instruction(
+        program_id,
+        setup.creator.pubkey(),
+        setup.amm_config,
+        &setup.pdas,
+        &setup.tokens,
+        setup.tokens.creator_token_0,
+        setup.tokens.creator_token_1,
+        true,
+        AMOUNT_IN,
+        0,
+    );
+    setup
+        .env
+        .rpc
+        .create_and_send_transaction(&[swap_ix], &setup.creator.pubkey(), &[&setup.creator])
+        .await
+        .unwrap();
+    let trade_fee = Fees::trading_fee(AMOUNT_IN as u128, TRADE_FEE_RATE).unwrap();
+    let creator_fee = Fees::creator_fee(trade_fee, CREATOR_FEE_RATE).unwrap() as u64;
+    assert!(creator_fee > 0);
+    let pool_state = get_pool_state(&mut setup.env.rpc, setup.pdas.pool_state).await;
+    assert_eq!(pool_state.creator_fees_token_0, creator_fee);
+    assert_eq!(pool_state.creator_fees_token_1, 0);
+
+    // Anyone but the pool creator is rejected
+    let other = Keypair::new();
+    setup
+        .env
+        .rpc
+        .airdrop_lamports(&other.pubkey(), 10_000_000_000)
+        .await
+        .unwrap();
+    let collect_ix = build_collect_creator_fee_instruction(
+        program_id,
+        other.pubkey(),
+        &setup.pdas,
+        &setup.tokens,
+        setup.tokens.creator_token_0,
+        setup.tokens.creator_token_1,
+        u64::MAX,
+        u64::MAX,
+    );
+    let result = setup
+        .env
+        .rpc
+        .create_and_send_transaction(&[collect_ix], &other.pubkey(), &[&other])
+        .await;
+    assert_rpc_error(result, 0, ErrorCode::InvalidOwner.into()).unwrap();
+
+    // The creator collects everything accrued
+    let balance_0_before =
+        get_token_balance(&mut setup.env.rpc, setup.tokens.creator_token_0).await;
+    let balance_1_before =
+        get_token_balance(&mut setup.env.rpc, setup.tokens.creator_token_1).await;
+    let collect_ix = build_collect_creator_fee_instruction(
+        program_id,
+        setup.creator.pubkey(),
+        &setup.pdas,
+        &setup.tokens,
+        setup.tokens.creator_token_0,
+        setup.tokens.creator_token_1,
+        u64::MAX,
+        u64::MAX,
+    );
+    setup
+        .env
+        .rpc
+        .create_and_send_transaction(&[collect_ix], &setup.creator.pubkey(), &[&setup.creator])
+        .await
+        .expect("Collect creator fee as the creator should succeed");
+    assert_eq!(
+        get_token_balance(&mut setup.env.rpc, setup.tokens.creator_token_0).await,
+        balance_0_before + creator_fee
+    );
+    assert_eq!(
+        get_token_balance(&mut setup.env.rpc, setup.tokens.creator_token_1).await,
+        balance_1_before
+    );
+    let pool_state = get_pool_state(&mut setup.env.rpc, setup.pdas.pool_state).await;
+    assert_eq!(pool_state.creator_fees_token_0, 0);
+}
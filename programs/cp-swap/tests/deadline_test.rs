@@ -0,0 +1,87 @@
+/// Integration tests for the `*_with_deadline` instructions.
+use anchor_lang::InstructionData;
+use light_program_test::{utils::assert::assert_rpc_error, Rpc};
+use raydium_cp_swap::error::ErrorCode;
+use solana_sdk::clock::Clock;
+use solana_signer::Signer;
+
+mod helpers;
+use helpers::*;
+
+#[tokio::test]
+async fn test_swap_with_deadline() {
+    let program_id = raydium_cp_swap::ID;
+    let mut setup = setup_pool_environment(program_id, 60).await;
+
+    let proof_result =
+        get_pool_create_accounts_proof(&setup.env.rpc, &program_id, &setup.pdas).await;
+    let init_ix = build_initialize_instruction(
+        program_id,
+        setup.creator.pubkey(),
+        setup.amm_config,
+        &setup.pdas,
+        &setup.tokens,
+        setup.env.config_pda,
+        &proof_result,
+        100_000,
+        100_000,
+        0,
+    );
+    setup
+        .env
+        .rpc
+        .create_and_send_transaction(&[init_ix], &setup.creator.pubkey(), &[&setup.creator])
+        .await
+        .unwrap();
+
+    let mut clock = setup.env.rpc.context.get_sysvar::<Clock>();
+    clock.unix_timestamp += 100;
+    setup.env.rpc.context.set_sysvar(&clock);
+    let now = clock.unix_timestamp as u64;
+
+    let swap_with_deadline = |deadline: u64| {
+        let mut swap_ix = build_swap_instruction(
+            program_id,
+            setup.creator.pubkey(),
+            setup.amm_config,
+            &setup.pdas,
+            &setup.tokens,
+            setup.tokens.creator_token_0,
+            setup.tokens.creator_token_1,
+            true,
+            1_000,
+            0,
+        );
+        swap_ix.data = raydium_cp_swap::instruction::SwapBaseInputWithDeadline {
+            amount_in: 1_000,
+            minimum_amount_out: 0,
+            deadline,
+        }
+        .data();
+        swap_ix
+    };
+
+    // One second late
+    let late_ix = swap_with_deadline(now - 1);
+    let result = setup
+        .env
+        .rpc
+        .create_and_send_transaction(&[late_ix], &setup.creator.pubkey(), &[&setup.creator])
+        .await;
+    assert_rpc_error(result, 0, ErrorCode::DeadlineExceeded.into()).unwrap();
+
+    // Due this second
+    let on_time_ix = swap_with_deadline(now);
+    let input_balance_before =
+        get_token_balance(&mut setup.env.rpc, setup.tokens.creator_token_0).await;
+    setup
+        .env
+        .rpc
+        .create_and_send_transaction(&[on_time_ix], &setup.creator.pubkey(), &[&setup.creator])
+        .await
+        .expect("Swap due at the current timestamp should succeed");
+    assert_eq!(
+        get_token_balance(&mut setup.env.rpc, setup.tokens.creator_token_0).await,
+        input_balance_before - 1_000
+    );
+}
@@ -0,0 +1,166 @@
+/// Integration tests for flash swaps, repaid by the flash-borrower program.
+use light_program_test::Rpc;
+use raydium_cp_swap::curve::Fees;
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+
+mod helpers;
+use helpers::*;
+
+const INITIAL_BALANCE: u64 = 10_000_000_000;
+const POOL_AMOUNT: u64 = 1_000_000_000;
+const BORROW_AMOUNT: u64 = 100_000_000;
+// Rates set by `create_amm_config`
+const TRADE_FEE_RATE: u64 = 2500;
+const PROTOCOL_FEE_RATE: u64 = 1000;
+const FUND_FEE_RATE: u64 = 500;
+
+/// A pool of `POOL_AMOUNT` of each token, with the flash-borrower program loaded.
+async fn setup_flash_swap_pool() -> PoolSetup {
+    let program_id = raydium_cp_swap::ID;
+    let mut env = setup_test_environment_with_programs(
+        program_id,
+        vec![("flash_borrower", flash_borrower::ID)],
+    )
+    .await;
+
+    let creator = Keypair::new();
+    env.rpc
+        .airdrop_lamports(&creator.pubkey(), 100_000_000_000)
+        .await
+        .unwrap();
+    let admin = get_admin_keypair();
+    env.rpc
+        .airdrop_lamports(&admin.pubkey(), 10_000_000_000)
+        .await
+        .unwrap();
+
+    let tokens =
+        setup_token_mints(&mut env.rpc, &env.payer, &creator.pubkey(), INITIAL_BALANCE).await;
+    let amm_config = create_amm_config(&mut env.rpc, &env.payer, &admin, program_id, 30).await;
+    setup_create_pool_fee_account(&mut env.rpc, &env.payer.pubkey());
+    let pdas = derive_amm_pdas(
+        &program_id,
+        &amm_config,
+        &tokens.token_0_mint,
+        &tokens.token_1_mint,
+        &creator.pubkey(),
+    );
+
+    let proof_result = get_pool_create_accounts_proof(&env.rpc, &program_id, &pdas).await;
+    let init_ix = build_initialize_instruction(
+        program_id,
+        creator.pubkey(),
+        amm_config,
+        &pdas,
+        &tokens,
+        env.config_pda,
+        &proof_result,
+        POOL_AMOUNT,
+        POOL_AMOUNT,
+        0,
+    );
+    env.rpc
+        .create_and_send_transaction(&[init_ix], &creator.pubkey(), &[&creator])
+        .await
+        .unwrap();
+    assert_pool_initialized(&mut env.rpc, &pdas).await;
+
+    PoolSetup {
+        env,
+        creator,
+        tokens,
+        amm_config,
+        pdas,
+    }
+}
+
+#[tokio::test]
+async fn test_flash_swap_repaid_with_fee() {
+    let program_id = raydium_cp_swap::ID;
+    let mut setup = setup_flash_swap_pool().await;
+    let rpc = &mut setup.env.rpc;
+
+    let repay_amount = BORROW_AMOUNT + BORROW_AMOUNT / 100;
+    let user_balance_before = get_token_balance(rpc, setup.tokens.creator_token_0).await;
+    let flash_ix = build_flash_swap_instruction(
+        program_id,
+        setup.creator.pubkey(),
+        setup.amm_config,
+        &setup.pdas,
+        &setup.tokens,
+        BORROW_AMOUNT,
+        0,
+        repay_amount,
+        0,
+    );
+    rpc.create_and_send_transaction(&[flash_ix], &setup.creator.pubkey(), &[&setup.creator])
+        .await
+        .expect("Flash swap repaid with a fee should succeed");
+
+    // The pool keeps what was paid on top of the borrowed amount
+    let profit = repay_amount - BORROW_AMOUNT;
+    assert_eq!(
+        get_token_balance(rpc, setup.pdas.token_0_vault).await,
+        POOL_AMOUNT + profit
+    );
+    assert_eq!(
+        get_token_balance(rpc, setup.pdas.token_1_vault).await,
+        POOL_AMOUNT
+    );
+    assert_eq!(
+        get_token_balance(rpc, setup.tokens.creator_token_0).await,
+        user_balance_before - profit
+    );
+
+    // Fees are charged on the repaid amount
+    let trade_fee = Fees::trading_fee(repay_amount as u128, TRADE_FEE_RATE).unwrap();
+    let pool_state = get_pool_state(rpc, setup.pdas.pool_state).await;
+    assert_eq!(
+        pool_state.protocol_fees_token_0 as u128,
+        Fees::protocol_fee(trade_fee, PROTOCOL_FEE_RATE).unwrap()
+    );
+    assert_eq!(
+        pool_state.fund_fees_token_0 as u128,
+        Fees::fund_fee(trade_fee, FUND_FEE_RATE).unwrap()
+    );
+    assert_eq!(pool_state.protocol_fees_token_1, 0);
+    assert_eq!(pool_state.fund_fees_token_1, 0);
+}
+
+#[tokio::test]
+async fn test_flash_swap_under_repaid_fails() {
+    let program_id = raydium_cp_swap::ID;
+    let mut setup = setup_flash_swap_pool().await;
+    let rpc = &mut setup.env.rpc;
+
+    // Returning the borrowed amount without the trade fee
+    let flash_ix = build_flash_swap_instruction(
+        program_id,
+        setup.creator.pubkey(),
+        setup.amm_config,
+        &setup.pdas,
+        &setup.tokens,
+        BORROW_AMOUNT,
+        0,
+        BORROW_AMOUNT,
+        0,
+    );
+    let result = rpc
+        .create_and_send_transaction(&[flash_ix], &setup.creator.pubkey(), &[&setup.creator])
+        .await;
+    assert!(result.is_err(), "Flash swap without the fee should fail");
+
+    // Nothing moved
+    assert_eq!(
+        get_token_balance(rpc, setup.pdas.token_0_vault).await,
+        POOL_AMOUNT
+    );
+    assert_eq!(
+        get_token_balance(rpc, setup.tokens.creator_token_0).await,
+        INITIAL_BALANCE - POOL_AMOUNT
+    );
+    let pool_state = get_pool_state(rpc, setup.pdas.pool_state).await;
+    assert_eq!(pool_state.protocol_fees_token_0, 0);
+    assert_eq!(pool_state.fund_fees_token_0, 0);
+}
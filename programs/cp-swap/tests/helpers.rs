@@ -3,7 +3,7 @@
 /// Functional integration test for cp-swap program.
 /// Tests pool initialization with light-program-test framework.
 
-use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_lang::{AnchorDeserialize, AnchorSerialize, InstructionData, ToAccountMetas};
 use light_client::interface::{
     get_create_accounts_proof, CreateAccountsProofInput, CreateAccountsProofResult,
     InitializeRentFreeConfig,
@@ -24,10 +24,17 @@ use light_token::{
 };
 use raydium_cp_swap::{
     instructions::initialize::LP_MINT_SIGNER_SEED,
-    states::{AMM_CONFIG_SEED, OBSERVATION_SEED, POOL_SEED, POOL_VAULT_SEED},
+    states::{
+        AmmConfig, MultiPoolState, PoolState, AMM_CONFIG_SEED, LAUNCH_POOL_SEED,
+        LAUNCH_POOL_VAULT_SEED, MULTI_POOL_SEED, OBSERVATION_SEED, POOL_SEED, POOL_VAULT_SEED,
+    },
+    instructions::{
+        CreateLaunchPoolParams, InitializeMultiPoolParams, MigrateLaunchPoolParams,
+        ROUTE_HOP_ACCOUNTS,
+    },
     InitializeParams, AUTH_SEED,
 };
-use solana_instruction::Instruction;
+use solana_instruction::{AccountMeta, Instruction};
 use solana_keypair::Keypair;
 use solana_pubkey::Pubkey;
 use solana_signer::Signer;
@@ -88,8 +95,17 @@ pub struct TokenSetup {
 
 /// Initialize the test environment with LightProgramTest and compression config.
 pub async fn setup_test_environment(program_id: Pubkey) -> TestEnv {
-    let mut config =
-        ProgramTestConfig::new_v2(true, Some(vec![("raydium_cp_swap", program_id)]));
+    setup_test_environment_with_programs(program_id, vec![]).await
+}
+
+/// Same as `setup_test_environment`, also loading other programs the test calls.
+pub async fn setup_test_environment_with_programs(
+    program_id: Pubkey,
+    other_programs: Vec<(&'static str, Pubkey)>,
+) -> TestEnv {
+    let mut programs = vec![("raydium_cp_swap", program_id)];
+    programs.extend(other_programs);
+    let mut config = ProgramTestConfig::new_v2(true, Some(programs));
     config = config.with_light_protocol_events();
 
     let mut rpc = LightProgramTest::new(config).await.unwrap();
@@ -298,6 +314,8 @@ pub async fn create_amm_config(
         protocol_fee_rate: 1000,
         fund_fee_rate: 500,
         create_pool_fee: 0,
+        curve_type: 0,
+        amp: 0,
     };
 
     let create_config_ix = Instruction {
@@ -364,7 +382,17 @@ pub fn derive_amm_pdas(
         ],
         program_id,
     );
+    derive_pool_pdas(program_id, pool_state, token_0_mint, token_1_mint, creator)
+}
 
+/// Derive the PDAs that hang off a pool state address.
+pub fn derive_pool_pdas(
+    program_id: &Pubkey,
+    pool_state: Pubkey,
+    token_0_mint: &Pubkey,
+    token_1_mint: &Pubkey,
+    creator: &Pubkey,
+) -> AmmPdas {
     let (authority, authority_bump) =
         Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], program_id);
 
@@ -475,6 +503,59 @@ pub fn build_withdraw_instruction(
         minimum_token_1_amount,
     };
 
+    // The optional observation account, for the oracle to record the price
+    let mut account_metas = accounts.to_account_metas(None);
+    account_metas.push(AccountMeta::new(pdas.observation_state, false));
+
+    Instruction {
+        program_id,
+        accounts: account_metas,
+        data: instruction_data.data(),
+    }
+}
+
+/// Build the WithdrawSingleToken instruction, paying out only token 0 or token 1.
+pub fn build_withdraw_single_token_instruction(
+    program_id: Pubkey,
+    owner: Pubkey,
+    amm_config: Pubkey,
+    pdas: &AmmPdas,
+    tokens: &TokenSetup,
+    output_token_account: Pubkey,
+    is_token_0_output: bool,
+    lp_token_amount: u64,
+    minimum_amount_out: u64,
+) -> Instruction {
+    let (input_vault, output_vault, output_token_mint) = if is_token_0_output {
+        (pdas.token_1_vault, pdas.token_0_vault, tokens.token_0_mint)
+    } else {
+        (pdas.token_0_vault, pdas.token_1_vault, tokens.token_1_mint)
+    };
+
+    let accounts = raydium_cp_swap::accounts::WithdrawSingleToken {
+        owner,
+        authority: pdas.authority,
+        amm_config,
+        pool_state: pdas.pool_state,
+        observation_state: pdas.observation_state,
+        owner_lp_token: pdas.creator_lp_token,
+        output_token_account,
+        input_vault,
+        output_vault,
+        token_program: spl_token::id(),
+        token_program_2022: spl_token_2022::id(),
+        output_token_mint,
+        lp_mint: pdas.lp_mint,
+        system_program: solana_sdk::system_program::ID,
+        light_token_cpi_authority: CPI_AUTHORITY_PDA,
+        light_token_program: light_token_program_id(),
+    };
+
+    let instruction_data = raydium_cp_swap::instruction::WithdrawSingleToken {
+        lp_token_amount,
+        minimum_amount_out,
+    };
+
     Instruction {
         program_id,
         accounts: accounts.to_account_metas(None),
@@ -579,9 +660,13 @@ pub fn build_deposit_instruction(
         maximum_token_1_amount,
     };
 
+    // The optional observation account, for the oracle to record the price
+    let mut account_metas = accounts.to_account_metas(None);
+    account_metas.push(AccountMeta::new(pdas.observation_state, false));
+
     Instruction {
         program_id,
-        accounts: accounts.to_account_metas(None),
+        accounts: account_metas,
         data: instruction_data.data(),
     }
 }
@@ -603,6 +688,12 @@ pub fn build_initialize_instruction(
         init_amount_0,
         init_amount_1,
         open_time,
+        token_0_weight: 500_000,
+        observation_update_duration: 0,
+        lbp_end_time: 0,
+        lbp_start_time: 0,
+        lbp_end_token_0_weight: 0,
+        reserve_mode: 0,
         create_accounts_proof: proof_result.create_accounts_proof.clone(),
         lp_mint_signer_bump: pdas.lp_mint_signer_bump,
         creator_lp_token_bump: pdas.creator_lp_token_bump,
@@ -653,6 +744,67 @@ pub fn build_initialize_instruction(
     }
 }
 
+/// Build the UpdateAmmConfig instruction, signed by the admin.
+pub fn build_update_amm_config_instruction(
+    program_id: Pubkey,
+    amm_config: Pubkey,
+    param: u8,
+    value: u64,
+) -> Instruction {
+    let accounts = raydium_cp_swap::accounts::UpdateAmmConfig {
+        owner: get_admin_keypair().pubkey(),
+        amm_config,
+    };
+
+    let instruction_data = raydium_cp_swap::instruction::UpdateAmmConfig { param, value };
+
+    Instruction {
+        program_id,
+        accounts: accounts.to_account_metas(None),
+        data: instruction_data.data(),
+    }
+}
+
+/// Build the CollectCreatorFee instruction.
+pub fn build_collect_creator_fee_instruction(
+    program_id: Pubkey,
+    owner: Pubkey,
+    pdas: &AmmPdas,
+    tokens: &TokenSetup,
+    recipient_token_0_account: Pubkey,
+    recipient_token_1_account: Pubkey,
+    amount_0_requested: u64,
+    amount_1_requested: u64,
+) -> Instruction {
+    let accounts = raydium_cp_swap::accounts::CollectCreatorFee {
+        owner,
+        authority: pdas.authority,
+        pool_state: pdas.pool_state,
+        observation_state: pdas.observation_state,
+        token_0_vault: pdas.token_0_vault,
+        token_1_vault: pdas.token_1_vault,
+        vault_0_mint: tokens.token_0_mint,
+        vault_1_mint: tokens.token_1_mint,
+        recipient_token_0_account,
+        recipient_token_1_account,
+        token_program: spl_token::id(),
+        token_program_2022: spl_token_2022::id(),
+        system_program: solana_sdk::system_program::ID,
+        light_token_cpi_authority: CPI_AUTHORITY_PDA,
+    };
+
+    let instruction_data = raydium_cp_swap::instruction::CollectCreatorFee {
+        amount_0_requested,
+        amount_1_requested,
+    };
+
+    Instruction {
+        program_id,
+        accounts: accounts.to_account_metas(None),
+        data: instruction_data.data(),
+    }
+}
+
 // ============================================================================
 // Assertions
 // ============================================================================
@@ -855,3 +1007,719 @@ pub async fn setup_pool_environment(program_id: Pubkey, amm_config_index: u16) -
         pdas,
     }
 }
+
+// ============================================================================
+// Launch Pool
+// ============================================================================
+
+/// PDAs of a launch pool and of the pool it migrates into.
+pub struct LaunchPdas {
+    pub launch_pool: Pubkey,
+    pub token_vault: Pubkey,
+    pub quote_vault: Pubkey,
+    /// Accounts of the migrated pool, `creator_lp_token` is unused
+    pub pool: AmmPdas,
+}
+
+/// Derive the launch pool PDAs for a token launched on `amm_config`.
+pub fn derive_launch_pdas(
+    program_id: &Pubkey,
+    amm_config: &Pubkey,
+    token_mint: &Pubkey,
+    quote_mint: &Pubkey,
+) -> LaunchPdas {
+    let (launch_pool, _) = Pubkey::find_program_address(
+        &[
+            LAUNCH_POOL_SEED.as_bytes(),
+            amm_config.as_ref(),
+            token_mint.as_ref(),
+        ],
+        program_id,
+    );
+    let (token_vault, _) = Pubkey::find_program_address(
+        &[
+            LAUNCH_POOL_VAULT_SEED.as_bytes(),
+            launch_pool.as_ref(),
+            token_mint.as_ref(),
+        ],
+        program_id,
+    );
+    let (quote_vault, _) = Pubkey::find_program_address(
+        &[
+            LAUNCH_POOL_VAULT_SEED.as_bytes(),
+            launch_pool.as_ref(),
+            quote_mint.as_ref(),
+        ],
+        program_id,
+    );
+    let (pool_state, _) = Pubkey::find_program_address(
+        &[POOL_SEED.as_bytes(), launch_pool.as_ref()],
+        program_id,
+    );
+    let (token_0_mint, token_1_mint) = if token_mint < quote_mint {
+        (token_mint, quote_mint)
+    } else {
+        (quote_mint, token_mint)
+    };
+    LaunchPdas {
+        launch_pool,
+        token_vault,
+        quote_vault,
+        pool: derive_pool_pdas(
+            program_id,
+            pool_state,
+            token_0_mint,
+            token_1_mint,
+            &Pubkey::default(),
+        ),
+    }
+}
+
+/// Clear the mint authority of a mint, launches require a fixed supply.
+pub async fn clear_mint_authority(rpc: &mut LightProgramTest, mint: Pubkey) {
+    let mut account = rpc.get_account(mint).await.unwrap().unwrap();
+    // COption<Pubkey> tag and key at the start of the base mint layout
+    account.data[..36].fill(0);
+    rpc.set_account(mint, account);
+}
+
+/// Build the CreateLaunchPool instruction.
+pub fn build_create_launch_pool_instruction(
+    program_id: Pubkey,
+    creator: Pubkey,
+    amm_config: Pubkey,
+    launch: &LaunchPdas,
+    token_mint: Pubkey,
+    quote_mint: Pubkey,
+    creator_token: Pubkey,
+    config_pda: Pubkey,
+    proof_result: &CreateAccountsProofResult,
+    virtual_quote_reserve: u64,
+    migration_market_cap: u64,
+) -> Instruction {
+    let accounts = raydium_cp_swap::accounts::CreateLaunchPool {
+        creator,
+        amm_config,
+        authority: launch.pool.authority,
+        launch_pool: launch.launch_pool,
+        token_mint,
+        quote_mint,
+        creator_token,
+        token_vault: launch.token_vault,
+        quote_vault: launch.quote_vault,
+        system_program: solana_sdk::system_program::ID,
+        compression_config: config_pda,
+        light_token_compressible_config: Pubkey::from(COMPRESSIBLE_CONFIG_V1),
+        light_token_rent_sponsor: Pubkey::from(LIGHT_TOKEN_RENT_SPONSOR),
+        light_token_program: light_token_program_id(),
+        light_token_cpi_authority: CPI_AUTHORITY_PDA,
+    };
+
+    let instruction_data = raydium_cp_swap::instruction::CreateLaunchPool {
+        params: CreateLaunchPoolParams {
+            virtual_quote_reserve,
+            migration_market_cap,
+            create_accounts_proof: proof_result.create_accounts_proof.clone(),
+        },
+    };
+
+    Instruction {
+        program_id,
+        accounts: [
+            accounts.to_account_metas(None),
+            proof_result.remaining_accounts.clone(),
+        ]
+        .concat(),
+        data: instruction_data.data(),
+    }
+}
+
+/// Build the SwapLaunchPool instruction, `buy` pays quote for the token.
+pub fn build_swap_launch_pool_instruction(
+    program_id: Pubkey,
+    payer: Pubkey,
+    amm_config: Pubkey,
+    launch: &LaunchPdas,
+    token_mint: Pubkey,
+    quote_mint: Pubkey,
+    payer_token: Pubkey,
+    payer_quote: Pubkey,
+    buy: bool,
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> Instruction {
+    let (input_token_account, output_token_account) = if buy {
+        (payer_quote, payer_token)
+    } else {
+        (payer_token, payer_quote)
+    };
+    let accounts = raydium_cp_swap::accounts::SwapLaunchPool {
+        payer,
+        authority: launch.pool.authority,
+        amm_config,
+        launch_pool: launch.launch_pool,
+        input_token_account,
+        output_token_account,
+        token_vault: launch.token_vault,
+        quote_vault: launch.quote_vault,
+        token_mint,
+        quote_mint,
+        light_token_program: light_token_program_id(),
+        system_program: solana_sdk::system_program::ID,
+        light_token_cpi_authority: CPI_AUTHORITY_PDA,
+    };
+
+    let instruction_data = raydium_cp_swap::instruction::SwapLaunchPool {
+        amount_in,
+        minimum_amount_out,
+    };
+
+    Instruction {
+        program_id,
+        accounts: accounts.to_account_metas(None),
+        data: instruction_data.data(),
+    }
+}
+
+/// Build the MigrateLaunchPool instruction.
+pub fn build_migrate_launch_pool_instruction(
+    program_id: Pubkey,
+    payer: Pubkey,
+    amm_config: Pubkey,
+    launch: &LaunchPdas,
+    token_0_mint: Pubkey,
+    token_1_mint: Pubkey,
+    config_pda: Pubkey,
+    proof_result: &CreateAccountsProofResult,
+) -> Instruction {
+    let pool = &launch.pool;
+    let accounts = raydium_cp_swap::accounts::MigrateLaunchPool {
+        payer,
+        amm_config,
+        authority: pool.authority,
+        launch_pool: launch.launch_pool,
+        pool_state: pool.pool_state,
+        token_0_mint,
+        token_1_mint,
+        lp_mint_signer: pool.lp_mint_signer,
+        lp_mint: pool.lp_mint,
+        pool_token_0_vault: pool.token_0_vault,
+        pool_token_1_vault: pool.token_1_vault,
+        observation_state: pool.observation_state,
+        launch_token_vault: launch.token_vault,
+        launch_quote_vault: launch.quote_vault,
+        system_program: solana_sdk::system_program::ID,
+        compression_config: config_pda,
+        light_token_compressible_config: Pubkey::from(COMPRESSIBLE_CONFIG_V1),
+        light_token_rent_sponsor: Pubkey::from(LIGHT_TOKEN_RENT_SPONSOR),
+        light_token_program: light_token_program_id(),
+        light_token_cpi_authority: CPI_AUTHORITY_PDA,
+    };
+
+    let instruction_data = raydium_cp_swap::instruction::MigrateLaunchPool {
+        params: MigrateLaunchPoolParams {
+            create_accounts_proof: proof_result.create_accounts_proof.clone(),
+            lp_mint_signer_bump: pool.lp_mint_signer_bump,
+            authority_bump: pool.authority_bump,
+        },
+    };
+
+    Instruction {
+        program_id,
+        accounts: [
+            accounts.to_account_metas(None),
+            proof_result.remaining_accounts.clone(),
+        ]
+        .concat(),
+        data: instruction_data.data(),
+    }
+}
+
+/// Read the pool state account.
+pub async fn get_pool_state(rpc: &mut LightProgramTest, pool_state: Pubkey) -> PoolState {
+    let account = rpc.get_account(pool_state).await.unwrap().unwrap();
+    PoolState::deserialize(&mut &account.data[8..]).unwrap()
+}
+
+/// Read the AMM config account.
+pub async fn get_amm_config(rpc: &mut LightProgramTest, amm_config: Pubkey) -> AmmConfig {
+    let account = rpc.get_account(amm_config).await.unwrap().unwrap();
+    AmmConfig::deserialize(&mut &account.data[8..]).unwrap()
+}
+
+// ============================================================================
+// Swap Route
+// ============================================================================
+
+/// One pool of a swap route.
+pub struct RouteHop<'a> {
+    pub amm_config: Pubkey,
+    pub pdas: &'a AmmPdas,
+    pub tokens: &'a TokenSetup,
+    pub is_token_0_input: bool,
+}
+
+/// Build the SwapRoute instruction, swapping through `hops` in order.
+pub fn build_swap_route_instruction(
+    program_id: Pubkey,
+    payer: Pubkey,
+    input_token_account: Pubkey,
+    output_token_account: Pubkey,
+    input_token_mint: Pubkey,
+    hops: &[RouteHop],
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> Instruction {
+    let (authority, _) = Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &program_id);
+    let accounts = raydium_cp_swap::accounts::SwapRoute {
+        payer,
+        authority,
+        input_token_account,
+        output_token_account,
+        input_token_mint,
+        light_token_program: light_token_program_id(),
+        system_program: solana_sdk::system_program::ID,
+        light_token_cpi_authority: CPI_AUTHORITY_PDA,
+    };
+
+    // ROUTE_HOP_ACCOUNTS per hop
+    let mut hop_accounts = Vec::with_capacity(hops.len() * ROUTE_HOP_ACCOUNTS);
+    for hop in hops {
+        let (input_vault, output_vault, output_mint) = if hop.is_token_0_input {
+            (
+                hop.pdas.token_0_vault,
+                hop.pdas.token_1_vault,
+                hop.tokens.token_1_mint,
+            )
+        } else {
+            (
+                hop.pdas.token_1_vault,
+                hop.pdas.token_0_vault,
+                hop.tokens.token_0_mint,
+            )
+        };
+        hop_accounts.extend([
+            AccountMeta::new_readonly(hop.amm_config, false),
+            AccountMeta::new(hop.pdas.pool_state, false),
+            AccountMeta::new(hop.pdas.observation_state, false),
+            AccountMeta::new(input_vault, false),
+            AccountMeta::new(output_vault, false),
+            AccountMeta::new_readonly(output_mint, false),
+        ]);
+    }
+
+    let instruction_data = raydium_cp_swap::instruction::SwapRoute {
+        amount_in,
+        minimum_amount_out,
+    };
+
+    Instruction {
+        program_id,
+        accounts: [accounts.to_account_metas(None), hop_accounts].concat(),
+        data: instruction_data.data(),
+    }
+}
+
+// ============================================================================
+// Flash Swap
+// ============================================================================
+
+/// Build the FlashSwap instruction, the test borrower repays `repay_amount_0`
+/// and `repay_amount_1` from the payer token accounts.
+pub fn build_flash_swap_instruction(
+    program_id: Pubkey,
+    payer: Pubkey,
+    amm_config: Pubkey,
+    pdas: &AmmPdas,
+    tokens: &TokenSetup,
+    amount_0_out: u64,
+    amount_1_out: u64,
+    repay_amount_0: u64,
+    repay_amount_1: u64,
+) -> Instruction {
+    let accounts = raydium_cp_swap::accounts::FlashSwap {
+        payer,
+        authority: pdas.authority,
+        amm_config,
+        pool_state: pdas.pool_state,
+        observation_state: pdas.observation_state,
+        token_0_vault: pdas.token_0_vault,
+        token_1_vault: pdas.token_1_vault,
+        token_0_account: tokens.creator_token_0,
+        token_1_account: tokens.creator_token_1,
+        vault_0_mint: tokens.token_0_mint,
+        vault_1_mint: tokens.token_1_mint,
+        borrower_program: flash_borrower::ID,
+        light_token_program: light_token_program_id(),
+        system_program: solana_sdk::system_program::ID,
+        light_token_cpi_authority: CPI_AUTHORITY_PDA,
+    };
+    let callback_accounts = flash_borrower::accounts::FlashSwapCallback {
+        sender: payer,
+        sender_token_0: tokens.creator_token_0,
+        token_0_vault: pdas.token_0_vault,
+        sender_token_1: tokens.creator_token_1,
+        token_1_vault: pdas.token_1_vault,
+        light_token_program: light_token_program_id(),
+        light_token_cpi_authority: CPI_AUTHORITY_PDA,
+        system_program: solana_sdk::system_program::ID,
+    };
+
+    let repay_args = flash_borrower::RepayArgs {
+        amount_0: repay_amount_0,
+        amount_1: repay_amount_1,
+        decimals: 9,
+    };
+    let instruction_data = raydium_cp_swap::instruction::FlashSwap {
+        amount_0_out,
+        amount_1_out,
+        data: repay_args.try_to_vec().unwrap(),
+    };
+
+    Instruction {
+        program_id,
+        accounts: [
+            accounts.to_account_metas(None),
+            callback_accounts.to_account_metas(None),
+        ]
+        .concat(),
+        data: instruction_data.data(),
+    }
+}
+
+// ============================================================================
+// Multi-Token Pool
+// ============================================================================
+
+/// PDAs of a multi-token pool, vaults in the order of the mints.
+pub struct MultiPoolPdas {
+    pub multi_pool_state: Pubkey,
+    pub multi_observation_state: Pubkey,
+    pub authority: Pubkey,
+    pub authority_bump: u8,
+    pub lp_mint_signer: Pubkey,
+    pub lp_mint_signer_bump: u8,
+    pub lp_mint: Pubkey,
+    pub creator_lp_token: Pubkey,
+    pub creator_lp_token_bump: u8,
+    pub vaults: Vec<Pubkey>,
+}
+
+/// Derive the PDAs of a multi-token pool, `mints` in ascending order.
+pub fn derive_multi_pool_pdas(
+    program_id: &Pubkey,
+    amm_config: &Pubkey,
+    pool_base: &Pubkey,
+    mints: &[Pubkey],
+    creator: &Pubkey,
+) -> MultiPoolPdas {
+    let (multi_pool_state, _) = Pubkey::find_program_address(
+        &[
+            MULTI_POOL_SEED.as_bytes(),
+            amm_config.as_ref(),
+            pool_base.as_ref(),
+        ],
+        program_id,
+    );
+    let (authority, authority_bump) =
+        Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], program_id);
+    let (multi_observation_state, _) = Pubkey::find_program_address(
+        &[OBSERVATION_SEED.as_bytes(), multi_pool_state.as_ref()],
+        program_id,
+    );
+    let (lp_mint_signer, lp_mint_signer_bump) = Pubkey::find_program_address(
+        &[LP_MINT_SIGNER_SEED, multi_pool_state.as_ref()],
+        program_id,
+    );
+    let (lp_mint, _) = find_mint_address(&lp_mint_signer);
+    let (creator_lp_token, creator_lp_token_bump) =
+        get_associated_token_address_and_bump(creator, &lp_mint);
+    let vaults = mints
+        .iter()
+        .map(|mint| {
+            Pubkey::find_program_address(
+                &[
+                    POOL_VAULT_SEED.as_bytes(),
+                    multi_pool_state.as_ref(),
+                    mint.as_ref(),
+                ],
+                program_id,
+            )
+            .0
+        })
+        .collect();
+
+    MultiPoolPdas {
+        multi_pool_state,
+        multi_observation_state,
+        authority,
+        authority_bump,
+        lp_mint_signer,
+        lp_mint_signer_bump,
+        lp_mint,
+        creator_lp_token,
+        creator_lp_token_bump,
+        vaults,
+    }
+}
+
+/// Get the create accounts proof for multi-token pool initialization.
+pub async fn get_multi_pool_create_accounts_proof(
+    rpc: &LightProgramTest,
+    program_id: &Pubkey,
+    pdas: &MultiPoolPdas,
+) -> CreateAccountsProofResult {
+    get_create_accounts_proof(
+        rpc,
+        program_id,
+        vec![
+            CreateAccountsProofInput::pda(pdas.multi_pool_state),
+            CreateAccountsProofInput::pda(pdas.multi_observation_state),
+            CreateAccountsProofInput::mint(pdas.lp_mint_signer),
+        ],
+    )
+    .await
+    .unwrap()
+}
+
+/// Build the InitializeMultiPool instruction, `pool_base` must sign.
+pub fn build_initialize_multi_pool_instruction(
+    program_id: Pubkey,
+    creator: Pubkey,
+    amm_config: Pubkey,
+    pool_base: Pubkey,
+    pdas: &MultiPoolPdas,
+    mints: &[Pubkey],
+    creator_token_accounts: &[Pubkey],
+    config_pda: Pubkey,
+    proof_result: &CreateAccountsProofResult,
+    init_amounts: Vec<u64>,
+    trade_fee_rates: Vec<u64>,
+) -> Instruction {
+    let accounts = raydium_cp_swap::accounts::InitializeMultiPool {
+        creator,
+        amm_config,
+        authority: pdas.authority,
+        pool_base,
+        multi_pool_state: pdas.multi_pool_state,
+        lp_mint_signer: pdas.lp_mint_signer,
+        lp_mint: pdas.lp_mint,
+        creator_lp_token: pdas.creator_lp_token,
+        multi_observation_state: pdas.multi_observation_state,
+        create_pool_fee: raydium_cp_swap::create_pool_fee_receiver::ID,
+        token_program: spl_token::id(),
+        system_program: solana_sdk::system_program::ID,
+        compression_config: config_pda,
+        light_token_compressible_config: Pubkey::from(COMPRESSIBLE_CONFIG_V1),
+        light_token_rent_sponsor: Pubkey::from(LIGHT_TOKEN_RENT_SPONSOR),
+        light_token_program: light_token_program_id(),
+        light_token_cpi_authority: CPI_AUTHORITY_PDA,
+    };
+
+    // A mint, vault and creator token account per token
+    let token_accounts = mints
+        .iter()
+        .zip(&pdas.vaults)
+        .zip(creator_token_accounts)
+        .flat_map(|((mint, vault), creator_token)| {
+            [
+                AccountMeta::new_readonly(*mint, false),
+                AccountMeta::new(*vault, false),
+                AccountMeta::new(*creator_token, false),
+            ]
+        });
+
+    let instruction_data = raydium_cp_swap::instruction::InitializeMultiPool {
+        params: InitializeMultiPoolParams {
+            init_amounts,
+            trade_fee_rates,
+            open_time: 0,
+            create_accounts_proof: proof_result.create_accounts_proof.clone(),
+            lp_mint_signer_bump: pdas.lp_mint_signer_bump,
+            creator_lp_token_bump: pdas.creator_lp_token_bump,
+            authority_bump: pdas.authority_bump,
+        },
+    };
+
+    Instruction {
+        program_id,
+        accounts: [
+            accounts.to_account_metas(None),
+            token_accounts.collect(),
+            proof_result.remaining_accounts.clone(),
+        ]
+        .concat(),
+        data: instruction_data.data(),
+    }
+}
+
+/// Build the SwapMultiPool instruction.
+pub fn build_swap_multi_pool_instruction(
+    program_id: Pubkey,
+    payer: Pubkey,
+    amm_config: Pubkey,
+    pdas: &MultiPoolPdas,
+    input_token_account: Pubkey,
+    output_token_account: Pubkey,
+    input_token_mint: Pubkey,
+    output_token_mint: Pubkey,
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> Instruction {
+    let accounts = raydium_cp_swap::accounts::SwapMultiPool {
+        payer,
+        authority: pdas.authority,
+        amm_config,
+        multi_pool_state: pdas.multi_pool_state,
+        multi_observation_state: pdas.multi_observation_state,
+        input_token_account,
+        output_token_account,
+        input_token_mint,
+        output_token_mint,
+        light_token_program: light_token_program_id(),
+        system_program: solana_sdk::system_program::ID,
+        light_token_cpi_authority: CPI_AUTHORITY_PDA,
+    };
+
+    let instruction_data = raydium_cp_swap::instruction::SwapMultiPool {
+        amount_in,
+        minimum_amount_out,
+    };
+
+    Instruction {
+        program_id,
+        accounts: [
+            accounts.to_account_metas(None),
+            pdas.vaults
+                .iter()
+                .map(|vault| AccountMeta::new(*vault, false))
+                .collect(),
+        ]
+        .concat(),
+        data: instruction_data.data(),
+    }
+}
+
+/// Remaining accounts of multi-token pool deposits and withdrawals: all
+/// vaults, then all mints, then all owner token accounts.
+fn multi_pool_token_accounts(
+    pdas: &MultiPoolPdas,
+    mints: &[Pubkey],
+    owner_token_accounts: &[Pubkey],
+) -> Vec<AccountMeta> {
+    pdas.vaults
+        .iter()
+        .map(|vault| AccountMeta::new(*vault, false))
+        .chain(mints.iter().map(|mint| AccountMeta::new_readonly(*mint, false)))
+        .chain(
+            owner_token_accounts
+                .iter()
+                .map(|account| AccountMeta::new(*account, false)),
+        )
+        .collect()
+}
+
+/// Build the DepositMultiPool instruction.
+pub fn build_deposit_multi_pool_instruction(
+    program_id: Pubkey,
+    owner: Pubkey,
+    pdas: &MultiPoolPdas,
+    mints: &[Pubkey],
+    owner_token_accounts: &[Pubkey],
+    lp_token_amount: u64,
+    maximum_token_amounts: Vec<u64>,
+) -> Instruction {
+    let accounts = raydium_cp_swap::accounts::DepositMultiPool {
+        owner,
+        authority: pdas.authority,
+        multi_pool_state: pdas.multi_pool_state,
+        multi_observation_state: pdas.multi_observation_state,
+        owner_lp_token: pdas.creator_lp_token,
+        lp_mint: pdas.lp_mint,
+        light_token_program: light_token_program_id(),
+        system_program: solana_sdk::system_program::ID,
+        light_token_cpi_authority: CPI_AUTHORITY_PDA,
+    };
+
+    let instruction_data = raydium_cp_swap::instruction::DepositMultiPool {
+        lp_token_amount,
+        maximum_token_amounts,
+    };
+
+    Instruction {
+        program_id,
+        accounts: [
+            accounts.to_account_metas(None),
+            multi_pool_token_accounts(pdas, mints, owner_token_accounts),
+        ]
+        .concat(),
+        data: instruction_data.data(),
+    }
+}
+
+/// Build the WithdrawMultiPool instruction.
+pub fn build_withdraw_multi_pool_instruction(
+    program_id: Pubkey,
+    owner: Pubkey,
+    pdas: &MultiPoolPdas,
+    mints: &[Pubkey],
+    owner_token_accounts: &[Pubkey],
+    lp_token_amount: u64,
+    minimum_token_amounts: Vec<u64>,
+) -> Instruction {
+    let accounts = raydium_cp_swap::accounts::WithdrawMultiPool {
+        owner,
+        authority: pdas.authority,
+        multi_pool_state: pdas.multi_pool_state,
+        multi_observation_state: pdas.multi_observation_state,
+        owner_lp_token: pdas.creator_lp_token,
+        lp_mint: pdas.lp_mint,
+        light_token_program: light_token_program_id(),
+        system_program: solana_sdk::system_program::ID,
+        light_token_cpi_authority: CPI_AUTHORITY_PDA,
+    };
+
+    let instruction_data = raydium_cp_swap::instruction::WithdrawMultiPool {
+        lp_token_amount,
+        minimum_token_amounts,
+    };
+
+    Instruction {
+        program_id,
+        accounts: [
+            accounts.to_account_metas(None),
+            multi_pool_token_accounts(pdas, mints, owner_token_accounts),
+        ]
+        .concat(),
+        data: instruction_data.data(),
+    }
+}
+
+/// Build the UpdateMultiPoolStatus instruction, signed by the admin.
+pub fn build_update_multi_pool_status_instruction(
+    program_id: Pubkey,
+    pdas: &MultiPoolPdas,
+    status: u8,
+) -> Instruction {
+    let accounts = raydium_cp_swap::accounts::UpdateMultiPoolStatus {
+        authority: get_admin_keypair().pubkey(),
+        multi_pool_state: pdas.multi_pool_state,
+    };
+
+    let instruction_data = raydium_cp_swap::instruction::UpdateMultiPoolStatus { status };
+
+    Instruction {
+        program_id,
+        accounts: accounts.to_account_metas(None),
+        data: instruction_data.data(),
+    }
+}
+
+/// Read the multi-token pool state account.
+pub async fn get_multi_pool_state(
+    rpc: &mut LightProgramTest,
+    multi_pool_state: Pubkey,
+) -> MultiPoolState {
+    let account = rpc.get_account(multi_pool_state).await.unwrap().unwrap();
+    MultiPoolState::deserialize(&mut &account.data[8..]).unwrap()
+}
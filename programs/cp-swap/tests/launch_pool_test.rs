@@ -0,0 +1,180 @@
+/// Integration tests for launch pools and their migration.
+use light_client::interface::{get_create_accounts_proof, CreateAccountsProofInput};
+use light_program_test::Rpc;
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+
+mod helpers;
+use helpers::*;
+
+const TOKEN_SUPPLY: u64 = 1_000_000;
+const VIRTUAL_QUOTE_RESERVE: u64 = 30_000;
+const MIGRATION_MARKET_CAP: u64 = 400_000;
+
+#[tokio::test]
+async fn test_migrate_after_pair_pool_front_run() {
+    let program_id = raydium_cp_swap::ID;
+    let mut env = setup_test_environment(program_id).await;
+
+    let creator = Keypair::new();
+    let trader = Keypair::new();
+    for owner in [&creator, &trader] {
+        env.rpc
+            .airdrop_lamports(&owner.pubkey(), 100_000_000_000)
+            .await
+            .unwrap();
+    }
+    let admin = get_admin_keypair();
+    env.rpc
+        .airdrop_lamports(&admin.pubkey(), 10_000_000_000)
+        .await
+        .unwrap();
+
+    let (token_mint, token_atas, _) = setup_create_mint(
+        &mut env.rpc,
+        &env.payer,
+        env.payer.pubkey(),
+        9,
+        vec![(TOKEN_SUPPLY, creator.pubkey()), (0, trader.pubkey())],
+    )
+    .await;
+    clear_mint_authority(&mut env.rpc, token_mint).await;
+    let (creator_token, trader_token) = (token_atas[0], token_atas[1]);
+    let (quote_mint, quote_atas, _) = setup_create_mint(
+        &mut env.rpc,
+        &env.payer,
+        env.payer.pubkey(),
+        9,
+        vec![(1_000_000, trader.pubkey())],
+    )
+    .await;
+    let trader_quote = quote_atas[0];
+
+    let amm_config = create_amm_config(&mut env.rpc, &env.payer, &admin, program_id, 20).await;
+    setup_create_pool_fee_account(&mut env.rpc, &env.payer.pubkey());
+    let launch = derive_launch_pdas(&program_id, &amm_config, &token_mint, &quote_mint);
+
+    // Create the launch
+    let proof_result = get_create_accounts_proof(
+        &env.rpc,
+        &program_id,
+        vec![CreateAccountsProofInput::pda(launch.launch_pool)],
+    )
+    .await
+    .unwrap();
+    let create_ix = build_create_launch_pool_instruction(
+        program_id,
+        creator.pubkey(),
+        amm_config,
+        &launch,
+        token_mint,
+        quote_mint,
+        creator_token,
+        env.config_pda,
+        &proof_result,
+        VIRTUAL_QUOTE_RESERVE,
+        MIGRATION_MARKET_CAP,
+    );
+    env.rpc
+        .create_and_send_transaction(&[create_ix], &creator.pubkey(), &[&creator])
+        .await
+        .expect("Create launch pool should succeed");
+
+    let buy = |amount_in: u64| {
+        build_swap_launch_pool_instruction(
+            program_id,
+            trader.pubkey(),
+            amm_config,
+            &launch,
+            token_mint,
+            quote_mint,
+            trader_token,
+            trader_quote,
+            true,
+            amount_in,
+            1,
+        )
+    };
+    env.rpc
+        .create_and_send_transaction(&[buy(10_000)], &trader.pubkey(), &[&trader])
+        .await
+        .expect("Buy should succeed");
+
+    // The trader opens a standard pool for the same config and pair first
+    let (token_0_mint, token_1_mint, trader_token_0, trader_token_1) = if token_mint < quote_mint {
+        (token_mint, quote_mint, trader_token, trader_quote)
+    } else {
+        (quote_mint, token_mint, trader_quote, trader_token)
+    };
+    let tokens = TokenSetup {
+        token_0_mint,
+        token_1_mint,
+        token_0_mint_signer: Default::default(),
+        token_1_mint_signer: Default::default(),
+        creator_token_0: trader_token_0,
+        creator_token_1: trader_token_1,
+    };
+    let pair_pdas = derive_amm_pdas(
+        &program_id,
+        &amm_config,
+        &token_0_mint,
+        &token_1_mint,
+        &trader.pubkey(),
+    );
+    let proof_result = get_pool_create_accounts_proof(&env.rpc, &program_id, &pair_pdas).await;
+    let init_ix = build_initialize_instruction(
+        program_id,
+        trader.pubkey(),
+        amm_config,
+        &pair_pdas,
+        &tokens,
+        env.config_pda,
+        &proof_result,
+        1_000,
+        1_000,
+        0,
+    );
+    env.rpc
+        .create_and_send_transaction(&[init_ix], &trader.pubkey(), &[&trader])
+        .await
+        .expect("Initialize should succeed");
+    assert_ne!(pair_pdas.pool_state, launch.pool.pool_state);
+
+    // (30_000 + 85_000) quote against ~261_000 tokens is past the market cap
+    env.rpc
+        .create_and_send_transaction(&[buy(75_000)], &trader.pubkey(), &[&trader])
+        .await
+        .expect("Buy should succeed");
+    env.rpc
+        .create_and_send_transaction(&[buy(1_000)], &trader.pubkey(), &[&trader])
+        .await
+        .expect_err("Buying past the migration market cap should fail");
+
+    let proof_result = get_pool_create_accounts_proof(&env.rpc, &program_id, &launch.pool).await;
+    let migrate_ix = build_migrate_launch_pool_instruction(
+        program_id,
+        env.payer.pubkey(),
+        amm_config,
+        &launch,
+        token_0_mint,
+        token_1_mint,
+        env.config_pda,
+        &proof_result,
+    );
+    env.rpc
+        .create_and_send_transaction(&[migrate_ix], &env.payer.pubkey(), &[&env.payer])
+        .await
+        .expect("Migrate should succeed next to the pair pool");
+
+    assert_pool_initialized(&mut env.rpc, &launch.pool).await;
+    let quote_vault = if token_0_mint == quote_mint {
+        launch.pool.token_0_vault
+    } else {
+        launch.pool.token_1_vault
+    };
+    assert_eq!(
+        get_token_balance(&mut env.rpc, quote_vault).await,
+        85_000,
+        "All paid in quote should move into the migrated pool"
+    );
+}
@@ -0,0 +1,340 @@
+/// Integration tests for multi-token pools.
+use light_program_test::Rpc;
+use raydium_cp_swap::curve::{CurveCalculator, FeeRates, SwapCurve, TradeDirection};
+use raydium_cp_swap::states::PoolStatusBitIndex;
+use solana_instruction::Instruction;
+use solana_keypair::Keypair;
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+
+mod helpers;
+use helpers::*;
+
+const TOKEN_COUNT: usize = 3;
+const INITIAL_BALANCE: u64 = 10_000_000_000;
+const POOL_AMOUNT: u64 = 1_000_000_000;
+const AMOUNT_IN: u64 = 100_000_000;
+const TRADE_FEE_RATES: [u64; TOKEN_COUNT] = [2500, 3000, 5000];
+
+struct MultiPoolSetup {
+    env: TestEnv,
+    creator: Keypair,
+    amm_config: Pubkey,
+    mints: Vec<Pubkey>,
+    creator_tokens: Vec<Pubkey>,
+    pdas: MultiPoolPdas,
+}
+
+impl MultiPoolSetup {
+    fn swap_instruction(
+        &self,
+        input: usize,
+        output: usize,
+        minimum_amount_out: u64,
+    ) -> Instruction {
+        build_swap_multi_pool_instruction(
+            raydium_cp_swap::ID,
+            self.creator.pubkey(),
+            self.amm_config,
+            &self.pdas,
+            self.creator_tokens[input],
+            self.creator_tokens[output],
+            self.mints[input],
+            self.mints[output],
+            AMOUNT_IN,
+            minimum_amount_out,
+        )
+    }
+
+    async fn vault_balances(&mut self) -> Vec<u64> {
+        let mut balances = Vec::with_capacity(TOKEN_COUNT);
+        for vault in &self.pdas.vaults {
+            balances.push(get_token_balance(&mut self.env.rpc, *vault).await);
+        }
+        balances
+    }
+
+    async fn creator_balances(&mut self) -> Vec<u64> {
+        let mut balances = Vec::with_capacity(TOKEN_COUNT);
+        for account in &self.creator_tokens {
+            balances.push(get_token_balance(&mut self.env.rpc, *account).await);
+        }
+        balances
+    }
+}
+
+/// A pool of three tokens with `POOL_AMOUNT` of each.
+async fn setup_multi_pool() -> MultiPoolSetup {
+    let program_id = raydium_cp_swap::ID;
+    let mut env = setup_test_environment(program_id).await;
+
+    let creator = Keypair::new();
+    env.rpc
+        .airdrop_lamports(&creator.pubkey(), 100_000_000_000)
+        .await
+        .unwrap();
+    let admin = get_admin_keypair();
+    env.rpc
+        .airdrop_lamports(&admin.pubkey(), 10_000_000_000)
+        .await
+        .unwrap();
+
+    let mut tokens = Vec::with_capacity(TOKEN_COUNT);
+    for _ in 0..TOKEN_COUNT {
+        let (mint, atas, _) = setup_create_mint(
+            &mut env.rpc,
+            &env.payer,
+            env.payer.pubkey(),
+            9,
+            vec![(INITIAL_BALANCE, creator.pubkey())],
+        )
+        .await;
+        tokens.push((mint, atas[0]));
+    }
+    tokens.sort();
+    let (mints, creator_tokens): (Vec<_>, Vec<_>) = tokens.into_iter().unzip();
+
+    let amm_config = create_amm_config(&mut env.rpc, &env.payer, &admin, program_id, 50).await;
+    setup_create_pool_fee_account(&mut env.rpc, &env.payer.pubkey());
+
+    let pool_base = Keypair::new();
+    let pdas = derive_multi_pool_pdas(
+        &program_id,
+        &amm_config,
+        &pool_base.pubkey(),
+        &mints,
+        &creator.pubkey(),
+    );
+    let proof_result = get_multi_pool_create_accounts_proof(&env.rpc, &program_id, &pdas).await;
+    let init_ix = build_initialize_multi_pool_instruction(
+        program_id,
+        creator.pubkey(),
+        amm_config,
+        pool_base.pubkey(),
+        &pdas,
+        &mints,
+        &creator_tokens,
+        env.config_pda,
+        &proof_result,
+        vec![POOL_AMOUNT; TOKEN_COUNT],
+        TRADE_FEE_RATES.to_vec(),
+    );
+    env.rpc
+        .create_and_send_transaction(&[init_ix], &creator.pubkey(), &[&creator, &pool_base])
+        .await
+        .expect("Initialize multi pool should succeed");
+
+    MultiPoolSetup {
+        env,
+        creator,
+        amm_config,
+        mints,
+        creator_tokens,
+        pdas,
+    }
+}
+
+async fn update_multi_pool_status(setup: &mut MultiPoolSetup, status: u8) {
+    let admin = get_admin_keypair();
+    let status_ix =
+        build_update_multi_pool_status_instruction(raydium_cp_swap::ID, &setup.pdas, status);
+    setup
+        .env
+        .rpc
+        .create_and_send_transaction(
+            &[status_ix],
+            &setup.env.payer.pubkey(),
+            &[&setup.env.payer, &admin],
+        )
+        .await
+        .expect("Update multi pool status should succeed");
+}
+
+#[tokio::test]
+async fn test_multi_pool_lifecycle() {
+    let program_id = raydium_cp_swap::ID;
+    let mut setup = setup_multi_pool().await;
+
+    // Initialize
+    assert_eq!(setup.vault_balances().await, vec![POOL_AMOUNT; TOKEN_COUNT]);
+    let pool_state = get_multi_pool_state(&mut setup.env.rpc, setup.pdas.multi_pool_state).await;
+    assert_eq!(pool_state.token_count as usize, TOKEN_COUNT);
+    assert_eq!(&pool_state.trade_fee_rates[..TOKEN_COUNT], &TRADE_FEE_RATES);
+    assert_eq!(&pool_state.mint_decimals[..TOKEN_COUNT], &[9; TOKEN_COUNT]);
+    // Equal reserves, the geometric mean is about the reserve. 100 lp stay locked.
+    let lp_supply = pool_state.lp_supply;
+    assert!(lp_supply.abs_diff(POOL_AMOUNT) <= 1);
+    assert_eq!(
+        get_token_balance(&mut setup.env.rpc, setup.pdas.creator_lp_token).await,
+        lp_supply - 100
+    );
+
+    // Swap token 0 for token 2, the input trade fee rate applies
+    let amm_config = get_amm_config(&mut setup.env.rpc, setup.amm_config).await;
+    let result = CurveCalculator::swap_base_input(
+        &SwapCurve::ConstantProduct,
+        TradeDirection::ZeroForOne,
+        AMOUNT_IN as u128,
+        POOL_AMOUNT as u128,
+        POOL_AMOUNT as u128,
+        &FeeRates {
+            trade_fee_rate: TRADE_FEE_RATES[0],
+            protocol_fee_rate: amm_config.protocol_fee_rate,
+            fund_fee_rate: amm_config.fund_fee_rate,
+            creator_fee_rate: 0,
+        },
+    )
+    .unwrap();
+    let amount_out = result.destination_amount_swapped as u64;
+    let balances_before = setup.creator_balances().await;
+    let swap_ix = setup.swap_instruction(0, 2, amount_out);
+    setup
+        .env
+        .rpc
+        .create_and_send_transaction(&[swap_ix], &setup.creator.pubkey(), &[&setup.creator])
+        .await
+        .expect("Swap multi pool should succeed");
+    assert_eq!(
+        setup.creator_balances().await,
+        vec![
+            balances_before[0] - AMOUNT_IN,
+            balances_before[1],
+            balances_before[2] + amount_out,
+        ]
+    );
+    assert_eq!(
+        setup.vault_balances().await,
+        vec![
+            POOL_AMOUNT + AMOUNT_IN,
+            POOL_AMOUNT,
+            POOL_AMOUNT - amount_out
+        ]
+    );
+
+    // Protocol and fund fees accrue in the input token only
+    let pool_state = get_multi_pool_state(&mut setup.env.rpc, setup.pdas.multi_pool_state).await;
+    assert!(result.protocol_fee > 0 && result.fund_fee > 0);
+    assert_eq!(
+        &pool_state.protocol_fees[..TOKEN_COUNT],
+        &[result.protocol_fee as u64, 0, 0]
+    );
+    assert_eq!(
+        &pool_state.fund_fees[..TOKEN_COUNT],
+        &[result.fund_fee as u64, 0, 0]
+    );
+
+    // Deposit in proportion to the reserves without the owed fees
+    let lp_token_amount = POOL_AMOUNT / 10;
+    let vault_amounts = pool_state.vault_amounts_without_fee(&setup.vault_balances().await);
+    let deposit_amounts: Vec<u64> = vault_amounts
+        .iter()
+        .map(|amount| {
+            (u128::from(lp_token_amount) * u128::from(*amount))
+                .div_ceil(u128::from(pool_state.lp_supply)) as u64
+        })
+        .collect();
+    let balances_before = setup.creator_balances().await;
+    let lp_balance_before =
+        get_token_balance(&mut setup.env.rpc, setup.pdas.creator_lp_token).await;
+    let deposit_ix = build_deposit_multi_pool_instruction(
+        program_id,
+        setup.creator.pubkey(),
+        &setup.pdas,
+        &setup.mints,
+        &setup.creator_tokens,
+        lp_token_amount,
+        deposit_amounts.clone(),
+    );
+    setup
+        .env
+        .rpc
+        .create_and_send_transaction(&[deposit_ix], &setup.creator.pubkey(), &[&setup.creator])
+        .await
+        .expect("Deposit multi pool should succeed");
+    let balances_after = setup.creator_balances().await;
+    for i in 0..TOKEN_COUNT {
+        assert_eq!(balances_before[i] - balances_after[i], deposit_amounts[i]);
+    }
+    assert_deposit_succeeded(
+        &mut setup.env.rpc,
+        setup.pdas.creator_lp_token,
+        lp_balance_before,
+        lp_token_amount,
+    )
+    .await;
+
+    // Withdrawing the same lp returns no more than was deposited
+    let withdraw_ix = build_withdraw_multi_pool_instruction(
+        program_id,
+        setup.creator.pubkey(),
+        &setup.pdas,
+        &setup.mints,
+        &setup.creator_tokens,
+        lp_token_amount,
+        vec![0; TOKEN_COUNT],
+    );
+    setup
+        .env
+        .rpc
+        .create_and_send_transaction(&[withdraw_ix], &setup.creator.pubkey(), &[&setup.creator])
+        .await
+        .expect("Withdraw multi pool should succeed");
+    let balances_withdrawn = setup.creator_balances().await;
+    for i in 0..TOKEN_COUNT {
+        assert!(balances_withdrawn[i] > balances_after[i]);
+        assert!(balances_withdrawn[i] <= balances_before[i]);
+    }
+    assert_withdraw_succeeded(
+        &mut setup.env.rpc,
+        setup.pdas.creator_lp_token,
+        lp_balance_before + lp_token_amount,
+        lp_token_amount,
+    )
+    .await;
+    let pool_state = get_multi_pool_state(&mut setup.env.rpc, setup.pdas.multi_pool_state).await;
+    assert_eq!(pool_state.lp_supply, lp_supply);
+}
+
+#[tokio::test]
+async fn test_multi_pool_swap_disabled_by_admin() {
+    let program_id = raydium_cp_swap::ID;
+    let mut setup = setup_multi_pool().await;
+
+    update_multi_pool_status(&mut setup, 1 << PoolStatusBitIndex::Swap as u8).await;
+    let balances_before = setup.creator_balances().await;
+    let swap_ix = setup.swap_instruction(1, 0, 0);
+    let result = setup
+        .env
+        .rpc
+        .create_and_send_transaction(&[swap_ix], &setup.creator.pubkey(), &[&setup.creator])
+        .await;
+    assert!(result.is_err(), "Swap on a disabled multi pool should fail");
+    assert_eq!(setup.creator_balances().await, balances_before);
+
+    // Deposits are still allowed
+    let deposit_ix = build_deposit_multi_pool_instruction(
+        program_id,
+        setup.creator.pubkey(),
+        &setup.pdas,
+        &setup.mints,
+        &setup.creator_tokens,
+        POOL_AMOUNT / 10,
+        vec![u64::MAX; TOKEN_COUNT],
+    );
+    setup
+        .env
+        .rpc
+        .create_and_send_transaction(&[deposit_ix], &setup.creator.pubkey(), &[&setup.creator])
+        .await
+        .expect("Deposit with only swaps disabled should succeed");
+
+    // Enabled again
+    update_multi_pool_status(&mut setup, 0).await;
+    let swap_ix = setup.swap_instruction(1, 0, 0);
+    setup
+        .env
+        .rpc
+        .create_and_send_transaction(&[swap_ix], &setup.creator.pubkey(), &[&setup.creator])
+        .await
+        .expect("Swap on a re-enabled multi pool should succeed");
+}
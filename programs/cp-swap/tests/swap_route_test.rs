@@ -0,0 +1,291 @@
+/// Integration tests for swapping through a route of pools.
+use light_program_test::Rpc;
+use raydium_cp_swap::curve::{CurveCalculator, TradeDirection};
+use solana_keypair::Keypair;
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+
+mod helpers;
+use helpers::*;
+
+const INITIAL_BALANCE: u64 = 10_000_000_000;
+const POOL_AMOUNT: u64 = 1_000_000_000;
+const AMOUNT_IN: u64 = 10_000_000;
+
+/// A route from mint `a` to mint `c` through an a/b pool and a b/c pool.
+struct RouteSetup {
+    env: TestEnv,
+    user: Keypair,
+    amm_config: Pubkey,
+    user_a: Pubkey,
+    user_b: Pubkey,
+    user_c: Pubkey,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+    ab_tokens: TokenSetup,
+    ab_pdas: AmmPdas,
+    bc_tokens: TokenSetup,
+    bc_pdas: AmmPdas,
+}
+
+impl RouteSetup {
+    fn hops(&self) -> [RouteHop<'_>; 2] {
+        [
+            RouteHop {
+                amm_config: self.amm_config,
+                pdas: &self.ab_pdas,
+                tokens: &self.ab_tokens,
+                is_token_0_input: self.ab_tokens.token_0_mint == self.mint_a,
+            },
+            RouteHop {
+                amm_config: self.amm_config,
+                pdas: &self.bc_pdas,
+                tokens: &self.bc_tokens,
+                is_token_0_input: self.bc_tokens.token_0_mint == self.mint_b,
+            },
+        ]
+    }
+}
+
+/// Pair two mints in pool order, with the user token accounts.
+fn pair_tokens(mint_x: Pubkey, user_x: Pubkey, mint_y: Pubkey, user_y: Pubkey) -> TokenSetup {
+    let ((token_0_mint, creator_token_0), (token_1_mint, creator_token_1)) = if mint_x < mint_y {
+        ((mint_x, user_x), (mint_y, user_y))
+    } else {
+        ((mint_y, user_y), (mint_x, user_x))
+    };
+    TokenSetup {
+        token_0_mint,
+        token_1_mint,
+        token_0_mint_signer: Default::default(),
+        token_1_mint_signer: Default::default(),
+        creator_token_0,
+        creator_token_1,
+    }
+}
+
+async fn setup_route() -> RouteSetup {
+    let program_id = raydium_cp_swap::ID;
+    let mut env = setup_test_environment(program_id).await;
+
+    let user = Keypair::new();
+    env.rpc
+        .airdrop_lamports(&user.pubkey(), 100_000_000_000)
+        .await
+        .unwrap();
+    let admin = get_admin_keypair();
+    env.rpc
+        .airdrop_lamports(&admin.pubkey(), 10_000_000_000)
+        .await
+        .unwrap();
+
+    let mut mints = Vec::new();
+    for _ in 0..3 {
+        let (mint, atas, _) = setup_create_mint(
+            &mut env.rpc,
+            &env.payer,
+            env.payer.pubkey(),
+            9,
+            vec![(INITIAL_BALANCE, user.pubkey())],
+        )
+        .await;
+        mints.push((mint, atas[0]));
+    }
+    let [(mint_a, user_a), (mint_b, user_b), (mint_c, user_c)] = mints[..] else {
+        unreachable!()
+    };
+
+    let amm_config = create_amm_config(&mut env.rpc, &env.payer, &admin, program_id, 40).await;
+    setup_create_pool_fee_account(&mut env.rpc, &env.payer.pubkey());
+
+    let ab_tokens = pair_tokens(mint_a, user_a, mint_b, user_b);
+    let bc_tokens = pair_tokens(mint_b, user_b, mint_c, user_c);
+    let mut pools = Vec::new();
+    for tokens in [&ab_tokens, &bc_tokens] {
+        let pdas = derive_amm_pdas(
+            &program_id,
+            &amm_config,
+            &tokens.token_0_mint,
+            &tokens.token_1_mint,
+            &user.pubkey(),
+        );
+        let proof_result = get_pool_create_accounts_proof(&env.rpc, &program_id, &pdas).await;
+        let init_ix = build_initialize_instruction(
+            program_id,
+            user.pubkey(),
+            amm_config,
+            &pdas,
+            tokens,
+            env.config_pda,
+            &proof_result,
+            POOL_AMOUNT,
+            POOL_AMOUNT,
+            0,
+        );
+        env.rpc
+            .create_and_send_transaction(&[init_ix], &user.pubkey(), &[&user])
+            .await
+            .unwrap();
+        assert_pool_initialized(&mut env.rpc, &pdas).await;
+        pools.push(pdas);
+    }
+    let bc_pdas = pools.pop().unwrap();
+    let ab_pdas = pools.pop().unwrap();
+
+    RouteSetup {
+        env,
+        user,
+        amm_config,
+        user_a,
+        user_b,
+        user_c,
+        mint_a,
+        mint_b,
+        ab_tokens,
+        ab_pdas,
+        bc_tokens,
+        bc_pdas,
+    }
+}
+
+/// Output of a single pool swap, computed off chain.
+async fn expected_amount_out(setup: &mut RouteSetup, hop: usize, amount_in: u64) -> u64 {
+    let hops = setup.hops();
+    let hop = &hops[hop];
+    let (pool_state, is_token_0_input) = (hop.pdas.pool_state, hop.is_token_0_input);
+    let pool_state = get_pool_state(&mut setup.env.rpc, pool_state).await;
+    let amm_config = get_amm_config(&mut setup.env.rpc, setup.amm_config).await;
+    let trade_direction = if is_token_0_input {
+        TradeDirection::ZeroForOne
+    } else {
+        TradeDirection::OneForZero
+    };
+    CurveCalculator::swap_base_input(
+        &pool_state.swap_curve(&amm_config).unwrap(),
+        trade_direction,
+        amount_in as u128,
+        POOL_AMOUNT as u128,
+        POOL_AMOUNT as u128,
+        &amm_config.fee_rates(amm_config.trade_fee_rate),
+    )
+    .unwrap()
+    .destination_amount_swapped as u64
+}
+
+#[tokio::test]
+async fn test_swap_route_through_two_pools() {
+    let program_id = raydium_cp_swap::ID;
+    let mut setup = setup_route().await;
+
+    let intermediate_amount = expected_amount_out(&mut setup, 0, AMOUNT_IN).await;
+    let amount_out = expected_amount_out(&mut setup, 1, intermediate_amount).await;
+    let rpc = &mut setup.env.rpc;
+    let user_a_before = get_token_balance(rpc, setup.user_a).await;
+    let user_b_before = get_token_balance(rpc, setup.user_b).await;
+    let user_c_before = get_token_balance(rpc, setup.user_c).await;
+
+    let route_ix = build_swap_route_instruction(
+        program_id,
+        setup.user.pubkey(),
+        setup.user_a,
+        setup.user_c,
+        setup.mint_a,
+        &setup.hops(),
+        AMOUNT_IN,
+        amount_out,
+    );
+    let rpc = &mut setup.env.rpc;
+    rpc.create_and_send_transaction(&[route_ix], &setup.user.pubkey(), &[&setup.user])
+        .await
+        .expect("Swap route should succeed");
+
+    assert_eq!(
+        get_token_balance(rpc, setup.user_a).await,
+        user_a_before - AMOUNT_IN
+    );
+    // The intermediate token goes from pool to pool, not through the user
+    assert_eq!(get_token_balance(rpc, setup.user_b).await, user_b_before);
+    assert_eq!(
+        get_token_balance(rpc, setup.user_c).await,
+        user_c_before + amount_out
+    );
+
+    let hops = setup.hops();
+    let ab_b_vault = if hops[0].is_token_0_input {
+        setup.ab_pdas.token_1_vault
+    } else {
+        setup.ab_pdas.token_0_vault
+    };
+    let bc_b_vault = if hops[1].is_token_0_input {
+        setup.bc_pdas.token_0_vault
+    } else {
+        setup.bc_pdas.token_1_vault
+    };
+    let rpc = &mut setup.env.rpc;
+    assert_eq!(
+        get_token_balance(rpc, ab_b_vault).await,
+        POOL_AMOUNT - intermediate_amount
+    );
+    assert_eq!(
+        get_token_balance(rpc, bc_b_vault).await,
+        POOL_AMOUNT + intermediate_amount
+    );
+}
+
+#[tokio::test]
+async fn test_swap_route_below_minimum_out_fails() {
+    let program_id = raydium_cp_swap::ID;
+    let mut setup = setup_route().await;
+
+    let intermediate_amount = expected_amount_out(&mut setup, 0, AMOUNT_IN).await;
+    let amount_out = expected_amount_out(&mut setup, 1, intermediate_amount).await;
+    let user_a_before = get_token_balance(&mut setup.env.rpc, setup.user_a).await;
+
+    let route_ix = build_swap_route_instruction(
+        program_id,
+        setup.user.pubkey(),
+        setup.user_a,
+        setup.user_c,
+        setup.mint_a,
+        &setup.hops(),
+        AMOUNT_IN,
+        amount_out + 1,
+    );
+    let rpc = &mut setup.env.rpc;
+    let result = rpc
+        .create_and_send_transaction(&[route_ix], &setup.user.pubkey(), &[&setup.user])
+        .await;
+    assert!(
+        result.is_err(),
+        "Swap route below the minimum out should fail"
+    );
+    assert_eq!(get_token_balance(rpc, setup.user_a).await, user_a_before);
+}
+
+#[tokio::test]
+async fn test_swap_route_out_of_order_fails() {
+    let program_id = raydium_cp_swap::ID;
+    let mut setup = setup_route().await;
+
+    // The b/c pool doesn't take token a
+    let [ab_hop, bc_hop] = setup.hops();
+    let route_ix = build_swap_route_instruction(
+        program_id,
+        setup.user.pubkey(),
+        setup.user_a,
+        setup.user_c,
+        setup.mint_a,
+        &[bc_hop, ab_hop],
+        AMOUNT_IN,
+        0,
+    );
+    let result = setup
+        .env
+        .rpc
+        .create_and_send_transaction(&[route_ix], &setup.user.pubkey(), &[&setup.user])
+        .await;
+    assert!(
+        result.is_err(),
+        "Swap route with hops out of order should fail"
+    );
+}
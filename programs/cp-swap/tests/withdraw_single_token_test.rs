@@ -0,0 +1,128 @@
+/// Integration tests for withdrawing liquidity as a single token.
+use light_program_test::{utils::assert::assert_rpc_error, Rpc};
+use raydium_cp_swap::{
+    curve::{CurveCalculator, RoundDirection, TradeDirection},
+    error::ErrorCode,
+};
+use solana_signer::Signer;
+
+mod helpers;
+use helpers::*;
+
+const POOL_AMOUNT: u64 = 500_000;
+
+#[tokio::test]
+async fn test_withdraw_single_token() {
+    let program_id = raydium_cp_swap::ID;
+    let mut setup = setup_pool_environment(program_id, 80).await;
+
+    let proof_result =
+        get_pool_create_accounts_proof(&setup.env.rpc, &program_id, &setup.pdas).await;
+    let init_ix = build_initialize_instruction(
+        program_id,
+        setup.creator.pubkey(),
+        setup.amm_config,
+        &setup.pdas,
+        &setup.tokens,
+        setup.env.config_pda,
+        &proof_result,
+        POOL_AMOUNT,
+        POOL_AMOUNT,
+        0,
+    );
+    setup
+        .env
+        .rpc
+        .create_and_send_transaction(&[init_ix], &setup.creator.pubkey(), &[&setup.creator])
+        .await
+        .unwrap();
+
+    // The proportional token 1, plus the token 0 share swapped back into the
+    // pool left after the withdraw
+    let pool_state = get_pool_state(&mut setup.env.rpc, setup.pdas.pool_state).await;
+    let amm_config = get_amm_config(&mut setup.env.rpc, setup.amm_config).await;
+    let curve = pool_state.swap_curve(&amm_config).unwrap();
+    let lp_token_amount =
+        get_token_balance(&mut setup.env.rpc, setup.pdas.creator_lp_token).await / 10;
+    let withdrawn = CurveCalculator::lp_tokens_to_trading_tokens(
+        &curve,
+        u128::from(lp_token_amount),
+        u128::from(pool_state.lp_supply),
+        u128::from(POOL_AMOUNT),
+        u128::from(POOL_AMOUNT),
+        RoundDirection::Floor,
+    )
+    .unwrap();
+    let swap = CurveCalculator::swap_base_input(
+        &curve,
+        TradeDirection::ZeroForOne,
+        withdrawn.token_0_amount,
+        u128::from(POOL_AMOUNT) - withdrawn.token_0_amount,
+        u128::from(POOL_AMOUNT) - withdrawn.token_1_amount,
+        &amm_config.fee_rates(amm_config.trade_fee_rate),
+    )
+    .unwrap();
+    assert!(swap.trade_fee > 0);
+    let amount_out = (withdrawn.token_1_amount + swap.destination_amount_swapped) as u64;
+    // Less than both sides at the pool price, the trade fee and price impact stay in the pool
+    assert!(amount_out < 2 * withdrawn.token_1_amount as u64);
+
+    let withdraw = |minimum_amount_out: u64| {
+        build_withdraw_single_token_instruction(
+            program_id,
+            setup.creator.pubkey(),
+            setup.amm_config,
+            &setup.pdas,
+            &setup.tokens,
+            setup.tokens.creator_token_1,
+            false,
+            lp_token_amount,
+            minimum_amount_out,
+        )
+    };
+
+    // One more than the amount out is rejected
+    let withdraw_ix = withdraw(amount_out + 1);
+    let result = setup
+        .env
+        .rpc
+        .create_and_send_transaction(&[withdraw_ix], &setup.creator.pubkey(), &[&setup.creator])
+        .await;
+    assert_rpc_error(result, 0, ErrorCode::ExceededSlippage.into()).unwrap();
+
+    let withdraw_ix = withdraw(amount_out);
+    let token_0_before = get_token_balance(&mut setup.env.rpc, setup.tokens.creator_token_0).await;
+    let token_1_before = get_token_balance(&mut setup.env.rpc, setup.tokens.creator_token_1).await;
+    let lp_before = get_token_balance(&mut setup.env.rpc, setup.pdas.creator_lp_token).await;
+    setup
+        .env
+        .rpc
+        .create_and_send_transaction(&[withdraw_ix], &setup.creator.pubkey(), &[&setup.creator])
+        .await
+        .expect("Withdraw single token should succeed");
+
+    assert_withdraw_succeeded(
+        &mut setup.env.rpc,
+        setup.pdas.creator_lp_token,
+        lp_before,
+        lp_token_amount,
+    )
+    .await;
+    assert_eq!(
+        get_token_balance(&mut setup.env.rpc, setup.tokens.creator_token_1).await,
+        token_1_before + amount_out
+    );
+    assert_eq!(
+        get_token_balance(&mut setup.env.rpc, setup.tokens.creator_token_0).await,
+        token_0_before
+    );
+    // All of token 0 stays in the pool
+    assert_eq!(
+        get_token_balance(&mut setup.env.rpc, setup.pdas.token_0_vault).await,
+        POOL_AMOUNT
+    );
+    assert_eq!(
+        get_token_balance(&mut setup.env.rpc, setup.pdas.token_1_vault).await,
+        POOL_AMOUNT - amount_out
+    );
+}
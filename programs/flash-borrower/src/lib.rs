@@ -0,0 +1,94 @@
+//! Borrower program for the flash swap tests of raydium-cp-swap. Its
+//! callback repays the amounts passed through the flash swap data.
+#![allow(deprecated)]
+
+use anchor_lang::prelude::*;
+use light_token::instruction::TransferInterfaceCpi;
+
+declare_id!("2pujBqgvjyNkzBSzBNCYTaXYdMSwMzMVyhYSAjbLfB18");
+
+/// The flash swap data this borrower expects
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct RepayArgs {
+    pub amount_0: u64,
+    pub amount_1: u64,
+    /// Decimals of both pool tokens
+    pub decimals: u8,
+}
+
+#[program]
+pub mod flash_borrower {
+    use super::*;
+
+    /// Called back by `flash_swap`, sends `RepayArgs` amounts from the sender
+    /// token accounts to the pool vaults
+    pub fn flash_swap_callback(
+        ctx: Context<FlashSwapCallback>,
+        _sender: Pubkey,
+        _amount_0_out: u64,
+        _amount_1_out: u64,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        let args = RepayArgs::try_from_slice(&data)?;
+        let accounts = &ctx.accounts;
+        for (amount, source, destination) in [
+            (
+                args.amount_0,
+                &accounts.sender_token_0,
+                &accounts.token_0_vault,
+            ),
+            (
+                args.amount_1,
+                &accounts.sender_token_1,
+                &accounts.token_1_vault,
+            ),
+        ] {
+            if amount == 0 {
+                continue;
+            }
+            TransferInterfaceCpi::new(
+                amount,
+                args.decimals,
+                source.to_account_info(),
+                destination.to_account_info(),
+                accounts.sender.to_account_info(),
+                accounts.sender.to_account_info(),
+                accounts.light_token_cpi_authority.to_account_info(),
+                accounts.system_program.to_account_info(),
+            )
+            .invoke()?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct FlashSwapCallback<'info> {
+    /// The user that started the flash swap, signs the repayment
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    /// CHECK: token_0 account of the sender
+    #[account(mut)]
+    pub sender_token_0: UncheckedAccount<'info>,
+
+    /// CHECK: the pool vault for token_0
+    #[account(mut)]
+    pub token_0_vault: UncheckedAccount<'info>,
+
+    /// CHECK: token_1 account of the sender
+    #[account(mut)]
+    pub sender_token_1: UncheckedAccount<'info>,
+
+    /// CHECK: the pool vault for token_1
+    #[account(mut)]
+    pub token_1_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Light Token program for CPI
+    pub light_token_program: UncheckedAccount<'info>,
+
+    /// CHECK: light_token CPI authority.
+    pub light_token_cpi_authority: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}